@@ -0,0 +1,116 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Expr, Ident, ItemFn};
+
+/// Arguments of the `notify_on_error` attribute
+struct Args {
+    worker: Option<Expr>,
+    args: Vec<Ident>,
+}
+
+/// Wrap a function returning a `Result` so any `Err` it returns (or, for
+/// synchronous functions, any panic) queues a notification on a `Worker`
+///
+/// The notification contains the function name, the error (or panic message),
+/// and the `Debug` representation of any arguments listed in `args(..)`,
+/// captured before the function body runs.
+///
+/// ```ignore
+/// #[notify_on_error(worker = &WORKER, args(customer_id))]
+/// async fn capture_payment(customer_id: u64, amount: u64) -> Result<(), PaymentError> {
+///     ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn notify_on_error(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = Args {
+        worker: None,
+        args: Vec::new(),
+    };
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("worker") {
+            args.worker = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("args") {
+            meta.parse_nested_meta(|arg| {
+                args.args.push(arg.path.require_ident()?.clone());
+                Ok(())
+            })
+        } else {
+            Err(meta.error("expected `worker = ..` or `args(..)`"))
+        }
+    });
+    parse_macro_input!(attr with parser);
+
+    let function = parse_macro_input!(item as ItemFn);
+    let Some(worker) = args.worker else {
+        return syn::Error::new_spanned(&function.sig, "missing `worker = ..` argument")
+            .to_compile_error()
+            .into();
+    };
+
+    expand(function, worker, args.args).into()
+}
+
+/// Wrap the body of a function, notifying on errors and panics
+fn expand(function: ItemFn, worker: Expr, args: Vec<Ident>) -> proc_macro2::TokenStream {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = function;
+    let name = sig.ident.to_string();
+    let output = match &sig.output {
+        syn::ReturnType::Default => quote!(()),
+        syn::ReturnType::Type(_, ty) => quote!(#ty),
+    };
+    let arg_names = args.iter().map(|arg| arg.to_string());
+
+    let capture_args = quote! {
+        let __dev_notify_args: ::std::vec::Vec<(&'static str, ::std::string::String)> =
+            ::std::vec![#((#arg_names, ::std::format!("{:?}", &#args))),*];
+    };
+    let notify_on_err = quote! {
+        if let ::std::result::Result::Err(error) = &__dev_notify_result {
+            ::dev_notify::__private::notify_error(&#worker, #name, __dev_notify_args, error);
+        }
+        __dev_notify_result
+    };
+
+    // Panics can only be caught around synchronous bodies
+    let body = if sig.asyncness.is_some() {
+        quote! {
+            #capture_args
+            let __dev_notify_result: #output = async move #block.await;
+            #notify_on_err
+        }
+    } else {
+        quote! {
+            #capture_args
+            let __dev_notify_result = ::std::panic::catch_unwind(
+                ::std::panic::AssertUnwindSafe(move || -> #output #block),
+            );
+            let __dev_notify_result = match __dev_notify_result {
+                ::std::result::Result::Ok(result) => result,
+                ::std::result::Result::Err(panic) => {
+                    ::dev_notify::__private::notify_panic(
+                        &#worker,
+                        #name,
+                        __dev_notify_args,
+                        &*panic,
+                    );
+                    ::std::panic::resume_unwind(panic)
+                }
+            };
+            #notify_on_err
+        }
+    };
+
+    quote! {
+        #(#attrs)*
+        #vis #sig {
+            #body
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{DeliveryReceipt, Error, Notification, Notifier};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A delivery attempt recorded in the audit log
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AuditEntry {
+    /// When delivery was attempted, like `2024-01-19 19:26:20.022233`
+    pub at: String,
+    /// The URL of the destination
+    pub url: String,
+    /// The notification, as sent
+    pub notification: Notification,
+    /// The HTTP status the destination responded with, if it responded
+    pub status: Option<u16>,
+    /// Why delivery failed, if it did
+    pub error: Option<String>,
+}
+impl AuditEntry {
+    /// Whether the notification was delivered
+    pub fn delivered(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// An append-only file recording every delivery attempt (JSON lines)
+///
+/// Like the spool, only destination URLs are written, never headers.
+pub(crate) struct AuditLog {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+impl AuditLog {
+    /// Create an `AuditLog` writing to a given file, created on first use
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Record a delivery attempt of a `Notification` to a given destination
+    pub(crate) fn record(
+        &self,
+        url: &str,
+        notification: &Notification,
+        result: &Result<DeliveryReceipt, Error>,
+    ) -> io::Result<()> {
+        let (status, error) = match result {
+            Ok(receipt) => (Some(receipt.status.as_u16()), None),
+            Err(Error::Http(e)) => (e.status().map(|s| s.as_u16()), Some(e.to_string())),
+            Err(e @ Error::Status { status, .. }) => (Some(status.as_u16()), Some(e.to_string())),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let mut line = serde_json::to_string(&AuditEntry {
+            at: crate::timestamp::now(),
+            url: url.to_string(),
+            notification: notification.clone(),
+            status,
+            error,
+        })?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())
+    }
+}
+
+/// Read every entry of an audit log, oldest first, skipping lines that can't be parsed
+fn read(path: &Path) -> io::Result<Vec<AuditEntry>> {
+    let file = fs::File::open(path)?;
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+impl Notifier {
+    /// Resend the notifications of an audit log matching a filter (e.g. those
+    /// that failed, or were sent during an incident), returning how many were
+    /// delivered
+    ///
+    /// Entries are resent through the named destination with the same URL (and
+    /// its headers), entries without one are skipped. Timestamps sort like
+    /// strings, so a range is `|e| e.at.as_str() >= since && e.at.as_str() < until`.
+    pub async fn replay_from<F>(&self, path: impl AsRef<Path>, filter: F) -> Result<usize, Error>
+    where
+        F: Fn(&AuditEntry) -> bool,
+    {
+        let entries = read(path.as_ref()).map_err(Error::AuditLog)?;
+
+        let mut delivered = 0;
+        for entry in entries.into_iter().filter(|entry| filter(entry)) {
+            let Some(destination) = self.destination_at(&entry.url) else {
+                continue;
+            };
+            if self.dispatch(entry.notification, destination).await.is_ok() {
+                delivered += 1;
+            }
+        }
+
+        Ok(delivered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockDestination;
+    use crate::{Notification, Notifier};
+
+    /// A test to make sure every delivery attempt is recorded and can be replayed
+    #[tokio::test]
+    async fn records_and_replays() {
+        let path = std::env::temp_dir().join(format!("dev-notify-audit-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let (slack, pagerduty) = (MockDestination::new(), MockDestination::new());
+        let notifier = Notifier::builder()
+            .audit_log(&path)
+            .destination("slack", slack.endpoint_at("mock://slack"))
+            .destination("pagerduty", pagerduty.endpoint_at("mock://pagerduty"))
+            .build()
+            .unwrap();
+
+        notifier.notify(Notification::new("Disk full")).await;
+        notifier.notify(Notification::new("CPU high")).await;
+        let replayed = notifier
+            .replay_from(&path, |entry| {
+                entry.url == "mock://pagerduty" && entry.notification.message == "Disk full"
+            })
+            .await
+            .unwrap();
+        assert_eq!(replayed, 1);
+        assert_eq!(pagerduty.len(), 3);
+        assert_eq!(slack.len(), 2);
+
+        // Replayed deliveries are recorded too
+        let audited = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(audited.lines().count(), 5);
+        assert!(notifier
+            .replay_from(&path, |entry| !entry.delivered())
+            .await
+            .is_ok());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(notifier.replay_from(&path, |_| true).await.is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::render::{fit_text, plain_text};
+use crate::{
+    ContextKind, Endpoint, Error, Formatter, Notification, OversizePolicy, RenderedPayload,
+    Severity,
+};
+use serde_json::{json, Value};
+
+/// The most characters in the title of a push
+const MAX_TITLE: usize = 250;
+
+/// The most characters in the body of a push, leaving room for the rest of
+/// it within the 4 KB APNs takes
+const MAX_BODY: usize = 3000;
+
+/// Renders notifications as Bark pushes, as `MessageFormat::Bark`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BarkFormatter {
+    policy: OversizePolicy,
+}
+impl BarkFormatter {
+    /// Create a `BarkFormatter` with the options of a destination
+    pub(crate) fn for_destination(destination: &Endpoint) -> Self {
+        Self {
+            policy: destination.oversize_policy(),
+        }
+    }
+}
+impl Formatter for BarkFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let payload = notification.clone().into_bark_payload(self.policy)?;
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into a Bark push, titled with
+    /// the message, to the device key set as the channel
+    ///
+    /// The destination is the push endpoint of the Bark server, like
+    /// `https://api.day.app/push`. Critical notifications ring even on silent
+    /// or do not disturb, errors break through focus modes, and the first link
+    /// in the context opens when the push is tapped.
+    pub(crate) fn into_bark_payload(self, policy: OversizePolicy) -> Result<Value, Error> {
+        let labels = self.labels.clone().unwrap_or_default();
+        let mut title = format!("{}: {}", labels.issue, self.message);
+        fit_text(&mut title, MAX_TITLE, policy)?;
+        let body = plain_text(&self, MAX_BODY, policy)?;
+
+        let level = match self.severity {
+            Severity::Critical => "critical",
+            Severity::Error => "timeSensitive",
+            Severity::Warning | Severity::Info => "active",
+            Severity::Debug => "passive",
+        };
+        let mut payload = json!({
+            "title": title,
+            "body": body,
+            "level": level,
+            "group": "dev-notify",
+        });
+        let link = self
+            .context
+            .iter()
+            .find(|ctx| matches!(ctx.kind, ContextKind::Link { .. }));
+        if let Some(link) = link {
+            payload["url"] = Value::String(link.value.clone());
+        }
+        if let Some(device_key) = self.channel {
+            payload["device_key"] = Value::String(device_key);
+        }
+
+        Ok(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, MessageFormat, Notification, OversizePolicy, Severity};
+    use serde_json::Value;
+
+    /// A test to make sure notifications map onto Bark pushes, leveled by severity
+    #[test]
+    fn can_parse_into_push() {
+        let mut notification = Notification::new("Deploy failed")
+            .with_context("Service", "api")
+            .with_channel("qLSDnAZVgXFjzBtwbu4X8A");
+        notification.timestamp = String::from("2024-01-19 19:26:20.022233");
+        notification
+            .context
+            .push(Context::link("Run", "https://ci.example.com/runs/7", "#7"));
+
+        let payload: Value = serde_json::from_str(
+            &notification
+                .clone()
+                .into_payload(MessageFormat::Bark, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(payload["device_key"], "qLSDnAZVgXFjzBtwbu4X8A");
+        assert_eq!(payload["title"], "Issue: Deploy failed");
+        assert_eq!(
+            payload["body"],
+            "Timestamp: 2024-01-19 19:26:20.022233\nService: api\nRun: #7 (https://ci.example.com/runs/7)"
+        );
+        assert_eq!(payload["url"], "https://ci.example.com/runs/7");
+        assert_eq!(payload["level"], "timeSensitive");
+
+        notification.severity = Severity::Critical;
+        let payload = notification
+            .clone()
+            .into_bark_payload(OversizePolicy::Reject)
+            .unwrap();
+        assert_eq!(payload["level"], "critical");
+
+        // Long attachments are cut to fit in a push
+        let payload = notification
+            .with_attachment("build.log", "x".repeat(5000))
+            .into_bark_payload(OversizePolicy::Truncate)
+            .unwrap();
+        assert!(payload["body"].as_str().unwrap().chars().count() <= 3000);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{DeliveryReceipt, Endpoint, Error, Notification, Notifier};
+use std::future::{poll_fn, Future};
+use std::task::Poll;
+
+impl Notifier {
+    /// Send many notifications to a given destination concurrently, at most
+    /// `concurrency` at a time, e.g. for backfill or replay jobs
+    ///
+    /// Each is sent like `send`, and the result of each is returned in the
+    /// order they were given. Runs on the current task, without spawning.
+    pub async fn send_all<I>(
+        &self,
+        notifications: I,
+        destination: &Endpoint,
+        concurrency: usize,
+    ) -> Vec<Result<Option<DeliveryReceipt>, Error>>
+    where
+        I: IntoIterator<Item = Notification>,
+    {
+        let concurrency = concurrency.max(1);
+        let send = |notification| Box::pin(self.send(notification, destination));
+
+        let mut pending = notifications.into_iter().enumerate();
+        let mut in_flight = Vec::with_capacity(concurrency);
+        let mut results = Vec::new();
+        poll_fn(|cx| loop {
+            while in_flight.len() < concurrency {
+                let Some((idx, notification)) = pending.next() else {
+                    break;
+                };
+                results.push(None);
+                in_flight.push((idx, send(notification)));
+            }
+            if in_flight.is_empty() {
+                return Poll::Ready(());
+            }
+
+            // Poll every send in flight, making room for more as they finish
+            let before = in_flight.len();
+            in_flight.retain_mut(|(idx, sending)| match sending.as_mut().poll(cx) {
+                Poll::Ready(result) => {
+                    results[*idx] = Some(result);
+                    false
+                }
+                Poll::Pending => true,
+            });
+            if in_flight.len() == before {
+                return Poll::Pending;
+            }
+        })
+        .await;
+
+        results.into_iter().flatten().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockDestination;
+    use crate::{Notification, Notifier, Severity};
+
+    /// A test to make sure every notification is sent, with results in order
+    #[tokio::test]
+    async fn sends_all_in_order() {
+        let mock = MockDestination::new();
+        let notifier = Notifier::builder()
+            .min_severity(Severity::Warning)
+            .build()
+            .unwrap();
+        let notifications = (0..10).map(|idx| {
+            let mut notification = Notification::new(format!("Backfill {idx}"));
+            if idx % 3 == 0 {
+                notification.severity = Severity::Info;
+            }
+            notification
+        });
+
+        let results = notifier
+            .send_all(notifications, &mock.endpoint_at("mock://slack"), 3)
+            .await;
+        assert_eq!(results.len(), 10);
+        for (idx, result) in results.iter().enumerate() {
+            assert_eq!(result.as_ref().unwrap().is_none(), idx % 3 == 0);
+        }
+        assert_eq!(mock.len(), 6);
+
+        let results = notifier
+            .send_all(Vec::new(), &mock.endpoint_at("mock://slack"), 0)
+            .await;
+        assert!(results.is_empty());
+    }
+}
@@ -0,0 +1,40 @@
+use clap::{Args, Subcommand};
+use dev_notify::{Config, Error};
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Work with the config file
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Check the config before deploying it: unknown keys, unset environment
+    /// variables, invalid destinations and routes to unknown destinations
+    Validate,
+}
+
+/// Run a config subcommand on the config file at a given path
+pub fn run(path: &Path, args: ConfigArgs) -> Result<ExitCode, Error> {
+    match args.command {
+        ConfigCommand::Validate => validate(path),
+    }
+}
+
+/// Report every problem with the config, failing if there are any
+fn validate(path: &Path) -> Result<ExitCode, Error> {
+    let problems = Config::from_path(path)?.validate();
+    if problems.is_empty() {
+        println!("{}: ok", path.display());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    for problem in &problems {
+        eprintln!("{}: {problem}", path.display());
+    }
+    eprintln!("dev-notify: {} problem(s) found", problems.len());
+    Ok(ExitCode::FAILURE)
+}
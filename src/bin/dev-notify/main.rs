@@ -0,0 +1,75 @@
+use clap::{Parser, Subcommand};
+use dev_notify::{Config, Notifier};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+mod config;
+mod pipe;
+mod send;
+mod test;
+mod test_report;
+mod watch;
+
+/// Config file used when `--config` isn't given, if it exists
+const DEFAULT_CONFIG: &str = "dev-notify.toml";
+
+/// Send notifications from shell scripts and CI jobs, with the same
+/// formatting and destinations as services using the library
+#[derive(Parser)]
+#[command(name = "dev-notify", version)]
+struct Cli {
+    /// Config file declaring destinations and routing, defaults to
+    /// `dev-notify.toml` if it exists, or else the `DEV_NOTIFY_*`
+    /// environment variables
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    Config(config::ConfigArgs),
+    Send(send::SendArgs),
+    Test(test::TestArgs),
+    TestReport(test_report::TestReportArgs),
+    Pipe(pipe::PipeArgs),
+    Watch(watch::WatchArgs),
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Config(args) => {
+            let path = cli.config.unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG));
+            config::run(&path, args)
+        }
+        command => match load_notifier(cli.config.as_deref()) {
+            Ok(notifier) => match command {
+                Command::Send(args) => send::run(&notifier, args).await,
+                Command::Test(args) => test::run(&notifier, args).await,
+                Command::TestReport(args) => test_report::run(&notifier, args).await,
+                Command::Pipe(args) => pipe::run(&notifier, args).await,
+                Command::Watch(args) => watch::run(&notifier, args).await,
+                Command::Config(_) => unreachable!("handled without a `Notifier`"),
+            },
+            Err(e) => Err(e),
+        },
+    };
+
+    result.unwrap_or_else(|e| {
+        eprintln!("dev-notify: {e}");
+        ExitCode::FAILURE
+    })
+}
+
+/// Build a `Notifier` from the config file, or the environment without one
+fn load_notifier(config: Option<&Path>) -> Result<Notifier, dev_notify::Error> {
+    match config {
+        Some(path) => Config::from_path(path)?.build(),
+        None if Path::new(DEFAULT_CONFIG).exists() => Config::from_path(DEFAULT_CONFIG)?.build(),
+        None => Notifier::from_env(),
+    }
+}
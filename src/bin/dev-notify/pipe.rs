@@ -0,0 +1,162 @@
+use crate::send::{self, parse_context};
+use clap::Args;
+use dev_notify::{Context, Error, Notification, Notifier, Severity};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Command, ExitCode, Stdio};
+use std::sync::mpsc;
+use std::thread;
+
+/// Pass output through while keeping its tail, then notify with it
+///
+/// Reads from stdin (`some_command 2>&1 | dev-notify pipe`), or runs the
+/// command given after `--` (`dev-notify pipe --on-failure -- some_command`).
+/// Only a command run by `dev-notify` itself has an exit status to check,
+/// since a pipe doesn't carry the exit status of the command writing to it.
+#[derive(Args)]
+pub struct PipeArgs {
+    /// Only notify when the command exits unsuccessfully
+    #[arg(long)]
+    on_failure: bool,
+
+    /// How many of the last lines of output to include as context
+    #[arg(long, short = 'n', default_value_t = 20)]
+    lines: usize,
+
+    /// The notification message, defaults to describing the command
+    #[arg(long, short)]
+    message: Option<String>,
+
+    /// Context as `label=value`, can be repeated
+    #[arg(long, short, value_parser = parse_context)]
+    context: Vec<Context>,
+
+    /// Severity of the notification
+    #[arg(long, short, default_value = "error")]
+    severity: Severity,
+
+    /// Named destination (or URL) to send to, can be repeated
+    #[arg(long = "dest", short)]
+    destinations: Vec<String>,
+
+    /// The command to run, instead of reading stdin
+    #[arg(last = true)]
+    command: Vec<String>,
+}
+
+/// Pass output through, then notify with its tail
+pub async fn run(notifier: &Notifier, args: PipeArgs) -> Result<ExitCode, Error> {
+    let (tail, status) =
+        match args.command.split_first() {
+            Some((program, program_args)) => {
+                let (tail, status) = run_command(program, program_args, args.lines)
+                    .map_err(|e| Error::Config(format!("couldn't run `{program}`: {e}")))?;
+                (tail, Some(status))
+            }
+            None if args.on_failure => return Err(Error::Config(String::from(
+                "--on-failure needs the command after `--`, a pipe doesn't carry its exit status",
+            ))),
+            None => (tail_stdin(args.lines), None),
+        };
+
+    let failed = status.is_some_and(|status| !status.success());
+    if !args.on_failure || failed {
+        let command = args.command.join(" ");
+        let mut notification = Notification::new(args.message.unwrap_or_else(|| {
+            match status.and_then(|status| status.code()) {
+                Some(code) => format!("`{command}` exited with code {code}"),
+                None if status.is_some() => format!("`{command}` was terminated"),
+                None => String::from("Piped output"),
+            }
+        }));
+        notification.context = args.context;
+        notification
+            .context
+            .push(Context::code("Output", Vec::from(tail).join("\n")));
+        notification.severity = args.severity;
+        send::deliver(notifier, notification, &args.destinations).await?;
+    }
+
+    // Pass on the exit status so scripts and CI jobs still fail
+    Ok(match status.and_then(|status| status.code()) {
+        Some(code) => ExitCode::from(u8::try_from(code).unwrap_or(1)),
+        None if failed => ExitCode::FAILURE,
+        None => ExitCode::SUCCESS,
+    })
+}
+
+/// Copy stdin to stdout, keeping the last `lines` lines
+fn tail_stdin(lines: usize) -> VecDeque<String> {
+    let mut tail = VecDeque::with_capacity(lines);
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines().map_while(Result::ok) {
+        let _ = writeln!(stdout, "{line}");
+        push_line(&mut tail, line, lines);
+    }
+
+    tail
+}
+
+/// Run a command passing its stdout and stderr through, keeping the
+/// last `lines` lines of both, interleaved as they arrive
+fn run_command(
+    program: &str,
+    args: &[String],
+    lines: usize,
+) -> io::Result<(VecDeque<String>, std::process::ExitStatus)> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let (sender, received) = mpsc::channel();
+    let readers = [
+        forward(child.stdout.take(), sender.clone(), false),
+        forward(child.stderr.take(), sender, true),
+    ];
+
+    let mut tail = VecDeque::with_capacity(lines);
+    for line in received {
+        push_line(&mut tail, line, lines);
+    }
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    Ok((tail, child.wait()?))
+}
+
+/// Forward lines from a child's output to ours, and to a channel
+fn forward(
+    output: Option<impl Read + Send + 'static>,
+    sender: mpsc::Sender<String>,
+    is_stderr: bool,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let Some(output) = output else {
+            return;
+        };
+        for line in BufReader::new(output).lines().map_while(Result::ok) {
+            if is_stderr {
+                eprintln!("{line}");
+            } else {
+                println!("{line}");
+            }
+            if sender.send(line).is_err() {
+                return;
+            }
+        }
+    })
+}
+
+/// Push a line onto the tail, dropping the oldest past `lines`
+fn push_line(tail: &mut VecDeque<String>, line: String, lines: usize) {
+    if lines == 0 {
+        return;
+    }
+    if tail.len() == lines {
+        tail.pop_front();
+    }
+    tail.push_back(line);
+}
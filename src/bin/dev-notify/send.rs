@@ -0,0 +1,87 @@
+use clap::Args;
+use dev_notify::{Context, ContextKind, Endpoint, Error, Notification, Notifier, Severity};
+use std::process::ExitCode;
+
+/// Send a notification
+#[derive(Args)]
+pub struct SendArgs {
+    /// The notification message
+    #[arg(long, short)]
+    message: String,
+
+    /// Context as `label=value`, can be repeated
+    #[arg(long, short, value_parser = parse_context)]
+    context: Vec<Context>,
+
+    /// Severity of the notification
+    #[arg(long, short, default_value = "error")]
+    severity: Severity,
+
+    /// Named destination (or URL) to send to, can be repeated, defaults
+    /// to the destinations routed to by the config
+    #[arg(long = "dest", short)]
+    destinations: Vec<String>,
+}
+
+/// Send a notification built from the arguments
+pub async fn run(notifier: &Notifier, args: SendArgs) -> Result<ExitCode, Error> {
+    let mut notification = Notification::new(args.message);
+    notification.context = args.context;
+    notification.severity = args.severity;
+    deliver(notifier, notification, &args.destinations).await?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Send a notification to the given destinations, or the
+/// destinations routed to by the config if none are given
+pub async fn deliver(
+    notifier: &Notifier,
+    notification: Notification,
+    destinations: &[String],
+) -> Result<(), Error> {
+    if destinations.is_empty() {
+        let results = notifier.notify(notification).await;
+        if results.is_empty() {
+            eprintln!("dev-notify: no destination accepted the notification");
+        }
+        for (_, result) in results {
+            result?;
+        }
+        return Ok(());
+    }
+
+    for destination in destinations {
+        let endpoint = resolve(notifier, destination)?;
+        notifier.send(notification.clone(), &endpoint).await?;
+    }
+
+    Ok(())
+}
+
+/// Look up a named destination, or treat it as a URL
+pub fn resolve(notifier: &Notifier, destination: &str) -> Result<Endpoint, Error> {
+    if let Some(endpoint) = notifier.destination(destination) {
+        return Ok(endpoint.clone());
+    }
+    if destination.starts_with("http://") || destination.starts_with("https://") {
+        return Ok(Endpoint::new(destination));
+    }
+
+    Err(Error::Config(format!(
+        "unknown destination `{destination}`"
+    )))
+}
+
+/// Parse `label=value` into `Context`
+pub fn parse_context(context: &str) -> Result<Context, String> {
+    let (label, value) = context
+        .split_once('=')
+        .ok_or_else(|| format!("expected `label=value`, got `{context}`"))?;
+
+    Ok(Context {
+        label: label.trim().to_string(),
+        value: value.trim().to_string(),
+        kind: ContextKind::Text,
+    })
+}
@@ -0,0 +1,56 @@
+use crate::send;
+use clap::Args;
+use dev_notify::{Endpoint, Error, Notifier};
+use std::process::ExitCode;
+use std::time::Instant;
+
+/// Send a canary notification to each destination and report the HTTP
+/// status and latency, e.g. to verify a rotated webhook URL
+#[derive(Args)]
+pub struct TestArgs {
+    /// Named destination (or URL) to test, can be repeated, defaults
+    /// to every destination in the config
+    #[arg(long = "dest", short)]
+    destinations: Vec<String>,
+}
+
+/// Test each destination, failing if any of them can't be delivered to
+pub async fn run(notifier: &Notifier, args: TestArgs) -> Result<ExitCode, Error> {
+    let destinations: Vec<(String, Endpoint)> = if args.destinations.is_empty() {
+        notifier
+            .destinations()
+            .map(|(name, endpoint)| (name.to_string(), endpoint.clone()))
+            .collect()
+    } else {
+        args.destinations
+            .iter()
+            .map(|name| Ok((name.clone(), send::resolve(notifier, name)?)))
+            .collect::<Result<_, Error>>()?
+    };
+    if destinations.is_empty() {
+        return Err(Error::Config(String::from("no destinations to test")));
+    }
+
+    let mut failed = 0;
+    for (name, endpoint) in &destinations {
+        let started = Instant::now();
+        let result = notifier.send_canary(endpoint).await;
+        let latency = started.elapsed().as_millis();
+        match result {
+            Ok(receipt) => println!("{name}: {} in {latency}ms", receipt.status),
+            Err(e) => {
+                failed += 1;
+                println!("{name}: failed after {latency}ms: {e}");
+            }
+        }
+    }
+
+    if failed > 0 {
+        eprintln!(
+            "dev-notify: {failed} of {} destination(s) failed",
+            destinations.len()
+        );
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,56 @@
+use crate::send::{self, parse_context};
+use clap::Args;
+use dev_notify::{Context, Error, Notifier, TestReport};
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+
+/// Pass `cargo test` JSON output through, then notify with a summary of
+/// failed tests
+///
+/// Reads libtest's JSON output from stdin, e.g.
+/// `cargo test -- -Z unstable-options --format json | dev-notify test-report`,
+/// and exits unsuccessfully if any test failed.
+#[derive(Args)]
+pub struct TestReportArgs {
+    /// Notify even when every test passed
+    #[arg(long)]
+    always: bool,
+
+    /// The notification message, defaults to how many tests failed
+    #[arg(long, short)]
+    message: Option<String>,
+
+    /// Context as `label=value`, can be repeated
+    #[arg(long, short, value_parser = parse_context)]
+    context: Vec<Context>,
+
+    /// Named destination (or URL) to send to, can be repeated
+    #[arg(long = "dest", short)]
+    destinations: Vec<String>,
+}
+
+/// Pass test output through, then notify with a summary of failed tests
+pub async fn run(notifier: &Notifier, args: TestReportArgs) -> Result<ExitCode, Error> {
+    let mut report = TestReport::new();
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines().map_while(Result::ok) {
+        let _ = writeln!(stdout, "{line}");
+        report.push_line(&line);
+    }
+    drop(stdout);
+
+    if args.always || !report.is_success() {
+        let mut notification = report.to_notification();
+        if let Some(message) = args.message {
+            notification.message = message;
+        }
+        notification.context.splice(0..0, args.context);
+        send::deliver(notifier, notification, &args.destinations).await?;
+    }
+
+    Ok(if report.is_success() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
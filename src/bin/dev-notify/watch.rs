@@ -0,0 +1,166 @@
+use crate::send::{self, parse_context};
+use clap::Args;
+use dev_notify::{Context, ContextKind, Error, Notification, Notifier, Severity};
+use regex::Regex;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// Tail a file and notify whenever a line matches a pattern
+#[derive(Args)]
+pub struct WatchArgs {
+    /// The file to tail, following truncation and rotation
+    #[arg(long, short)]
+    file: PathBuf,
+
+    /// Regular expression a line must match to notify
+    #[arg(long, short)]
+    pattern: Regex,
+
+    /// How many lines before a match to include as context
+    #[arg(long, short = 'B', default_value_t = 5)]
+    before: usize,
+
+    /// How many lines after a match to include as context
+    #[arg(long, short = 'A', default_value_t = 5)]
+    after: usize,
+
+    /// How often to check the file for new lines, in milliseconds
+    #[arg(long, default_value_t = 500)]
+    interval_ms: u64,
+
+    /// Context as `label=value`, can be repeated
+    #[arg(long, short, value_parser = parse_context)]
+    context: Vec<Context>,
+
+    /// Severity of the notifications
+    #[arg(long, short, default_value = "error")]
+    severity: Severity,
+
+    /// Named destination (or URL) to send to, can be repeated
+    #[arg(long = "dest", short)]
+    destinations: Vec<String>,
+}
+
+/// A matching line waiting for the lines after it
+struct Match {
+    line: String,
+    before: Vec<String>,
+    after: Vec<String>,
+}
+
+/// Tail the file until interrupted, notifying on matching lines
+pub async fn run(notifier: &Notifier, args: WatchArgs) -> Result<ExitCode, Error> {
+    let mut reader = open_at_end(&args.file)?;
+    let mut position = reader
+        .stream_position()
+        .map_err(|e| watch_error(&args.file, e))?;
+    let mut partial = String::new();
+    let mut before: VecDeque<String> = VecDeque::with_capacity(args.before);
+    let mut pending: Vec<Match> = Vec::new();
+
+    loop {
+        let read = reader
+            .read_line(&mut partial)
+            .map_err(|e| watch_error(&args.file, e))?;
+
+        // Wait for more once we've caught up, or for the rest of a partly written line
+        if read == 0 || !partial.ends_with('\n') {
+            // Nothing new is coming soon, so don't hold matches back any longer
+            for found in pending.drain(..) {
+                notify(notifier, &args, found).await;
+            }
+            tokio::time::sleep(Duration::from_millis(args.interval_ms)).await;
+
+            // Start over from the beginning of a truncated or rotated file
+            let len = std::fs::metadata(&args.file).map(|m| m.len()).unwrap_or(0);
+            if len < position {
+                reader = open(&args.file)?;
+                position = 0;
+                partial.clear();
+            }
+            continue;
+        }
+        position += partial.len() as u64;
+        let line = partial.trim_end_matches(['\n', '\r']).to_string();
+        partial.clear();
+
+        // Complete any matches waiting on the lines after them
+        for found in pending
+            .iter_mut()
+            .filter(|found| found.after.len() < args.after)
+        {
+            found.after.push(line.clone());
+        }
+        let (complete, waiting): (Vec<Match>, Vec<Match>) = pending
+            .into_iter()
+            .partition(|found| found.after.len() >= args.after);
+        pending = waiting;
+        for found in complete {
+            notify(notifier, &args, found).await;
+        }
+
+        if args.pattern.is_match(&line) {
+            pending.push(Match {
+                line: line.clone(),
+                before: before.iter().cloned().collect(),
+                after: Vec::new(),
+            });
+        }
+        if args.before > 0 {
+            if before.len() == args.before {
+                before.pop_front();
+            }
+            before.push_back(line);
+        }
+    }
+}
+
+/// Send a notification about a matching line, reporting failures without stopping
+async fn notify(notifier: &Notifier, args: &WatchArgs, found: Match) {
+    let mut notification = Notification::new(found.line.trim());
+    notification.context = args.context.clone();
+    notification.context.push(Context {
+        label: String::from("File"),
+        value: args.file.display().to_string(),
+        kind: ContextKind::Text,
+    });
+    let surrounding: Vec<String> = found
+        .before
+        .into_iter()
+        .chain([found.line])
+        .chain(found.after)
+        .collect();
+    notification
+        .context
+        .push(Context::code("Surrounding Lines", surrounding.join("\n")));
+    notification.severity = args.severity;
+
+    if let Err(e) = send::deliver(notifier, notification, &args.destinations).await {
+        eprintln!("dev-notify: {e}");
+    }
+}
+
+/// Open a file to read from its start
+fn open(path: &Path) -> Result<BufReader<File>, Error> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| watch_error(path, e))
+}
+
+/// Open a file to read only lines written from now on
+fn open_at_end(path: &Path) -> Result<BufReader<File>, Error> {
+    let mut reader = open(path)?;
+    reader
+        .seek(SeekFrom::End(0))
+        .map_err(|e| watch_error(path, e))?;
+    Ok(reader)
+}
+
+/// Describe a failure to read the watched file
+fn watch_error(path: &Path, e: std::io::Error) -> Error {
+    Error::Config(format!("couldn't read {}: {e}", path.display()))
+}
@@ -0,0 +1,115 @@
+use crate::{Context, ContextKind, Notification, Severity};
+use std::borrow::Cow;
+
+/// A `Notification` built from borrowed data (e.g. the fields of a request
+/// or log record), so hot paths only copy it into a `Notification` once
+/// it's known to be sent
+///
+/// Owned values are moved rather than copied.
+#[derive(Clone, Debug)]
+pub struct NotificationRef<'a> {
+    pub message: Cow<'a, str>,
+    pub context: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    pub severity: Severity,
+}
+impl<'a> NotificationRef<'a> {
+    /// Create a `NotificationRef` with a given message, `Severity::Error`
+    /// and no context
+    pub fn new(message: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            message: message.into(),
+            context: Vec::new(),
+            severity: Severity::default(),
+        }
+    }
+
+    /// Add a labelled value to the context
+    pub fn with_context(
+        mut self,
+        label: impl Into<Cow<'a, str>>,
+        value: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        self.context.push((label.into(), value.into()));
+        self
+    }
+
+    /// Set the severity (defaults to `Severity::Error`)
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Copy the borrowed data into a `Notification`, timestamped now
+    pub fn into_owned(self) -> Notification {
+        let mut notification = Notification::new(self.message.into_owned());
+        notification.severity = self.severity;
+        notification.context = self
+            .context
+            .into_iter()
+            .map(|(label, value)| Context {
+                label: label.into_owned(),
+                value: value.into_owned(),
+                kind: ContextKind::Text,
+            })
+            .collect();
+
+        notification
+    }
+}
+impl<'a> From<NotificationRef<'a>> for Notification {
+    fn from(notification: NotificationRef<'a>) -> Self {
+        notification.into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NotificationRef;
+    use crate::testing::MockDestination;
+    use crate::Notifier;
+    use crate::{Notification, Severity};
+
+    /// A test to make sure borrowed notifications convert into equivalent owned ones
+    #[test]
+    fn converts_into_owned() {
+        let (path, status) = (String::from("/checkout"), 502);
+        let notification: Notification = NotificationRef::new("Upstream failed")
+            .with_context("Path", path.as_str())
+            .with_context("Status", status.to_string())
+            .with_severity(Severity::Critical)
+            .into();
+
+        assert_eq!(notification.message, "Upstream failed");
+        assert_eq!(notification.severity, Severity::Critical);
+        assert_eq!(notification.context[0].label, "Path");
+        assert_eq!(notification.context[0].value, "/checkout");
+        assert_eq!(notification.context[1].value, "502");
+    }
+
+    /// A test to make sure borrowed notifications below a threshold are skipped
+    #[tokio::test]
+    async fn sends_borrowed() {
+        let mock = MockDestination::new();
+        let notifier = Notifier::builder()
+            .min_severity(Severity::Warning)
+            .build()
+            .unwrap();
+        let destination = mock.endpoint_at("mock://slack");
+
+        let message = String::from("Cache miss");
+        let skipped = notifier
+            .send_ref(
+                NotificationRef::new(message.as_str()).with_severity(Severity::Info),
+                &destination,
+            )
+            .await
+            .unwrap();
+        assert!(skipped.is_none());
+        assert!(notifier
+            .send_ref(NotificationRef::new(message.as_str()), &destination)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(mock.len(), 1);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::idle::{IdleMap, IDLE_TIMEOUT};
+use crate::time::Instant;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Failure tracking for a single destination
+#[derive(Default)]
+struct CircuitState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// Whether the single attempt through the half-open circuit is in flight
+    probing: bool,
+}
+
+/// Stops sending to destinations that fail repeatedly, keeping state per URL
+///
+/// After `failure_threshold` consecutive failures the circuit for a destination
+/// opens for `cooldown`, then a single attempt is let through: success closes
+/// the circuit again, failure reopens it for another `cooldown`. Other requests
+/// fail fast while that attempt is in flight.
+///
+/// Only failures of the destination count (see `Error::is_retryable`), a
+/// request failing for another reason (e.g. an oversized payload) is neither
+/// a success nor a failure.
+pub(crate) struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    states: Mutex<IdleMap<CircuitState>>,
+}
+impl CircuitBreaker {
+    /// Create a `CircuitBreaker` with a given threshold and cooldown
+    ///
+    /// Destinations that haven't failed for an hour (or the cooldown, if
+    /// longer) are forgotten.
+    pub(crate) fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            states: Mutex::new(IdleMap::new(IDLE_TIMEOUT.max(cooldown))),
+        }
+    }
+
+    /// Whether a request to a given destination is allowed now, taking the
+    /// single attempt through its circuit if it's half-open
+    ///
+    /// Every allowed request must be followed by `record` or `release`.
+    pub(crate) fn allows(&self, url: &str) -> bool {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = states.get_mut(url) else {
+            return true;
+        };
+        let Some(opened_at) = state.opened_at else {
+            return true;
+        };
+        if opened_at.elapsed() < self.cooldown || state.probing {
+            return false;
+        }
+
+        state.probing = true;
+        true
+    }
+
+    /// Record the outcome of a request to a given destination
+    pub(crate) fn record(&self, url: &str, succeeded: bool) {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        if succeeded {
+            states.remove(url);
+            return;
+        }
+
+        let state = states.entry(url, CircuitState::default);
+        state.probing = false;
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if state.consecutive_failures >= self.failure_threshold {
+            state.opened_at = Some(Instant::now());
+        }
+    }
+
+    /// Release a request to a given destination that neither succeeded nor
+    /// failed, letting another attempt through its circuit if it's half-open
+    pub(crate) fn release(&self, url: &str) {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(state) = states.get_mut(url) {
+            state.probing = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CircuitBreaker;
+    use std::time::Duration;
+
+    /// A test to make sure the circuit opens after repeated failures
+    #[test]
+    fn opens_after_threshold() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(3600));
+        breaker.record("https://hooks.slack.com/a", false);
+        assert!(breaker.allows("https://hooks.slack.com/a"));
+        breaker.record("https://hooks.slack.com/a", false);
+        assert!(!breaker.allows("https://hooks.slack.com/a"));
+        assert!(breaker.allows("https://hooks.slack.com/b"));
+    }
+
+    /// A test to make sure a success resets the failure count
+    #[test]
+    fn closes_on_success() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(3600));
+        breaker.record("https://hooks.slack.com/a", false);
+        breaker.record("https://hooks.slack.com/a", true);
+        breaker.record("https://hooks.slack.com/a", false);
+        assert!(breaker.allows("https://hooks.slack.com/a"));
+    }
+
+    /// A test to make sure a single attempt is let through after the cooldown
+    #[test]
+    fn half_opens_after_cooldown() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record("https://hooks.slack.com/a", false);
+        assert!(breaker.allows("https://hooks.slack.com/a"));
+        assert!(!breaker.allows("https://hooks.slack.com/a"));
+
+        // A failed attempt reopens the circuit for another cooldown
+        breaker.record("https://hooks.slack.com/a", false);
+        assert!(breaker.allows("https://hooks.slack.com/a"));
+
+        // An attempt that neither succeeded nor failed lets another through
+        breaker.release("https://hooks.slack.com/a");
+        assert!(breaker.allows("https://hooks.slack.com/a"));
+
+        breaker.record("https://hooks.slack.com/a", true);
+        assert!(breaker.allows("https://hooks.slack.com/a"));
+        assert!(breaker.allows("https://hooks.slack.com/a"));
+    }
+}
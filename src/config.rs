@@ -0,0 +1,768 @@
+#[cfg(feature = "gitlab")]
+use crate::GitlabIssue;
+#[cfg(feature = "signal")]
+use crate::Signal;
+#[cfg(feature = "sms")]
+use crate::Sms;
+use crate::{
+    ContextLimit, ContextOrder, Endpoint, Error, Labels, Locale, MaintenanceAction,
+    MaintenanceWindow, MessageFormat, Notifier, NotifierBuilder, OversizePolicy, Route,
+    SamplingPolicy, Severity, SeverityEmoji,
+};
+#[cfg(feature = "email")]
+use crate::{EmailApi, EmailTemplate};
+use crate::{Overflow, QuietHours, RateLimit};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Declarative `Notifier` configuration, usually loaded from a TOML file
+///
+/// String values can reference environment variables as `${NAME}`, so
+/// credentials can stay out of the file:
+///
+/// ```toml
+/// min_severity = "warning"
+/// labels = { issue = "Alert", timestamp = "Occurred at" }
+/// context_limit = { max_len = 500, attach_full = true }
+/// context_order = { pinned = ["Customer ID"], alphabetical = true }
+///
+/// [quiet_hours]
+/// start = "22:00"
+/// end = "07:00"
+/// utc_offset_minutes = 60
+///
+/// [[maintenance]]
+/// start = "2024-01-19 22:00:00"
+/// end = "2024-01-19 23:00:00"
+/// action = "tag"
+/// reason = "Database upgrade"
+/// context = { "Service" = "billing" }
+///
+/// [sampling]
+/// capacity = 10
+/// refill_interval_secs = 60
+///
+/// [destinations.slack]
+/// url = "${SLACK_WEBHOOK_URL}"
+/// rate_limit = { max = 1, per_secs = 1, overflow = "queue" }
+///
+/// [destinations.oncall]
+/// url = "https://alerts.internal/notify"
+/// bearer_token = "${ONCALL_TOKEN}"
+/// headers = { "X-Team" = "payments" }
+/// format = "text"
+///
+/// [[routes]]
+/// destinations = ["slack"]
+///
+/// [[routes]]
+/// destinations = ["oncall"]
+/// min_severity = "critical"
+///
+/// [[routes]]
+/// destinations = ["slack"]
+/// message_matches = "Payment*failed"
+/// context = { "Team" = "payments" }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    min_severity: Option<Severity>,
+    /// Formerly `rate_limit`, which is still accepted but deprecated, as
+    /// it's easily confused with the `rate_limit` of a destination
+    #[serde(alias = "rate_limit")]
+    sampling: Option<SamplingConfig>,
+    proxy: Option<String>,
+    spool: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    labels: Option<Labels>,
+    /// A built-in `Locale` by language code
+    locale: Option<String>,
+    context_limit: Option<ContextLimitConfig>,
+    context_order: Option<ContextOrderConfig>,
+    quiet_hours: Option<QuietHoursConfig>,
+    #[serde(default)]
+    maintenance: Vec<MaintenanceConfig>,
+    #[serde(default)]
+    destinations: BTreeMap<String, DestinationConfig>,
+    #[serde(default)]
+    routes: Vec<RouteConfig>,
+}
+
+/// Token bucket sampling, applied per fingerprint (or message)
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SamplingConfig {
+    capacity: u32,
+    refill_interval_secs: u64,
+}
+
+/// The most characters per context value, optionally attaching longer ones in full
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContextLimitConfig {
+    max_len: usize,
+    #[serde(default)]
+    attach_full: bool,
+}
+
+/// Context labels shown first, optionally with the rest sorted by label
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContextOrderConfig {
+    #[serde(default)]
+    pinned: Vec<String>,
+    #[serde(default)]
+    alphabetical: bool,
+}
+
+/// Hours during which sub-critical notifications are held for a digest
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QuietHoursConfig {
+    start: String,
+    end: String,
+    #[serde(default)]
+    utc_offset_minutes: i32,
+    below: Option<Severity>,
+}
+
+/// A maintenance window, in UTC, with conditions like a route's
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaintenanceConfig {
+    start: String,
+    end: String,
+    action: Option<MaintenanceAction>,
+    reason: Option<String>,
+    min_severity: Option<Severity>,
+    message_contains: Option<String>,
+    message_matches: Option<String>,
+    #[serde(default)]
+    has_context: Vec<String>,
+    #[serde(default)]
+    context: BTreeMap<String, String>,
+}
+
+/// A named destination
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DestinationConfig {
+    url: String,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    bearer_token: Option<String>,
+    basic_auth: Option<BasicAuthConfig>,
+    min_severity: Option<Severity>,
+    format: Option<MessageFormat>,
+    oversize: Option<OversizePolicy>,
+    #[cfg(feature = "slack")]
+    #[serde(default)]
+    action_buttons: bool,
+    channel: Option<String>,
+    severity_emoji: Option<BTreeMap<Severity, String>>,
+    unfurl_links: Option<bool>,
+    unfurl_media: Option<bool>,
+    gzip_min_len: Option<usize>,
+    rate_limit: Option<DestinationRateLimitConfig>,
+    #[cfg(feature = "email")]
+    email_template: Option<PathBuf>,
+    #[cfg(feature = "email")]
+    email_api: Option<DestinationEmailApiConfig>,
+    #[cfg(feature = "sms")]
+    sms: Option<DestinationSmsConfig>,
+    #[cfg(feature = "gitlab")]
+    gitlab: Option<DestinationGitlabConfig>,
+    #[cfg(feature = "signal")]
+    signal: Option<DestinationSignalConfig>,
+    #[cfg(feature = "signing")]
+    signing_secret: Option<String>,
+}
+
+/// The rate limit of a named destination
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DestinationRateLimitConfig {
+    max: u32,
+    per_secs: u64,
+    #[serde(default)]
+    overflow: Overflow,
+}
+
+/// The username and password of a named destination
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BasicAuthConfig {
+    username: String,
+    password: String,
+}
+
+/// The HTTP email API of a named destination
+#[cfg(feature = "email")]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DestinationEmailApiConfig {
+    provider: EmailProviderConfig,
+    from: String,
+}
+
+/// A provider of HTTP email APIs
+#[cfg(feature = "email")]
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum EmailProviderConfig {
+    SendGrid,
+    Mailgun,
+}
+
+/// The SMS options of a named destination
+#[cfg(feature = "sms")]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DestinationSmsConfig {
+    max_segments: Option<usize>,
+    top_context: Option<usize>,
+}
+
+/// The GitLab issue options of a named destination
+#[cfg(feature = "gitlab")]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DestinationGitlabConfig {
+    #[serde(default)]
+    incident: bool,
+    #[serde(default)]
+    labels: Vec<String>,
+}
+
+/// The Signal options of a named destination
+#[cfg(feature = "signal")]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DestinationSignalConfig {
+    number: String,
+}
+
+/// A route to named destinations
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RouteConfig {
+    destinations: Vec<String>,
+    min_severity: Option<Severity>,
+    message_contains: Option<String>,
+    message_matches: Option<String>,
+    /// Labels of context entries that must be present
+    #[serde(default)]
+    has_context: Vec<String>,
+    /// Values context entries must hold, by label
+    #[serde(default)]
+    context: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Load a `Config` from a TOML file
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("couldn't read {}: {e}", path.display())))?;
+        Self::from_toml(&contents)
+    }
+
+    /// Parse a `Config` from TOML
+    pub fn from_toml(contents: &str) -> Result<Self, Error> {
+        toml::from_str(contents).map_err(|e| Error::Config(e.to_string()))
+    }
+
+    /// Check the `Config` before deploying it, returning every problem found
+    /// instead of stopping at the first like `build`: unset environment
+    /// variables, destinations without a valid HTTP(S) URL, routes to unknown
+    /// destinations, and any other setting `build` would reject
+    pub fn validate(&self) -> Vec<Error> {
+        let mut problems = Vec::new();
+        let mut values: Vec<(String, &str)> = Vec::new();
+        if let Some(proxy) = &self.proxy {
+            values.push((String::from("proxy"), proxy));
+        }
+        for (name, destination) in &self.destinations {
+            let field = |field: &str| format!("destination `{name}` {field}");
+            values.push((field("url"), &destination.url));
+            for (header, value) in &destination.headers {
+                values.push((field(&format!("header `{header}`")), value));
+            }
+            if let Some(token) = &destination.bearer_token {
+                values.push((field("bearer_token"), token));
+            }
+            if let Some(auth) = &destination.basic_auth {
+                values.push((field("basic_auth password"), &auth.password));
+            }
+            #[cfg(feature = "signing")]
+            if let Some(secret) = &destination.signing_secret {
+                values.push((field("signing_secret"), secret));
+            }
+        }
+        for (field, value) in values {
+            for name in unset_variables(value) {
+                problems.push(Error::Config(format!(
+                    "{field} references environment variable `{name}`, which is not set"
+                )));
+            }
+        }
+
+        for (name, destination) in &self.destinations {
+            let Ok(url) = interpolate(&destination.url) else {
+                continue;
+            };
+            match reqwest::Url::parse(&url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                _ => problems.push(Error::Config(format!(
+                    "destination `{name}` has an invalid URL, expected an http(s) URL"
+                ))),
+            }
+        }
+        for route in &self.routes {
+            for unknown in route
+                .destinations
+                .iter()
+                .filter(|name| !self.destinations.contains_key(*name))
+            {
+                problems.push(Error::Config(format!(
+                    "route references unknown destination `{unknown}`"
+                )));
+            }
+        }
+
+        // Anything else is only reported by building
+        if problems.is_empty() {
+            if let Err(e) = self.clone().build() {
+                problems.push(e);
+            }
+        }
+
+        problems
+    }
+
+    /// Build a `Notifier` from the `Config`
+    pub fn build(self) -> Result<Notifier, Error> {
+        self.into_builder()?.build()
+    }
+
+    /// Convert the `Config` into a `NotifierBuilder`, to add settings
+    /// that can't be configured declaratively (e.g. hooks)
+    ///
+    /// Environment variables are interpolated and routes are checked
+    /// to only reference declared destinations.
+    pub fn into_builder(self) -> Result<NotifierBuilder, Error> {
+        let mut builder = Notifier::builder();
+        if let Some(severity) = self.min_severity {
+            builder = builder.min_severity(severity);
+        }
+        if let Some(sampling) = self.sampling {
+            builder = builder.sampling(SamplingPolicy::TokenBucket {
+                capacity: sampling.capacity,
+                refill_interval: Duration::from_secs(sampling.refill_interval_secs),
+            });
+        }
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(interpolate(&proxy)?);
+        }
+        if let Some(spool) = self.spool {
+            builder = builder.spool(spool);
+        }
+        if let Some(audit_log) = self.audit_log {
+            builder = builder.audit_log(audit_log);
+        }
+        if let Some(labels) = self.labels {
+            builder = builder.labels(labels);
+        }
+        if let Some(language) = self.locale {
+            let locale = Locale::builtin(&language).ok_or_else(|| {
+                Error::Config(format!(
+                    "unknown locale `{language}`, expected one of en, de, es or fr"
+                ))
+            })?;
+            builder = builder.locale(locale);
+        }
+        if let Some(config) = self.context_limit {
+            let mut limit = ContextLimit::new(config.max_len);
+            if config.attach_full {
+                limit = limit.attach_full();
+            }
+            builder = builder.context_limit(limit);
+        }
+        if let Some(config) = self.context_order {
+            let mut order = config
+                .pinned
+                .into_iter()
+                .fold(ContextOrder::new(), ContextOrder::pin);
+            if config.alphabetical {
+                order = order.alphabetical();
+            }
+            builder = builder.context_order(order);
+        }
+        if let Some(config) = self.quiet_hours {
+            let mut quiet_hours =
+                QuietHours::new(&config.start, &config.end, config.utc_offset_minutes)?;
+            if let Some(severity) = config.below {
+                quiet_hours = quiet_hours.below(severity);
+            }
+            builder = builder.quiet_hours(quiet_hours);
+        }
+        for config in self.maintenance {
+            let mut window = MaintenanceWindow::new(&config.start, &config.end)?;
+            if let Some(action) = config.action {
+                window = window.action(action);
+            }
+            if let Some(reason) = config.reason {
+                window = window.reason(reason);
+            }
+            if let Some(severity) = config.min_severity {
+                window = window.min_severity(severity);
+            }
+            if let Some(text) = config.message_contains {
+                window = window.message_contains(text);
+            }
+            if let Some(pattern) = config.message_matches {
+                window = window.message_matches(pattern);
+            }
+            for label in config.has_context {
+                window = window.has_context(label);
+            }
+            for (label, value) in config.context {
+                window = window.context_equals(label, value);
+            }
+            builder = builder.maintenance_window(window);
+        }
+
+        for route in &self.routes {
+            if let Some(unknown) = route
+                .destinations
+                .iter()
+                .find(|name| !self.destinations.contains_key(*name))
+            {
+                return Err(Error::Config(format!(
+                    "route references unknown destination `{unknown}`"
+                )));
+            }
+        }
+        for (name, destination) in self.destinations {
+            let mut endpoint = Endpoint::new(interpolate(&destination.url)?);
+            for (header, value) in &destination.headers {
+                endpoint = endpoint.header(header, interpolate(value)?);
+            }
+            if let Some(token) = &destination.bearer_token {
+                endpoint = endpoint.bearer_auth(interpolate(token)?);
+            }
+            if let Some(auth) = &destination.basic_auth {
+                endpoint = endpoint.basic_auth(&auth.username, interpolate(&auth.password)?);
+            }
+            if let Some(severity) = destination.min_severity {
+                endpoint = endpoint.min_severity(severity);
+            }
+            if let Some(format) = destination.format {
+                endpoint = endpoint.format(format);
+            }
+            if let Some(policy) = destination.oversize {
+                endpoint = endpoint.oversize(policy);
+            }
+            #[cfg(feature = "slack")]
+            if destination.action_buttons {
+                endpoint = endpoint.action_buttons();
+            }
+            if let Some(channel) = &destination.channel {
+                endpoint = endpoint.channel(channel);
+            }
+            if let Some(overrides) = &destination.severity_emoji {
+                let emoji = overrides
+                    .iter()
+                    .fold(SeverityEmoji::new(), |emoji, (severity, value)| {
+                        emoji.set(*severity, value)
+                    });
+                endpoint = endpoint.severity_emoji(emoji);
+            }
+            if let Some(unfurl) = destination.unfurl_links {
+                endpoint = endpoint.unfurl_links(unfurl);
+            }
+            if let Some(unfurl) = destination.unfurl_media {
+                endpoint = endpoint.unfurl_media(unfurl);
+            }
+            if let Some(min_len) = destination.gzip_min_len {
+                endpoint = endpoint.gzip(min_len);
+            }
+            if let Some(limit) = &destination.rate_limit {
+                endpoint = endpoint.rate_limit(
+                    RateLimit::new(limit.max, Duration::from_secs(limit.per_secs))
+                        .overflow(limit.overflow),
+                );
+            }
+            #[cfg(feature = "email")]
+            if let Some(path) = &destination.email_template {
+                let html = std::fs::read_to_string(path)
+                    .map_err(|e| Error::Config(format!("couldn't read {}: {e}", path.display())))?;
+                endpoint = endpoint.email_template(EmailTemplate::new(html));
+            }
+            #[cfg(feature = "email")]
+            if let Some(api) = &destination.email_api {
+                endpoint = endpoint.email_api(match api.provider {
+                    EmailProviderConfig::SendGrid => EmailApi::sendgrid(&api.from),
+                    EmailProviderConfig::Mailgun => EmailApi::mailgun(&api.from),
+                });
+            }
+            #[cfg(feature = "sms")]
+            if let Some(options) = &destination.sms {
+                let mut sms = Sms::new();
+                if let Some(max_segments) = options.max_segments {
+                    sms = sms.max_segments(max_segments);
+                }
+                if let Some(top_context) = options.top_context {
+                    sms = sms.top_context(top_context);
+                }
+                endpoint = endpoint.sms(sms);
+            }
+            #[cfg(feature = "gitlab")]
+            if let Some(options) = &destination.gitlab {
+                let mut issue = options
+                    .labels
+                    .iter()
+                    .fold(GitlabIssue::new(), |issue, label| issue.label(label));
+                if options.incident {
+                    issue = issue.incident();
+                }
+                endpoint = endpoint.gitlab(issue);
+            }
+            #[cfg(feature = "signal")]
+            if let Some(signal) = &destination.signal {
+                endpoint = endpoint.signal(Signal::new(&signal.number));
+            }
+            #[cfg(feature = "signing")]
+            if let Some(secret) = &destination.signing_secret {
+                endpoint = endpoint.sign_with(interpolate(secret)?);
+            }
+            builder = builder.destination(name, endpoint);
+        }
+        for config in self.routes {
+            let mut route = Route::new(config.destinations);
+            if let Some(severity) = config.min_severity {
+                route = route.min_severity(severity);
+            }
+            if let Some(text) = config.message_contains {
+                route = route.message_contains(text);
+            }
+            if let Some(pattern) = config.message_matches {
+                route = route.message_matches(pattern);
+            }
+            for label in config.has_context {
+                route = route.has_context(label);
+            }
+            for (label, value) in config.context {
+                route = route.context_equals(label, value);
+            }
+            builder = builder.route(route);
+        }
+
+        Ok(builder)
+    }
+}
+
+/// Replace every `${NAME}` in a value with the environment variable `NAME`
+fn interpolate(value: &str) -> Result<String, Error> {
+    let mut interpolated = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        interpolated.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            return Err(Error::Config(format!("unterminated `${{` in `{value}`")));
+        };
+
+        let name = &rest[start + 2..start + end];
+        let variable = std::env::var(name)
+            .map_err(|_| Error::Config(format!("environment variable `{name}` is not set")))?;
+        interpolated.push_str(&variable);
+        rest = &rest[start + end + 1..];
+    }
+    interpolated.push_str(rest);
+
+    Ok(interpolated)
+}
+
+/// The names of the environment variables a value references that aren't set
+fn unset_variables(value: &str) -> Vec<&str> {
+    let mut unset = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + end];
+        if std::env::var(name).is_err() {
+            unset.push(name);
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    unset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpolate;
+    use crate::Config;
+
+    /// A test to make sure environment variables are interpolated
+    #[test]
+    fn can_interpolate_env_vars() {
+        std::env::set_var("DEV_NOTIFY_TEST_TEAM", "payments");
+        assert_eq!(
+            interpolate("https://${DEV_NOTIFY_TEST_TEAM}.internal/${DEV_NOTIFY_TEST_TEAM}")
+                .unwrap(),
+            "https://payments.internal/payments"
+        );
+        assert!(interpolate("${DEV_NOTIFY_TEST_UNSET}").is_err());
+        assert!(interpolate("${DEV_NOTIFY_TEST_TEAM").is_err());
+    }
+
+    /// A test to make sure a full config builds a `Notifier`
+    #[cfg(feature = "slack")]
+    #[test]
+    fn can_build_from_config() {
+        std::env::set_var("DEV_NOTIFY_TEST_TOKEN", "secret-token");
+        let config = Config::from_toml(
+            r#"
+            min_severity = "warning"
+            labels = { issue = "Alert", timestamp = "Occurred at" }
+            locale = "de"
+            context_limit = { max_len = 500, attach_full = true }
+            context_order = { pinned = ["Customer ID"], alphabetical = true }
+            audit_log = "/var/log/dev-notify/audit.jsonl"
+
+            [quiet_hours]
+            start = "22:00"
+            end = "07:00"
+            utc_offset_minutes = -300
+            below = "error"
+
+            [[maintenance]]
+            start = "2024-01-19 22:00:00"
+            end = "2024-01-19 23:00:00"
+            action = "tag"
+            reason = "Database upgrade"
+            message_matches = "Connection*"
+            context = { "Service" = "billing" }
+
+            [sampling]
+            capacity = 10
+            refill_interval_secs = 60
+
+            [destinations.slack]
+            url = "https://hooks.slack.com/services/T000/B000/XXXX"
+
+            [destinations.oncall]
+            url = "https://alerts.internal/notify"
+            bearer_token = "${DEV_NOTIFY_TEST_TOKEN}"
+            headers = { "X-Team" = "payments" }
+            format = "terse"
+            oversize = "reject"
+            action_buttons = true
+            unfurl_links = false
+            gzip_min_len = 65536
+            rate_limit = { max = 5, per_secs = 3600, overflow = "digest" }
+
+            [[routes]]
+            destinations = ["slack"]
+
+            [[routes]]
+            destinations = ["oncall"]
+            min_severity = "critical"
+
+            [[routes]]
+            destinations = ["oncall"]
+            message_matches = "Payment*failed"
+            has_context = ["Order ID"]
+            context = { "Team" = "payments" }
+            "#,
+        )
+        .unwrap();
+        assert!(config.build().is_ok())
+    }
+
+    /// A test to make sure destinations can send through an HTTP email API
+    #[cfg(feature = "email")]
+    #[test]
+    fn can_configure_email_api() {
+        std::env::set_var("DEV_NOTIFY_TEST_TOKEN", "secret-token");
+        let config = Config::from_toml(
+            r#"
+            [destinations.email]
+            url = "https://api.mailgun.net/v3/example.com/messages"
+            basic_auth = { username = "api", password = "${DEV_NOTIFY_TEST_TOKEN}" }
+            email_api = { provider = "mailgun", from = "alerts@example.com" }
+            channel = "oncall@example.com"
+            "#,
+        )
+        .unwrap();
+        assert!(config.build().is_ok())
+    }
+
+    /// A test to make sure routes can't reference undeclared destinations
+    #[test]
+    fn rejects_unknown_route_destination() {
+        let config = Config::from_toml(
+            r#"
+            [[routes]]
+            destinations = ["pagerduty"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.build().is_err())
+    }
+
+    /// A test to make sure validation reports every problem at once
+    #[test]
+    fn reports_every_problem() {
+        let config = Config::from_toml(
+            r#"
+            [destinations.slack]
+            url = "${DEV_NOTIFY_TEST_UNSET_URL}"
+
+            [destinations.oncall]
+            url = "alerts.internal/notify"
+            bearer_token = "${DEV_NOTIFY_TEST_UNSET_TOKEN}"
+
+            [[routes]]
+            destinations = ["slack", "pagerduty"]
+            "#,
+        )
+        .unwrap();
+        let problems: Vec<String> = config.validate().iter().map(|e| e.to_string()).collect();
+        assert_eq!(problems.len(), 4);
+        assert!(problems[0].contains("`DEV_NOTIFY_TEST_UNSET_TOKEN`"));
+        assert!(problems[1].contains("`DEV_NOTIFY_TEST_UNSET_URL`"));
+        assert!(problems[2].contains("destination `oncall` has an invalid URL"));
+        assert!(problems[3].contains("unknown destination `pagerduty`"));
+
+        let locale = Config::from_toml("locale = \"xx\"").unwrap();
+        assert_eq!(locale.validate().len(), 1);
+        assert!(Config::default().validate().is_empty());
+    }
+
+    /// A test to make sure the deprecated `rate_limit` key still configures sampling
+    #[test]
+    fn accepts_deprecated_rate_limit() {
+        let config = Config::from_toml(
+            r#"
+            [rate_limit]
+            capacity = 10
+            refill_interval_secs = 60
+            "#,
+        )
+        .unwrap();
+        assert!(config.sampling.is_some())
+    }
+
+    /// A test to make sure typos in the config are reported
+    #[test]
+    fn rejects_unknown_fields() {
+        assert!(Config::from_toml("min_severty = \"error\"").is_err())
+    }
+}
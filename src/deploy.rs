@@ -0,0 +1,225 @@
+use crate::{Notification, Severity};
+
+/// Variables CI providers describe the commit being built with, by what
+/// they hold, tried in order
+const BRANCH_VARS: [&str; 4] = [
+    "GIT_BRANCH",
+    "GITHUB_REF_NAME",
+    "CI_COMMIT_BRANCH",
+    "BUILDKITE_BRANCH",
+];
+const SHA_VARS: [&str; 4] = ["GIT_SHA", "GITHUB_SHA", "CI_COMMIT_SHA", "BUILDKITE_COMMIT"];
+const AUTHOR_VARS: [&str; 4] = [
+    "GIT_AUTHOR",
+    "GITHUB_ACTOR",
+    "CI_COMMIT_AUTHOR",
+    "BUILDKITE_BUILD_AUTHOR",
+];
+const MESSAGE_VARS: [&str; 3] = [
+    "GIT_COMMIT_MESSAGE",
+    "CI_COMMIT_MESSAGE",
+    "BUILDKITE_MESSAGE",
+];
+
+/// A standard "service deployed" notification, describing the service,
+/// environment and the git commit it was built from
+///
+/// Use the `deploy_notification!` macro to take the service name and
+/// version from the calling crate, and the commit from `GIT_BRANCH`,
+/// `GIT_SHA`, `GIT_AUTHOR` and `GIT_COMMIT_MESSAGE` variables set at build
+/// time (e.g. `cargo:rustc-env=GIT_SHA=...` in a build script), or
+/// `with_ci_env` to read the commit from CI variables at runtime.
+#[derive(Clone, Debug, Default)]
+pub struct DeployNotification {
+    service: String,
+    environment: String,
+    version: Option<String>,
+    branch: Option<String>,
+    sha: Option<String>,
+    author: Option<String>,
+    commit_message: Option<String>,
+}
+impl DeployNotification {
+    /// Describe a deployment of a service to an environment, e.g. `production`
+    pub fn new(service: impl Into<String>, environment: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            environment: environment.into(),
+            ..Self::default()
+        }
+    }
+
+    /// The version of the service deployed
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// The git branch deployed
+    pub fn branch(mut self, branch: impl Into<String>) -> Self {
+        self.branch = Some(branch.into());
+        self
+    }
+
+    /// The git SHA deployed
+    pub fn sha(mut self, sha: impl Into<String>) -> Self {
+        self.sha = Some(sha.into());
+        self
+    }
+
+    /// The author of the commit deployed
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// The message of the commit deployed, only its first line is kept
+    pub fn commit_message(mut self, message: impl Into<String>) -> Self {
+        self.commit_message = Some(message.into());
+        self
+    }
+
+    /// Fill in the commit details that aren't set yet from CI variables
+    ///
+    /// Recognizes the `GIT_*` variables, then those of GitHub Actions,
+    /// GitLab CI and Buildkite.
+    pub fn with_ci_env(self) -> Self {
+        self.with_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Fill in the commit details that aren't set yet from variables looked up by name
+    fn with_vars(mut self, var: impl Fn(&str) -> Option<String>) -> Self {
+        let lookup = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| var(name).filter(|value| !value.is_empty()))
+        };
+        self.branch = self.branch.or_else(|| lookup(&BRANCH_VARS));
+        self.sha = self.sha.or_else(|| lookup(&SHA_VARS));
+        self.author = self.author.or_else(|| lookup(&AUTHOR_VARS));
+        self.commit_message = self.commit_message.or_else(|| lookup(&MESSAGE_VARS));
+        self
+    }
+}
+impl From<DeployNotification> for Notification {
+    fn from(deploy: DeployNotification) -> Self {
+        let message = match &deploy.version {
+            Some(version) => format!(
+                "{} {version} deployed to {}",
+                deploy.service, deploy.environment
+            ),
+            None => format!("{} deployed to {}", deploy.service, deploy.environment),
+        };
+        let mut notification = Notification::new(message)
+            .with_context("Service", &deploy.service)
+            .with_context("Environment", &deploy.environment);
+        notification.severity = Severity::Info;
+
+        let commit_message = deploy
+            .commit_message
+            .as_deref()
+            .and_then(|message| message.lines().next())
+            .map(String::from);
+        let details = [
+            ("Version", deploy.version),
+            ("Branch", deploy.branch),
+            ("Git SHA", deploy.sha),
+            ("Author", deploy.author),
+            ("Commit", commit_message),
+        ];
+        for (label, value) in details {
+            if let Some(value) = value {
+                notification = notification.with_context(label, value);
+            }
+        }
+
+        notification
+    }
+}
+
+/// Create a `DeployNotification` of the calling crate to an environment,
+/// with its package name as the service, its version, and the commit from
+/// the `GIT_BRANCH`, `GIT_SHA`, `GIT_AUTHOR` and `GIT_COMMIT_MESSAGE`
+/// build-time variables (if set)
+#[macro_export]
+macro_rules! deploy_notification {
+    ($environment:expr) => {{
+        let mut deploy = $crate::DeployNotification::new(env!("CARGO_PKG_NAME"), $environment)
+            .version(env!("CARGO_PKG_VERSION"));
+        if let Some(branch) = option_env!("GIT_BRANCH") {
+            deploy = deploy.branch(branch);
+        }
+        if let Some(sha) = option_env!("GIT_SHA") {
+            deploy = deploy.sha(sha);
+        }
+        if let Some(author) = option_env!("GIT_AUTHOR") {
+            deploy = deploy.author(author);
+        }
+        if let Some(message) = option_env!("GIT_COMMIT_MESSAGE") {
+            deploy = deploy.commit_message(message);
+        }
+        deploy
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeployNotification;
+    use crate::{Notification, Severity};
+    use std::collections::HashMap;
+
+    /// A test to make sure deployments are described with the commit as context
+    #[test]
+    fn describes_deployment() {
+        let notification: Notification = deploy_notification!("production")
+            .sha("4f2a9c1")
+            .commit_message("Fix checkout rounding\n\nCloses #42")
+            .into();
+        assert_eq!(
+            notification.message,
+            format!(
+                "dev_notify {} deployed to production",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+        assert_eq!(notification.severity, Severity::Info);
+
+        let labels: Vec<_> = notification
+            .context
+            .iter()
+            .map(|c| (c.label.as_str(), c.value.as_str()))
+            .collect();
+        assert_eq!(labels[0], ("Service", "dev_notify"));
+        assert_eq!(labels[1], ("Environment", "production"));
+        assert!(labels.contains(&("Git SHA", "4f2a9c1")));
+        assert!(labels.contains(&("Commit", "Fix checkout rounding")));
+    }
+
+    /// A test to make sure CI variables fill in the commit, without overriding what's set
+    #[test]
+    fn can_read_ci_vars() {
+        let vars = HashMap::from([
+            ("GITHUB_REF_NAME", "main"),
+            ("GITHUB_SHA", "9b1e7d0"),
+            ("GIT_AUTHOR", ""),
+            ("GITHUB_ACTOR", "octocat"),
+        ]);
+        let notification: Notification = DeployNotification::new("api", "staging")
+            .sha("4f2a9c1")
+            .with_vars(|name| vars.get(name).map(|value| value.to_string()))
+            .into();
+
+        let value = |label| {
+            notification
+                .context
+                .iter()
+                .find(|c| c.label == label)
+                .map(|c| c.value.as_str())
+        };
+        assert_eq!(notification.message, "api deployed to staging");
+        assert_eq!(value("Branch"), Some("main"));
+        assert_eq!(value("Git SHA"), Some("4f2a9c1"));
+        assert_eq!(value("Author"), Some("octocat"));
+        assert_eq!(value("Commit"), None);
+    }
+}
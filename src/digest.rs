@@ -0,0 +1,163 @@
+use crate::{Notification, Severity};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Collects low severity notifications into a periodic summary, so a `Worker`
+/// only sends e.g. "14 warnings in the last hour" instead of each warning,
+/// while anything at or above a severity still goes out immediately
+pub struct Digest {
+    interval: Duration,
+    below: Severity,
+    top: usize,
+    pending: Mutex<Vec<Notification>>,
+}
+impl Digest {
+    /// Summarize notifications below `Severity::Error` every `interval`
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            below: Severity::Error,
+            top: 3,
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Summarize notifications below a given severity (defaults to `Severity::Error`)
+    pub fn below(mut self, severity: Severity) -> Self {
+        self.below = severity;
+        self
+    }
+
+    /// How many of the most frequent messages to list in a summary (defaults to 3)
+    pub fn top(mut self, top: usize) -> Self {
+        self.top = top;
+        self
+    }
+
+    /// How often a summary is sent
+    pub(crate) fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Hold a notification for the next summary, handing it back
+    /// if it's severe enough to send immediately
+    pub(crate) fn collect(&self, notification: Notification) -> Option<Notification> {
+        if notification.severity >= self.below {
+            return Some(notification);
+        }
+        self.lock().push(notification);
+        None
+    }
+
+    /// Summarize the notifications held since the last summary, if any
+    pub(crate) fn summary(&self) -> Option<Notification> {
+        let pending = std::mem::take(&mut *self.lock());
+        let severity = pending.iter().map(|n| n.severity).max()?;
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for notification in &pending {
+            *counts.entry(&notification.message).or_default() += 1;
+        }
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts.truncate(self.top);
+
+        let kind = if pending.iter().all(|n| n.severity == severity) {
+            plural(severity)
+        } else {
+            "notifications"
+        };
+        let mut message = format!(
+            "{} {kind} in the last {}",
+            pending.len(),
+            period(self.interval)
+        );
+        if !counts.is_empty() {
+            let top: Vec<_> = counts
+                .iter()
+                .map(|(message, count)| format!("`{message}` ({count})"))
+                .collect();
+            message.push_str(&format!(", top {}: {}", counts.len(), top.join(", ")));
+        }
+
+        let mut notification = Notification::new(message);
+        notification.severity = severity;
+        Some(notification)
+    }
+
+    /// Lock the pending notifications, even if a panicking thread poisoned them
+    fn lock(&self) -> MutexGuard<'_, Vec<Notification>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// What to call several notifications of a severity
+fn plural(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Debug => "debug messages",
+        Severity::Info => "info messages",
+        Severity::Warning => "warnings",
+        Severity::Error => "errors",
+        Severity::Critical => "critical errors",
+    }
+}
+
+/// Describe a digest interval, e.g. "hour" or "15 minutes"
+fn period(interval: Duration) -> String {
+    match interval.as_secs() {
+        0 => format!("{}ms", interval.as_millis()),
+        3600 => String::from("hour"),
+        60 => String::from("minute"),
+        secs if secs.is_multiple_of(3600) => format!("{} hours", secs / 3600),
+        secs if secs.is_multiple_of(60) => format!("{} minutes", secs / 60),
+        secs => format!("{secs}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Digest;
+    use crate::{Notification, Severity};
+    use std::time::Duration;
+
+    fn warning(message: &str) -> Notification {
+        let mut notification = Notification::new(message);
+        notification.severity = Severity::Warning;
+        notification
+    }
+
+    /// A test to make sure low severity notifications are summarized by frequency
+    #[test]
+    fn summarizes_low_severity() {
+        let digest = Digest::new(Duration::from_secs(3600)).top(2);
+        for message in ["Disk 90% full", "Slow query", "Disk 90% full", "Cache miss"] {
+            assert!(digest.collect(warning(message)).is_none());
+        }
+
+        let summary = digest.summary().unwrap();
+        assert_eq!(summary.severity, Severity::Warning);
+        assert_eq!(
+            summary.message,
+            "4 warnings in the last hour, top 2: `Disk 90% full` (2), `Cache miss` (1)"
+        );
+        assert!(digest.summary().is_none());
+    }
+
+    /// A test to make sure severe notifications are handed back to send immediately
+    #[test]
+    fn passes_through_severe() {
+        let digest = Digest::new(Duration::from_secs(900));
+        assert!(digest.collect(Notification::new("Database down")).is_some());
+        assert!(digest.summary().is_none());
+
+        let mut info = Notification::new("Deploy started");
+        info.severity = Severity::Info;
+        digest.collect(info);
+        digest.collect(warning("Slow query"));
+        assert_eq!(
+            digest.summary().unwrap().message,
+            "2 notifications in the last 15 minutes, top 2: `Deploy started` (1), `Slow query` (1)"
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use crate::render::fit;
+use crate::{
+    Attachment, Context, ContextKind, Endpoint, Error, Formatter, Notification, OversizePolicy,
+    RenderedPayload,
+};
+use serde_json::{json, Value};
+
+/// The most fields Discord renders in a single embed
+const MAX_FIELDS: usize = 25;
+
+/// The most characters Discord renders in the title of an embed
+const MAX_TITLE: usize = 256;
+
+/// The most characters Discord renders in the description of an embed
+const MAX_DESCRIPTION: usize = 4096;
+
+/// The most characters Discord renders in the name of a field
+const MAX_FIELD_NAME: usize = 256;
+
+/// The most characters Discord renders in the value of a field
+const MAX_FIELD_VALUE: usize = 1024;
+
+/// Renders notifications as Discord embeds, as `MessageFormat::Discord`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct DiscordFormatter {
+    policy: OversizePolicy,
+}
+impl DiscordFormatter {
+    /// Create a `DiscordFormatter` with the options of a destination
+    pub(crate) fn for_destination(destination: &Endpoint) -> Self {
+        Self {
+            policy: destination.oversize_policy(),
+        }
+    }
+}
+impl Formatter for DiscordFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let payload = notification.clone().into_discord_payload(self.policy)?;
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into a Discord webhook payload
+    /// with a single embed, fitting it within the limits of Discord
+    ///
+    /// Context entries become fields (inline unless they're code), each
+    /// section a field listing its context, and the severity the color bar.
+    pub(crate) fn into_discord_payload(self, policy: OversizePolicy) -> Result<Value, Error> {
+        let labels = self.labels.unwrap_or_default();
+
+        let mut fields: Vec<Value> = self.context.iter().map(context_field).collect();
+        for section in &self.sections {
+            let lines: Vec<String> = section
+                .context
+                .iter()
+                .map(|ctx| format!("**{}**: {}", ctx.label, discord_value(ctx)))
+                .collect();
+            fields.push(json!({
+                "name": section.title,
+                "value": lines.join("\n"),
+                "inline": false,
+            }));
+        }
+        if fields.len() > MAX_FIELDS {
+            if policy == OversizePolicy::Reject {
+                return Err(Error::PayloadTooLarge {
+                    limit: MAX_FIELDS,
+                    size: fields.len(),
+                });
+            }
+            fields.truncate(MAX_FIELDS);
+        }
+        for field in &mut fields {
+            fit(&mut field["name"], MAX_FIELD_NAME, policy)?;
+            fit(&mut field["value"], MAX_FIELD_VALUE, policy)?;
+        }
+
+        let mut title = Value::String(format!("{}: {}", labels.issue, self.message));
+        fit(&mut title, MAX_TITLE, policy)?;
+        let mut embed = json!({
+            "title": title,
+            "color": self.severity.color(),
+            "fields": fields,
+            "footer": { "text": format!("{}: {}", labels.timestamp, self.timestamp) },
+        });
+        if !self.attachments.is_empty() {
+            let attachments: Vec<String> = self
+                .attachments
+                .into_iter()
+                .map(inline_attachment)
+                .collect();
+            let mut description = Value::String(attachments.join("\n"));
+            fit(&mut description, MAX_DESCRIPTION, policy)?;
+            embed["description"] = description;
+        }
+
+        Ok(json!({ "embeds": [embed] }))
+    }
+}
+
+/// Render a `Context` as an embed field, inline unless it's a code block
+fn context_field(ctx: &Context) -> Value {
+    json!({
+        "name": ctx.label,
+        "value": discord_value(ctx),
+        "inline": ctx.kind != ContextKind::Code,
+    })
+}
+
+/// Render the value of a `Context` in Discord markdown
+fn discord_value(ctx: &Context) -> String {
+    match &ctx.kind {
+        ContextKind::Text => ctx.value.clone(),
+        ContextKind::Code => format!("```\n{}\n```", ctx.value),
+        ContextKind::Link { text } => format!("[{text}]({})", ctx.value),
+    }
+}
+
+/// Render an `Attachment` inline as a code block
+fn inline_attachment(attachment: Attachment) -> String {
+    format!("**{}**\n```{}```", attachment.filename, attachment.content)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, MessageFormat, Notification, OversizePolicy, Section, Severity};
+    use serde_json::Value;
+
+    /// A test to make sure context becomes embed fields and severity the color
+    #[test]
+    fn can_parse_into_embed() {
+        let mut notification = Notification::new("Payment failed")
+            .with_context("Order ID", 1042)
+            .with_section(Section::new("Request").with_context("Method", "POST"))
+            .with_attachment("response.json", "{}");
+        notification.severity = Severity::Critical;
+
+        let payload: Value = serde_json::from_str(
+            &notification
+                .into_payload(MessageFormat::Discord, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        let embed = &payload["embeds"][0];
+        assert_eq!(embed["title"], "Issue: Payment failed");
+        assert_eq!(embed["color"], 0x992d22);
+        assert_eq!(embed["fields"][0]["name"], "Order ID");
+        assert_eq!(embed["fields"][0]["value"], "1042");
+        assert_eq!(embed["fields"][0]["inline"], true);
+        assert_eq!(embed["fields"][1]["name"], "Request");
+        assert_eq!(embed["fields"][1]["value"], "**Method**: POST");
+        assert_eq!(embed["description"], "**response.json**\n```{}```");
+        assert!(payload.get("content").is_none());
+    }
+
+    /// A test to make sure embeds over Discord's limits are truncated or rejected per policy
+    #[test]
+    fn fits_embed_within_limits() {
+        let mut notification = Notification::new("x".repeat(300));
+        for idx in 0..30 {
+            notification = notification.with_context(format!("Entry {idx}"), idx);
+        }
+
+        let payload = notification
+            .clone()
+            .into_discord_payload(OversizePolicy::Truncate)
+            .unwrap();
+        let embed = &payload["embeds"][0];
+        assert_eq!(embed["fields"].as_array().unwrap().len(), 25);
+        assert_eq!(embed["title"].as_str().unwrap().chars().count(), 256);
+
+        assert!(matches!(
+            notification.into_discord_payload(OversizePolicy::Reject),
+            Err(Error::PayloadTooLarge { limit: 25, .. })
+        ));
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{Error, Notification, Notifier, Severity};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+use tokio::task::JoinHandle;
+
+/// Where the Docker daemon listens by default
+#[cfg(unix)]
+const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+/// How long to wait before reconnecting a dropped event stream
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// The events API request, for the container events we notify on
+const EVENTS_REQUEST: &str = "GET /events?filters=%7B%22type%22%3A%5B%22container%22%5D%2C\
+     %22event%22%3A%5B%22die%22%2C%22oom%22%2C%22restart%22%5D%7D HTTP/1.0\r\n\
+     Host: docker\r\n\r\n";
+
+/// Where the Docker daemon is reached
+#[derive(Clone, Debug)]
+enum Address {
+    #[cfg(unix)]
+    Unix(String),
+    Tcp(String),
+}
+
+/// Watches the Docker events API, notifying when containers die with a
+/// non-zero exit code, are OOM killed or restart, with the container name,
+/// image and exit code as context, routed like any other notification
+///
+/// For single hosts without an orchestrator; repeats for a container share
+/// a fingerprint, so grouping can fold restart loops.
+#[derive(Clone, Debug)]
+pub struct DockerWatcher {
+    address: Address,
+}
+impl DockerWatcher {
+    /// Create a `DockerWatcher` for the local daemon, through its socket
+    /// at `/var/run/docker.sock`
+    #[cfg(unix)]
+    pub fn new() -> Self {
+        Self::unix(DEFAULT_SOCKET)
+    }
+
+    /// Create a `DockerWatcher` for a daemon listening on a Unix socket
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<String>) -> Self {
+        Self {
+            address: Address::Unix(path.into()),
+        }
+    }
+
+    /// Create a `DockerWatcher` for a daemon listening on TCP without TLS,
+    /// like `127.0.0.1:2375`
+    pub fn tcp(address: impl Into<String>) -> Self {
+        Self {
+            address: Address::Tcp(address.into()),
+        }
+    }
+
+    /// Spawn a task on the current tokio runtime watching container events,
+    /// sending a notification through a given `Notifier` (routed by it) for
+    /// each one, and reconnecting whenever the stream drops
+    ///
+    /// Aborting the returned task stops watching.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn(self, notifier: Arc<Notifier>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = self.connect(&notifier).await {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(error = %e, "docker event stream dropped");
+                    #[cfg(not(feature = "tracing"))]
+                    let _ = e;
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        })
+    }
+
+    /// Connect to the daemon and stream events until the connection closes
+    async fn connect(&self, notifier: &Notifier) -> Result<(), Error> {
+        let connect_error = |e| Error::Config(format!("couldn't connect to docker: {e}"));
+        match &self.address {
+            #[cfg(unix)]
+            Address::Unix(path) => {
+                let stream = UnixStream::connect(path).await.map_err(connect_error)?;
+                watch(stream, notifier).await
+            }
+            Address::Tcp(address) => {
+                let stream = TcpStream::connect(address).await.map_err(connect_error)?;
+                watch(stream, notifier).await
+            }
+        }
+    }
+}
+#[cfg(unix)]
+impl Default for DockerWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stream events from a connection to the daemon until it closes
+///
+/// HTTP/1.0 keeps the response unchunked, a line of JSON per event.
+async fn watch(
+    mut stream: impl AsyncRead + AsyncWrite + Unpin,
+    notifier: &Notifier,
+) -> Result<(), Error> {
+    let io_error = |e: std::io::Error| Error::Config(format!("docker event stream: {e}"));
+    stream
+        .write_all(EVENTS_REQUEST.as_bytes())
+        .await
+        .map_err(io_error)?;
+
+    let mut lines = BufReader::new(stream).lines();
+    let status = lines.next_line().await.map_err(io_error)?;
+    if !status
+        .as_deref()
+        .is_some_and(|status| status.contains(" 200 "))
+    {
+        return Err(Error::Config(format!(
+            "docker events API responded with {}",
+            status.unwrap_or_default()
+        )));
+    }
+    // Skip the headers
+    while let Some(header) = lines.next_line().await.map_err(io_error)? {
+        if header.is_empty() {
+            break;
+        }
+    }
+
+    while let Some(line) = lines.next_line().await.map_err(io_error)? {
+        let notification = serde_json::from_str::<Event>(&line)
+            .ok()
+            .and_then(|event| event.notification());
+        if let Some(notification) = notification {
+            // Failures are already handled by the `Notifier`
+            let _ = notifier.notify(notification).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// The parts of a container event we read
+#[derive(Deserialize)]
+struct Event {
+    #[serde(rename = "Action")]
+    action: String,
+    #[serde(rename = "Actor")]
+    actor: Actor,
+}
+
+#[derive(Deserialize)]
+struct Actor {
+    #[serde(rename = "ID")]
+    id: String,
+    #[serde(rename = "Attributes", default)]
+    attributes: HashMap<String, String>,
+}
+
+impl Event {
+    /// Describe the event as a `Notification`, unless it's a clean exit
+    fn notification(&self) -> Option<Notification> {
+        let attribute = |name: &str| self.actor.attributes.get(name).map(String::as_str);
+        let exit_code = attribute("exitCode");
+        let (description, severity) = match self.action.as_str() {
+            "die" if exit_code == Some("0") => return None,
+            "die" => ("died", Severity::Error),
+            "oom" => ("was OOM killed", Severity::Error),
+            "restart" => ("restarted", Severity::Warning),
+            _ => return None,
+        };
+        let id: String = self.actor.id.chars().take(12).collect();
+        let name = attribute("name").unwrap_or(&id);
+
+        let mut notification = Notification::new(format!("Container {name} {description}"))
+            .with_context("Container", name);
+        if let Some(image) = attribute("image") {
+            notification = notification.with_context("Image", image);
+        }
+        if let Some(exit_code) = exit_code {
+            notification = notification.with_context("Exit Code", exit_code);
+        }
+        notification = notification.with_context("ID", &id);
+        notification.severity = severity;
+        notification.fingerprint = Some(format!("docker:{name}"));
+
+        Some(notification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{watch, Event};
+    use crate::testing::MockDestination;
+    use crate::{Notifier, Severity};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const DIE: &str = r#"{"status":"die","id":"4f2a9c1e8b7d6a5f","from":"nginx:1.25","Type":"container","Action":"die","Actor":{"ID":"4f2a9c1e8b7d6a5f","Attributes":{"exitCode":"137","image":"nginx:1.25","name":"web"}},"scope":"local","time":1705692380}"#;
+
+    /// A test to make sure container events become notifications, skipping clean exits
+    #[test]
+    fn can_parse_container_event() {
+        let notification = serde_json::from_str::<Event>(DIE)
+            .unwrap()
+            .notification()
+            .unwrap();
+        assert_eq!(notification.message, "Container web died");
+        assert_eq!(notification.severity, Severity::Error);
+        assert_eq!(notification.fingerprint.as_deref(), Some("docker:web"));
+        let context: Vec<_> = notification
+            .context
+            .iter()
+            .map(|c| (c.label.as_str(), c.value.as_str()))
+            .collect();
+        assert_eq!(
+            context,
+            [
+                ("Container", "web"),
+                ("Image", "nginx:1.25"),
+                ("Exit Code", "137"),
+                ("ID", "4f2a9c1e8b7d"),
+            ]
+        );
+
+        let clean_exit = DIE.replace("\"137\"", "\"0\"");
+        let event: Event = serde_json::from_str(&clean_exit).unwrap();
+        assert!(event.notification().is_none());
+    }
+
+    /// A test to make sure events streamed by the daemon are notified
+    #[tokio::test]
+    async fn notifies_streamed_events() {
+        let mock = MockDestination::new();
+        let notifier = Notifier::builder()
+            .destination("ops", mock.endpoint())
+            .build()
+            .unwrap();
+
+        let (client, mut daemon) = tokio::io::duplex(4096);
+        let serve = async move {
+            let mut request = [0; 64];
+            let read = daemon.read(&mut request).await.unwrap();
+            assert!(request[..read].starts_with(b"GET /events?filters="));
+            let oom = DIE.replace("\"Action\":\"die\"", "\"Action\":\"oom\"");
+            let response =
+                format!("HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{DIE}\n{oom}\n");
+            daemon.write_all(response.as_bytes()).await.unwrap();
+        };
+        let (result, ()) = tokio::join!(watch(client, &notifier), serve);
+        result.unwrap();
+
+        let delivered = mock.delivered();
+        assert_eq!(delivered.len(), 2);
+        assert_eq!(delivered[1].message, "Container web was OOM killed");
+    }
+}
@@ -0,0 +1,402 @@
+use crate::render::escape_html;
+use crate::{Context, ContextKind, Endpoint, Error, Formatter, Notification, RenderedPayload};
+use serde_json::{json, Value};
+
+/// The template of HTML emails, unless overridden
+const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<body style="font-family: sans-serif">
+<p><span style="background: {{color}}; color: #ffffff; padding: 2px 8px; border-radius: 4px">{{severity}}</span></p>
+<h2>{{issue_label}}: {{message}}</h2>
+<p><i>{{timestamp_label}}: {{timestamp}}</i></p>
+<table cellpadding="4">{{context}}</table>
+{{attachments}}
+</body>
+</html>"#;
+
+/// An HTTP email API to send emails through, for environments blocking SMTP
+///
+/// With SendGrid the destination is `https://api.sendgrid.com/v3/mail/send`,
+/// authenticated with `Endpoint::bearer_auth`. With Mailgun it's
+/// `https://api.mailgun.net/v3/{domain}/messages`, authenticated with
+/// `Endpoint::basic_auth("api", key)`. Emails go to the recipient set as the
+/// channel.
+#[derive(Clone, Debug)]
+pub struct EmailApi {
+    provider: EmailProvider,
+    from: String,
+}
+impl EmailApi {
+    /// Send through the SendGrid v3 API from a given sender
+    pub fn sendgrid(from: impl Into<String>) -> Self {
+        Self {
+            provider: EmailProvider::SendGrid,
+            from: from.into(),
+        }
+    }
+
+    /// Send through the Mailgun API from a given sender
+    pub fn mailgun(from: impl Into<String>) -> Self {
+        Self {
+            provider: EmailProvider::Mailgun,
+            from: from.into(),
+        }
+    }
+
+    /// The content type of requests to the API
+    pub(crate) fn content_type(&self) -> &'static str {
+        match self.provider {
+            EmailProvider::SendGrid => "application/json",
+            EmailProvider::Mailgun => "application/x-www-form-urlencoded",
+        }
+    }
+
+    /// Reshape an email payload into the request body the API takes
+    fn request_body(&self, email: Value) -> String {
+        let field = |name: &str| email[name].as_str().unwrap_or_default().to_string();
+        match self.provider {
+            EmailProvider::SendGrid => {
+                let to: Vec<Value> = email["to"]
+                    .as_str()
+                    .map(|to| json!({ "email": to }))
+                    .into_iter()
+                    .collect();
+                json!({
+                    "personalizations": [{ "to": to }],
+                    "from": { "email": self.from },
+                    "subject": field("subject"),
+                    "content": [
+                        { "type": "text/plain", "value": field("text") },
+                        { "type": "text/html", "value": field("html") },
+                    ],
+                })
+                .to_string()
+            }
+            EmailProvider::Mailgun => form_encode(&[
+                ("from", &self.from),
+                ("to", &field("to")),
+                ("subject", &field("subject")),
+                ("text", &field("text")),
+                ("html", &field("html")),
+            ]),
+        }
+    }
+}
+
+/// The providers of `EmailApi`
+#[derive(Clone, Copy, Debug)]
+enum EmailProvider {
+    SendGrid,
+    Mailgun,
+}
+
+/// The HTML body of emails, with `{{placeholders}}` filled in per notification
+///
+/// The placeholders are `subject`, `message`, `severity`, `color` (of the
+/// severity, like `#e74c3c`), `timestamp`, `issue_label`, `timestamp_label`,
+/// `context` (table rows, sections included) and `attachments`. Values are
+/// escaped, unknown placeholders are left as is.
+#[derive(Clone, Debug)]
+pub struct EmailTemplate {
+    html: String,
+}
+impl EmailTemplate {
+    /// Create an `EmailTemplate` from HTML with placeholders
+    pub fn new(html: impl Into<String>) -> Self {
+        Self { html: html.into() }
+    }
+
+    /// Fill in the placeholders of the template for a `Notification`
+    fn render(&self, notification: &Notification) -> String {
+        let labels = notification.labels.clone().unwrap_or_default();
+        let mut context: String = notification.context.iter().map(context_row).collect();
+        for section in &notification.sections {
+            context.push_str(&format!(
+                "<tr><th colspan=\"2\" align=\"left\">{}</th></tr>",
+                escape_html(&section.title)
+            ));
+            context.extend(section.context.iter().map(context_row));
+        }
+        let attachments: String = notification
+            .attachments
+            .iter()
+            .map(|attachment| {
+                format!(
+                    "<p><b>{}</b></p><pre>{}</pre>",
+                    escape_html(&attachment.filename),
+                    escape_html(&attachment.content)
+                )
+            })
+            .collect();
+
+        fill(
+            &self.html,
+            &[
+                ("subject", escape_html(&subject(notification))),
+                ("message", escape_html(&notification.message)),
+                ("severity", format!("{:?}", notification.severity)),
+                ("color", format!("#{:06x}", notification.severity.color())),
+                ("timestamp", escape_html(&notification.timestamp)),
+                ("issue_label", escape_html(&labels.issue)),
+                ("timestamp_label", escape_html(&labels.timestamp)),
+                ("context", context),
+                ("attachments", attachments),
+            ],
+        )
+    }
+}
+impl Default for EmailTemplate {
+    fn default() -> Self {
+        Self::new(DEFAULT_TEMPLATE)
+    }
+}
+
+/// Renders notifications as emails, as `MessageFormat::Email`: a generic
+/// payload, or the request body of an `EmailApi` when the destination has one
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct EmailFormatter<'a> {
+    template: Option<&'a EmailTemplate>,
+    api: Option<&'a EmailApi>,
+}
+impl<'a> EmailFormatter<'a> {
+    /// Create an `EmailFormatter` with the options of a destination
+    pub(crate) fn for_destination(destination: &'a Endpoint) -> Self {
+        Self {
+            template: destination.email_template_config(),
+            api: destination.email_api_config(),
+        }
+    }
+}
+impl Formatter for EmailFormatter<'_> {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let default = EmailTemplate::default();
+        let template = self.template.unwrap_or(&default);
+        let notification = notification.clone();
+        Ok(match self.api {
+            Some(api) => RenderedPayload::new(
+                notification.into_email_api_body(template, api),
+                api.content_type(),
+            ),
+            None => RenderedPayload::json(notification.into_email_payload(template).to_string()),
+        })
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into an email payload for
+    /// HTTP email APIs, with an HTML body rendered from a given template and
+    /// a plain text alternative, to the recipient set as the channel
+    pub(crate) fn into_email_payload(self, template: &EmailTemplate) -> Value {
+        let mut payload = json!({
+            "subject": subject(&self),
+            "html": template.render(&self),
+            "text": self.plain_text(),
+        });
+        if let Some(to) = self.channel {
+            payload["to"] = Value::String(to);
+        }
+
+        payload
+    }
+
+    /// Consume the `Notification` and parse it into the request body of an
+    /// HTTP email API, rendered from a given template
+    pub(crate) fn into_email_api_body(self, template: &EmailTemplate, api: &EmailApi) -> String {
+        api.request_body(self.into_email_payload(template))
+    }
+
+    /// Render the `Notification` as plain text, without any markup
+    fn plain_text(&self) -> String {
+        let labels = self.labels.clone().unwrap_or_default();
+        let mut text = format!(
+            "{}: {}\n{}: {}\n",
+            labels.issue, self.message, labels.timestamp, self.timestamp
+        );
+        for ctx in &self.context {
+            text.push_str(&format!("{}: {}\n", ctx.label, ctx.value));
+        }
+        for section in &self.sections {
+            text.push_str(&format!("\n{}\n", section.title));
+            for ctx in &section.context {
+                text.push_str(&format!("{}: {}\n", ctx.label, ctx.value));
+            }
+        }
+        for attachment in &self.attachments {
+            text.push_str(&format!(
+                "\n{}\n{}\n",
+                attachment.filename, attachment.content
+            ));
+        }
+
+        text
+    }
+}
+
+/// The subject line of an email, like `[Critical] Database down`
+fn subject(notification: &Notification) -> String {
+    format!("[{:?}] {}", notification.severity, notification.message)
+}
+
+/// Render a `Context` as an HTML table row
+fn context_row(ctx: &Context) -> String {
+    let value = match &ctx.kind {
+        ContextKind::Text => escape_html(&ctx.value),
+        ContextKind::Code => format!("<pre>{}</pre>", escape_html(&ctx.value)),
+        ContextKind::Link { text } => format!(
+            "<a href=\"{}\">{}</a>",
+            escape_html(&ctx.value),
+            escape_html(text)
+        ),
+    };
+
+    format!(
+        "<tr><th align=\"left\">{}</th><td>{value}</td></tr>",
+        escape_html(&ctx.label)
+    )
+}
+
+/// Encode fields as an `application/x-www-form-urlencoded` body
+fn form_encode(fields: &[(&str, &str)]) -> String {
+    let encode = |text: &str| {
+        let mut encoded = String::with_capacity(text.len());
+        for byte in text.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => {
+                    encoded.push(byte as char)
+                }
+                b' ' => encoded.push('+'),
+                _ => encoded.push_str(&format!("%{byte:02X}")),
+            }
+        }
+        encoded
+    };
+
+    fields
+        .iter()
+        .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Replace `{{name}}` placeholders with their values in a single pass, so
+/// values are never filled in themselves
+fn fill(template: &str, values: &[(&str, String)]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let value = after.find("}}").and_then(|end| {
+            let name = after[..end].trim();
+            let (_, value) = values.iter().find(|(n, _)| *n == name)?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                filled.push_str(value);
+                rest = &after[end + 2..];
+            }
+            None => {
+                filled.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{form_encode, EmailApi, EmailTemplate};
+    use crate::{
+        Context, Endpoint, MessageFormat, Notification, OversizePolicy, Section, Severity,
+    };
+    use serde_json::Value;
+
+    /// A test to make sure emails have an HTML body with a plain text alternative
+    #[test]
+    fn can_parse_into_email() {
+        let mut notification = Notification::new("Payment <failed>")
+            .with_context("Order ID", 1042)
+            .with_section(Section::new("Request").with_context("Method", "POST"))
+            .with_channel("oncall@example.com");
+        notification.severity = Severity::Critical;
+        notification.context.push(Context::link(
+            "Order",
+            "https://shop.example.com/1042",
+            "View",
+        ));
+
+        let payload: Value = serde_json::from_str(
+            &notification
+                .into_payload(MessageFormat::Email, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(payload["to"], "oncall@example.com");
+        assert_eq!(payload["subject"], "[Critical] Payment <failed>");
+        let html = payload["html"].as_str().unwrap();
+        assert!(html.contains("background: #992d22"));
+        assert!(html.contains("<h2>Issue: Payment &lt;failed&gt;</h2>"));
+        assert!(html.contains("<tr><th align=\"left\">Order ID</th><td>1042</td></tr>"));
+        assert!(html.contains("<a href=\"https://shop.example.com/1042\">View</a>"));
+        assert!(html.contains("<th colspan=\"2\" align=\"left\">Request</th>"));
+        let text = payload["text"].as_str().unwrap();
+        assert!(text.starts_with("Issue: Payment <failed>\n"));
+        assert!(text.ends_with("\nRequest\nMethod: POST\n"));
+    }
+
+    /// A test to make sure emails are reshaped for the SendGrid and Mailgun APIs
+    #[test]
+    fn can_send_through_email_apis() {
+        let notification = Notification::new("Disk full").with_channel("oncall@example.com");
+
+        let sendgrid = Endpoint::new("https://api.sendgrid.com/v3/mail/send")
+            .email_api(EmailApi::sendgrid("alerts@example.com"));
+        let rendered = notification.clone().render_for(&sendgrid).unwrap();
+        assert_eq!(rendered.content_type, "application/json");
+        let payload: Value = serde_json::from_str(&rendered.body).unwrap();
+        assert_eq!(
+            payload["personalizations"][0]["to"][0]["email"],
+            "oncall@example.com"
+        );
+        assert_eq!(payload["from"]["email"], "alerts@example.com");
+        assert_eq!(payload["subject"], "[Error] Disk full");
+        assert_eq!(payload["content"][1]["type"], "text/html");
+
+        let mailgun = Endpoint::new("https://api.mailgun.net/v3/example.com/messages")
+            .email_api(EmailApi::mailgun("Alerts <alerts@example.com>"))
+            .basic_auth("api", "key-123");
+        let rendered = notification.render_for(&mailgun).unwrap();
+        assert_eq!(rendered.content_type, "application/x-www-form-urlencoded");
+        assert!(rendered.body.starts_with(
+            "from=Alerts+%3Calerts%40example.com%3E&to=oncall%40example.com\
+             &subject=%5BError%5D+Disk+full&text="
+        ));
+        assert!(rendered.body.contains("&html=%3C%21DOCTYPE+html%3E"));
+    }
+
+    /// A test to make sure form fields are percent encoded
+    #[test]
+    fn encodes_form() {
+        assert_eq!(
+            form_encode(&[("to", "a+b@example.com"), ("text", "50% off\né")]),
+            "to=a%2Bb%40example.com&text=50%25+off%0A%C3%A9"
+        );
+    }
+
+    /// A test to make sure custom templates fill in known placeholders only, once
+    #[test]
+    fn can_override_template() {
+        let template = EmailTemplate::new("<h1>{{ severity }}</h1>{{message}}{{unknown}}");
+        let notification = Notification::new("{{severity}} & more");
+
+        let payload = notification.into_email_payload(&template);
+        assert_eq!(
+            payload["html"],
+            "<h1>Error</h1>{{severity}} &amp; more{{unknown}}"
+        );
+    }
+}
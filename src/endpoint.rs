@@ -0,0 +1,594 @@
+#[cfg(any(test, feature = "testing"))]
+use crate::testing::MockDestination;
+#[cfg(feature = "github-actions")]
+use crate::GithubActions;
+#[cfg(feature = "gitlab")]
+use crate::GitlabIssue;
+#[cfg(feature = "pubsub")]
+use crate::PubSub;
+#[cfg(feature = "service-bus")]
+use crate::ServiceBus;
+#[cfg(feature = "signal")]
+use crate::Signal;
+#[cfg(feature = "sms")]
+use crate::Sms;
+#[cfg(feature = "web-push")]
+use crate::WebPush;
+#[cfg(feature = "email")]
+use crate::{EmailApi, EmailTemplate};
+use crate::{Formatter, RateLimit};
+use crate::{MessageFormat, OversizePolicy, Severity, SeverityEmoji};
+use reqwest::{Client, Method, RequestBuilder};
+use std::sync::Arc;
+
+/// An HTTP destination (API endpoint) along with any
+/// extra headers required to deliver to it
+#[derive(Clone, Debug)]
+pub struct Endpoint {
+    url: String,
+    headers: Vec<(String, String)>,
+    bearer_token: Option<String>,
+    basic_auth: Option<(String, String)>,
+    min_severity: Option<Severity>,
+    format: MessageFormat,
+    oversize: OversizePolicy,
+    #[cfg(feature = "slack")]
+    action_buttons: bool,
+    channel: Option<String>,
+    severity_emoji: Option<SeverityEmoji>,
+    unfurl_links: Option<bool>,
+    unfurl_media: Option<bool>,
+    gzip_min_len: Option<usize>,
+    rate_limit: Option<RateLimit>,
+    #[cfg(feature = "email")]
+    email_template: Option<EmailTemplate>,
+    #[cfg(feature = "email")]
+    email_api: Option<EmailApi>,
+    #[cfg(feature = "sms")]
+    sms: Option<Sms>,
+    #[cfg(feature = "gitlab")]
+    gitlab: Option<GitlabIssue>,
+    #[cfg(feature = "signal")]
+    signal: Option<Signal>,
+    formatter: Option<Arc<dyn Formatter>>,
+    #[cfg(feature = "signing")]
+    signing_secret: Option<String>,
+    #[cfg(feature = "github-actions")]
+    github_actions: Option<GithubActions>,
+    #[cfg(feature = "web-push")]
+    web_push: Option<WebPush>,
+    #[cfg(feature = "pubsub")]
+    pubsub: Option<PubSub>,
+    #[cfg(feature = "service-bus")]
+    service_bus: Option<ServiceBus>,
+    #[cfg(any(test, feature = "testing"))]
+    mock: Option<MockDestination>,
+}
+impl Endpoint {
+    /// Create an `Endpoint` for a given URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: Vec::new(),
+            bearer_token: None,
+            basic_auth: None,
+            min_severity: None,
+            format: MessageFormat::default(),
+            oversize: OversizePolicy::default(),
+            #[cfg(feature = "slack")]
+            action_buttons: false,
+            channel: None,
+            severity_emoji: None,
+            unfurl_links: None,
+            unfurl_media: None,
+            gzip_min_len: None,
+            rate_limit: None,
+            #[cfg(feature = "email")]
+            email_template: None,
+            #[cfg(feature = "email")]
+            email_api: None,
+            #[cfg(feature = "sms")]
+            sms: None,
+            #[cfg(feature = "gitlab")]
+            gitlab: None,
+            #[cfg(feature = "signal")]
+            signal: None,
+            formatter: None,
+            #[cfg(feature = "signing")]
+            signing_secret: None,
+            #[cfg(feature = "github-actions")]
+            github_actions: None,
+            #[cfg(feature = "web-push")]
+            web_push: None,
+            #[cfg(feature = "pubsub")]
+            pubsub: None,
+            #[cfg(feature = "service-bus")]
+            service_bus: None,
+            #[cfg(any(test, feature = "testing"))]
+            mock: None,
+        }
+    }
+
+    /// The URL of the `Endpoint`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The scheme and host of the `Endpoint`, safe to log unlike the full URL
+    /// (webhook URLs usually embed a secret token in their path)
+    #[cfg(feature = "tracing")]
+    pub(crate) fn origin(&self) -> String {
+        match reqwest::Url::parse(&self.url) {
+            Ok(url) => format!("{}://{}", url.scheme(), url.host_str().unwrap_or_default()),
+            Err(_) => String::from("<invalid url>"),
+        }
+    }
+
+    /// Add a header sent with every request to the `Endpoint`
+    ///
+    /// Invalid header names or values are reported when sending.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Authenticate every request to the `Endpoint` with a bearer token
+    pub fn bearer_auth(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        self
+    }
+
+    /// Authenticate every request to the `Endpoint` with a username and password
+    pub fn basic_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.basic_auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Only deliver notifications at or above a given severity to the `Endpoint`
+    pub fn min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    /// Sign every request body to the `Endpoint` with a shared secret, so the
+    /// receiver can verify it came from us
+    ///
+    /// Requests carry the Unix timestamp they were signed at in an
+    /// `X-Dev-Notify-Timestamp` header, and `sha256=<hex>` in an
+    /// `X-Dev-Notify-Signature` header, the HMAC-SHA256 of `{timestamp}.{body}`.
+    #[cfg(feature = "signing")]
+    pub fn sign_with(mut self, secret: impl Into<String>) -> Self {
+        self.signing_secret = Some(secret.into());
+        self
+    }
+
+    /// Also report notifications to the `Endpoint` as workflow commands when
+    /// running inside GitHub Actions, see `GithubActions`
+    #[cfg(feature = "github-actions")]
+    pub fn github_actions(mut self, github_actions: GithubActions) -> Self {
+        self.github_actions = Some(github_actions);
+        self
+    }
+
+    /// How notifications to the `Endpoint` are reported inside GitHub Actions, if at all
+    #[cfg(feature = "github-actions")]
+    pub(crate) fn github_actions_config(&self) -> Option<&GithubActions> {
+        self.github_actions.as_ref()
+    }
+
+    /// Push notifications to the `Endpoint` (the URL of a push subscription)
+    /// encrypted for a `WebPush` subscription, in place of its format
+    #[cfg(feature = "web-push")]
+    pub fn web_push(mut self, web_push: WebPush) -> Self {
+        self.web_push = Some(web_push);
+        self
+    }
+
+    /// The Web Push subscription notifications are pushed to, if any
+    #[cfg(feature = "web-push")]
+    pub(crate) fn web_push_config(&self) -> Option<&WebPush> {
+        self.web_push.as_ref()
+    }
+
+    /// Publish notifications to the `Endpoint` (the publish URL of a Pub/Sub
+    /// topic) with given options, implies `MessageFormat::PubSub`
+    #[cfg(feature = "pubsub")]
+    pub fn pubsub(mut self, pubsub: PubSub) -> Self {
+        self.format = MessageFormat::PubSub;
+        self.pubsub = Some(pubsub);
+        self
+    }
+
+    /// The Pub/Sub options of the `Endpoint`, if any
+    #[cfg(feature = "pubsub")]
+    pub(crate) fn pubsub_config(&self) -> Option<&PubSub> {
+        self.pubsub.as_ref()
+    }
+
+    /// Send notifications to the `Endpoint` (the messages URL of a Service
+    /// Bus queue or topic) with given options, implies `MessageFormat::ServiceBus`
+    #[cfg(feature = "service-bus")]
+    pub fn service_bus(mut self, service_bus: ServiceBus) -> Self {
+        self.format = MessageFormat::ServiceBus;
+        self.service_bus = Some(service_bus);
+        self
+    }
+
+    /// The Service Bus options of the `Endpoint`, if any
+    #[cfg(feature = "service-bus")]
+    pub(crate) fn service_bus_config(&self) -> Option<&ServiceBus> {
+        self.service_bus.as_ref()
+    }
+
+    /// Render notifications for the `Endpoint` in a given format
+    /// (defaults to `MessageFormat::Blocks`)
+    pub fn format(mut self, format: MessageFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// What to do when a payload is over a limit of the `Endpoint`
+    /// (defaults to `OversizePolicy::Truncate`)
+    pub fn oversize(mut self, policy: OversizePolicy) -> Self {
+        self.oversize = policy;
+        self
+    }
+
+    /// End Slack messages to the `Endpoint` with "Acknowledge" and "Resolve"
+    /// buttons (`MessageFormat::Blocks` only)
+    ///
+    /// Clicks are posted to the Request URL of the Slack app, parse them with
+    /// `Interaction::from_request` and pass them to `Notifier::respond`.
+    #[cfg(feature = "slack")]
+    pub fn action_buttons(mut self) -> Self {
+        self.action_buttons = true;
+        self
+    }
+
+    /// Post notifications to a given channel unless they set their own with
+    /// `Notification::with_channel` (required by the Slack Web API)
+    pub fn channel(mut self, channel: impl Into<String>) -> Self {
+        self.channel = Some(channel.into());
+        self
+    }
+
+    /// Prefix messages to the `Endpoint` with an emoji for their severity
+    pub fn severity_emoji(mut self, emoji: SeverityEmoji) -> Self {
+        self.severity_emoji = Some(emoji);
+        self
+    }
+
+    /// The emoji prefixed to messages to the `Endpoint` by severity, if any
+    pub(crate) fn severity_emoji_config(&self) -> Option<&SeverityEmoji> {
+        self.severity_emoji.as_ref()
+    }
+
+    /// Whether Slack previews links in messages to the `Endpoint`, e.g.
+    /// `false` so URLs in the context don't bury later messages
+    /// (defaults to what Slack does)
+    pub fn unfurl_links(mut self, unfurl: bool) -> Self {
+        self.unfurl_links = Some(unfurl);
+        self
+    }
+
+    /// Whether Slack previews media (images, videos) linked in messages to
+    /// the `Endpoint` (defaults to what Slack does)
+    pub fn unfurl_media(mut self, unfurl: bool) -> Self {
+        self.unfurl_media = Some(unfurl);
+        self
+    }
+
+    /// Whether Slack previews links and media in messages to the `Endpoint`, if set
+    pub(crate) fn unfurl_config(&self) -> (Option<bool>, Option<bool>) {
+        (self.unfurl_links, self.unfurl_media)
+    }
+
+    /// Gzip request bodies to the `Endpoint` of at least a given size in
+    /// bytes, sent with `Content-Encoding: gzip`, e.g. for webhooks taking
+    /// long attachments or log excerpts
+    ///
+    /// Signatures are of the uncompressed body.
+    pub fn gzip(mut self, min_len: usize) -> Self {
+        self.gzip_min_len = Some(min_len);
+        self
+    }
+
+    /// The channel notifications are posted to by default, if any
+    pub(crate) fn default_channel(&self) -> Option<&str> {
+        self.channel.as_deref()
+    }
+
+    /// Limit how many notifications the `Endpoint` takes in a period,
+    /// see `RateLimit`
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// How many notifications the `Endpoint` takes in a period, if limited
+    pub(crate) fn rate_limit_config(&self) -> Option<&RateLimit> {
+        self.rate_limit.as_ref()
+    }
+
+    /// Render notifications to the `Endpoint` as emails with a custom HTML
+    /// template, implies `MessageFormat::Email`
+    #[cfg(feature = "email")]
+    pub fn email_template(mut self, template: EmailTemplate) -> Self {
+        self.format = MessageFormat::Email;
+        self.email_template = Some(template);
+        self
+    }
+
+    /// The custom template of emails to the `Endpoint`, if any
+    #[cfg(feature = "email")]
+    pub(crate) fn email_template_config(&self) -> Option<&EmailTemplate> {
+        self.email_template.as_ref()
+    }
+
+    /// Send notifications to the `Endpoint` as emails through an HTTP email
+    /// API, implies `MessageFormat::Email`
+    #[cfg(feature = "email")]
+    pub fn email_api(mut self, api: EmailApi) -> Self {
+        self.format = MessageFormat::Email;
+        self.email_api = Some(api);
+        self
+    }
+
+    /// The HTTP email API of the `Endpoint`, if any
+    #[cfg(feature = "email")]
+    pub(crate) fn email_api_config(&self) -> Option<&EmailApi> {
+        self.email_api.as_ref()
+    }
+
+    /// Render notifications to the `Endpoint` as SMS with given options,
+    /// implies `MessageFormat::Sms`
+    #[cfg(feature = "sms")]
+    pub fn sms(mut self, sms: Sms) -> Self {
+        self.format = MessageFormat::Sms;
+        self.sms = Some(sms);
+        self
+    }
+
+    /// The SMS options of the `Endpoint`, if any
+    #[cfg(feature = "sms")]
+    pub(crate) fn sms_config(&self) -> Option<&Sms> {
+        self.sms.as_ref()
+    }
+
+    /// Open GitLab issues for notifications to the `Endpoint` with given
+    /// options, implies `MessageFormat::Gitlab`
+    #[cfg(feature = "gitlab")]
+    pub fn gitlab(mut self, issue: GitlabIssue) -> Self {
+        self.format = MessageFormat::Gitlab;
+        self.gitlab = Some(issue);
+        self
+    }
+
+    /// The GitLab issue options of the `Endpoint`, if any
+    #[cfg(feature = "gitlab")]
+    pub(crate) fn gitlab_config(&self) -> Option<&GitlabIssue> {
+        self.gitlab.as_ref()
+    }
+
+    /// Send notifications to the `Endpoint` as Signal messages with given
+    /// options, implies `MessageFormat::Signal`
+    #[cfg(feature = "signal")]
+    pub fn signal(mut self, signal: Signal) -> Self {
+        self.format = MessageFormat::Signal;
+        self.signal = Some(signal);
+        self
+    }
+
+    /// The Signal options of the `Endpoint`, if any
+    #[cfg(feature = "signal")]
+    pub(crate) fn signal_config(&self) -> Option<&Signal> {
+        self.signal.as_ref()
+    }
+
+    /// Render notifications to the `Endpoint` with a custom `Formatter`,
+    /// in place of its format and the options rendering with it
+    pub fn formatter(mut self, formatter: impl Formatter + 'static) -> Self {
+        self.formatter = Some(Arc::new(formatter));
+        self
+    }
+
+    /// The custom `Formatter` of the `Endpoint`, if any
+    pub(crate) fn formatter_config(&self) -> Option<&dyn Formatter> {
+        self.formatter.as_deref()
+    }
+
+    /// How notifications are rendered for the `Endpoint`
+    pub(crate) fn message_format(&self) -> MessageFormat {
+        self.format
+    }
+
+    /// Whether Slack messages to the `Endpoint` end with action buttons
+    #[cfg(feature = "slack")]
+    pub(crate) fn has_action_buttons(&self) -> bool {
+        self.action_buttons
+    }
+
+    /// What to do when a payload is over a limit of the `Endpoint`
+    pub(crate) fn oversize_policy(&self) -> OversizePolicy {
+        self.oversize
+    }
+
+    /// Whether the `Endpoint` accepts notifications of a given severity
+    pub(crate) fn accepts(&self, severity: Severity) -> bool {
+        self.min_severity.is_none_or(|min| severity >= min)
+    }
+
+    /// Deliver to a `MockDestination` instead of over HTTP
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn mock(mut self, mock: MockDestination) -> Self {
+        self.mock = Some(mock);
+        self
+    }
+
+    /// The `MockDestination` delivered to instead of HTTP, if any
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn mock_destination(&self) -> Option<&MockDestination> {
+        self.mock.as_ref()
+    }
+
+    /// Start a POST request to the `Endpoint` with its headers applied
+    pub(crate) fn post(&self, http_client: &Client) -> RequestBuilder {
+        self.request(http_client, Method::POST, &self.url)
+    }
+
+    /// Start a POST request to the `Endpoint` with a given body, signed if
+    /// the `Endpoint` has a signing secret and gzipped if it's large enough
+    pub(crate) fn post_body(&self, http_client: &Client, body: String) -> RequestBuilder {
+        let request = self.post(http_client);
+
+        #[cfg(feature = "signing")]
+        let request = match &self.signing_secret {
+            Some(secret) => {
+                let timestamp = crate::time::SystemTime::now()
+                    .duration_since(crate::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                request
+                    .header(crate::signing::TIMESTAMP_HEADER, timestamp)
+                    .header(
+                        crate::signing::SIGNATURE_HEADER,
+                        crate::signing::sign(secret, timestamp, &body),
+                    )
+            }
+            None => request,
+        };
+
+        match self.gzip_min_len {
+            Some(min_len) if body.len() >= min_len => request
+                .header("Content-Encoding", "gzip")
+                .body(crate::gzip::compress(body.as_bytes())),
+            _ => request.body(body),
+        }
+    }
+
+    /// Start a GET request to another URL on the same API as the `Endpoint`,
+    /// with its headers applied
+    #[cfg(feature = "slack")]
+    pub(crate) fn get(&self, http_client: &Client, url: &str) -> RequestBuilder {
+        self.request(http_client, Method::GET, url)
+    }
+
+    /// Start a POST request to another URL on the same API as the `Endpoint`,
+    /// with its headers applied
+    #[cfg(feature = "slack")]
+    pub(crate) fn post_to(&self, http_client: &Client, url: &str) -> RequestBuilder {
+        self.request(http_client, Method::POST, url)
+    }
+
+    /// Start a request with the headers of the `Endpoint` applied
+    fn request(&self, http_client: &Client, method: Method, url: &str) -> RequestBuilder {
+        let mut request = http_client.request(method, url);
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if let Some(token) = &self.bearer_token {
+            request = request.bearer_auth(token);
+        }
+        if let Some((username, password)) = &self.basic_auth {
+            request = request.basic_auth(username, Some(password));
+        }
+
+        request
+    }
+}
+impl From<&str> for Endpoint {
+    fn from(url: &str) -> Self {
+        Self::new(url)
+    }
+}
+impl From<String> for Endpoint {
+    fn from(url: String) -> Self {
+        Self::new(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Endpoint, Severity};
+
+    /// A test to make sure custom headers and bearer auth are applied to requests
+    #[test]
+    fn applies_headers() {
+        let endpoint = Endpoint::new("https://gateway.internal/notify")
+            .header("X-Team", "payments")
+            .bearer_auth("secret-token");
+
+        let request = endpoint.post(&reqwest::Client::new()).build().unwrap();
+        assert_eq!(request.headers()["X-Team"], "payments");
+        assert_eq!(request.headers()["Authorization"], "Bearer secret-token");
+    }
+
+    /// A test to make sure basic auth is applied to requests
+    #[test]
+    fn applies_basic_auth() {
+        let endpoint = Endpoint::new("https://api.mailgun.net/v3/example.com/messages")
+            .basic_auth("api", "key");
+
+        let request = endpoint.post(&reqwest::Client::new()).build().unwrap();
+        assert_eq!(request.headers()["Authorization"], "Basic YXBpOmtleQ==");
+    }
+
+    /// A test to make sure invalid headers are reported instead of dropped
+    #[test]
+    fn rejects_invalid_header() {
+        let endpoint =
+            Endpoint::new("https://gateway.internal/notify").header("X Team", "payments");
+        assert!(endpoint.post(&reqwest::Client::new()).build().is_err())
+    }
+
+    /// A test to make sure signed requests carry a timestamp and matching signature
+    #[cfg(feature = "signing")]
+    #[test]
+    fn signs_request_body() {
+        let endpoint = Endpoint::new("https://gateway.internal/notify").sign_with("secret");
+
+        let request = endpoint
+            .post_body(&reqwest::Client::new(), String::from("{\"text\":\"hi\"}"))
+            .build()
+            .unwrap();
+        let timestamp: u64 = request.headers()["X-Dev-Notify-Timestamp"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            request.headers()["X-Dev-Notify-Signature"],
+            crate::signing::sign("secret", timestamp, "{\"text\":\"hi\"}").as_str()
+        );
+    }
+
+    /// A test to make sure only bodies of at least the minimum size are gzipped
+    #[test]
+    fn gzips_large_bodies() {
+        let endpoint = Endpoint::new("https://gateway.internal/notify").gzip(64);
+        let http_client = reqwest::Client::new();
+
+        let request = endpoint
+            .post_body(&http_client, String::from("{\"text\":\"hi\"}"))
+            .build()
+            .unwrap();
+        assert!(request.headers().get("Content-Encoding").is_none());
+
+        let body = format!("{{\"text\":\"{}\"}}", "hi ".repeat(100));
+        let request = endpoint.post_body(&http_client, body).build().unwrap();
+        assert_eq!(request.headers()["Content-Encoding"], "gzip");
+        let compressed = request.body().unwrap().as_bytes().unwrap();
+        assert_eq!(&compressed[..2], [0x1f, 0x8b]);
+        assert!(compressed.len() < 100);
+    }
+
+    /// A test to make sure a minimum severity filters lesser notifications
+    #[test]
+    fn filters_by_min_severity() {
+        let endpoint =
+            Endpoint::new("https://gateway.internal/notify").min_severity(Severity::Error);
+        assert!(!endpoint.accepts(Severity::Info));
+        assert!(endpoint.accepts(Severity::Error));
+        assert!(endpoint.accepts(Severity::Critical));
+    }
+}
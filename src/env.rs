@@ -0,0 +1,78 @@
+use crate::{Endpoint, Error, Notifier, NotifierBuilder, Severity};
+
+/// Webhook URL for a destination named `slack`
+const SLACK_WEBHOOK: &str = "DEV_NOTIFY_SLACK_WEBHOOK";
+/// Minimum severity to send, by name (e.g. `warning`)
+const MIN_SEVERITY: &str = "DEV_NOTIFY_MIN_SEVERITY";
+/// Proxy URL to route requests through
+const PROXY: &str = "DEV_NOTIFY_PROXY";
+/// Path to spool failed notifications to
+const SPOOL: &str = "DEV_NOTIFY_SPOOL";
+
+impl Notifier {
+    /// Build a `Notifier` from environment variables, so containerized
+    /// services can be configured without code changes
+    ///
+    /// Recognizes:
+    /// - `DEV_NOTIFY_SLACK_WEBHOOK`: webhook URL for a destination named `slack`
+    /// - `DEV_NOTIFY_MIN_SEVERITY`: minimum severity to send, e.g. `warning`
+    /// - `DEV_NOTIFY_PROXY`: proxy URL to route requests through
+    /// - `DEV_NOTIFY_SPOOL`: path to spool failed notifications to
+    pub fn from_env() -> Result<Notifier, Error> {
+        NotifierBuilder::default()
+            .with_vars(|name| std::env::var(name).ok())?
+            .build()
+    }
+}
+
+impl NotifierBuilder {
+    /// Apply settings from variables looked up by name
+    fn with_vars(mut self, var: impl Fn(&str) -> Option<String>) -> Result<Self, Error> {
+        if let Some(url) = var(SLACK_WEBHOOK) {
+            self = self.destination("slack", Endpoint::new(url));
+        }
+        if let Some(severity) = var(MIN_SEVERITY) {
+            let severity = severity
+                .parse::<Severity>()
+                .map_err(|e| Error::Config(format!("{MIN_SEVERITY}: {e}")))?;
+            self = self.min_severity(severity);
+        }
+        if let Some(url) = var(PROXY) {
+            self = self.proxy(url);
+        }
+        if let Some(path) = var(SPOOL) {
+            self = self.spool(path);
+        }
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::NotifierBuilder;
+    use std::collections::HashMap;
+
+    /// A test to make sure variables configure a `Notifier`
+    #[test]
+    fn can_build_from_vars() {
+        let vars = HashMap::from([
+            (
+                "DEV_NOTIFY_SLACK_WEBHOOK",
+                "https://hooks.slack.com/services/T000/B000/XXXX",
+            ),
+            ("DEV_NOTIFY_MIN_SEVERITY", "Warning"),
+        ]);
+        let builder = NotifierBuilder::default()
+            .with_vars(|name| vars.get(name).map(|value| value.to_string()));
+        assert!(builder.unwrap().build().is_ok())
+    }
+
+    /// A test to make sure an unknown severity is reported
+    #[test]
+    fn rejects_unknown_severity() {
+        let builder = NotifierBuilder::default()
+            .with_vars(|name| (name == "DEV_NOTIFY_MIN_SEVERITY").then(|| String::from("loud")));
+        assert!(builder.is_err())
+    }
+}
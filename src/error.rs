@@ -0,0 +1,157 @@
+use reqwest::StatusCode;
+use std::fmt;
+
+/// An error sending a `Notification`
+#[derive(Debug)]
+pub enum Error {
+    /// The HTTP request to a destination failed
+    Http(reqwest::Error),
+    /// The destination responded with an error status
+    Status { status: StatusCode, body: String },
+    /// The destination failed repeatedly, so requests to it are
+    /// skipped until its cooldown period has passed
+    CircuitOpen { url: String },
+    /// Reading or writing the spool of failed notifications failed
+    Spool(std::io::Error),
+    /// The configuration is invalid
+    Config(String),
+    /// The rendered payload is over a limit of the destination, and its
+    /// `OversizePolicy` rejects oversized payloads
+    PayloadTooLarge { limit: usize, size: usize },
+    /// Appending to the GitHub Actions job summary failed
+    JobSummary(std::io::Error),
+    /// Reading the audit log failed
+    AuditLog(std::io::Error),
+    /// The background task of a `Worker` stopped, so nothing more can be queued
+    WorkerStopped,
+    /// An incoming request (e.g. a Slack interaction) isn't signed with the
+    /// expected secret, or was signed too long ago
+    InvalidSignature,
+}
+impl Error {
+    /// Whether the failure is likely transient, so sending again later may
+    /// succeed: timeouts, connection failures, 408, 429 and 5xx responses,
+    /// and open circuits
+    ///
+    /// Permanent failures, like an invalid webhook (other 4xx responses),
+    /// an oversized payload or invalid configuration, would only fail again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Http(e) => match e.status() {
+                Some(status) => is_transient(status),
+                None => {
+                    // Connection failures aren't told apart from other request errors on wasm32
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if e.is_connect() {
+                        return true;
+                    }
+                    !e.is_builder() && (e.is_timeout() || e.is_request())
+                }
+            },
+            Self::Status { status, .. } => is_transient(*status),
+            Self::CircuitOpen { .. } => true,
+            Self::Spool(_)
+            | Self::Config(_)
+            | Self::PayloadTooLarge { .. }
+            | Self::JobSummary(_)
+            | Self::AuditLog(_)
+            | Self::WorkerStopped
+            | Self::InvalidSignature => false,
+        }
+    }
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(e) => write!(f, "HTTP request failed: {e}"),
+            Self::Status { status, body } => {
+                write!(f, "destination responded with {status}: {body}")
+            }
+            Self::CircuitOpen { url } => {
+                write!(f, "circuit open for {url} after repeated failures")
+            }
+            Self::Spool(e) => write!(f, "spool of failed notifications unavailable: {e}"),
+            Self::Config(reason) => write!(f, "invalid configuration: {reason}"),
+            Self::PayloadTooLarge { limit, size } => {
+                write!(
+                    f,
+                    "payload of {size} exceeds the destination limit of {limit}"
+                )
+            }
+            Self::JobSummary(e) => write!(f, "writing the job summary failed: {e}"),
+            Self::AuditLog(e) => write!(f, "audit log unavailable: {e}"),
+            Self::WorkerStopped => write!(f, "the background worker stopped"),
+            Self::InvalidSignature => write!(f, "request signature is invalid or expired"),
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(e) => Some(e),
+            Self::Status { .. }
+            | Self::CircuitOpen { .. }
+            | Self::Config(_)
+            | Self::PayloadTooLarge { .. }
+            | Self::WorkerStopped
+            | Self::InvalidSignature => None,
+            Self::Spool(e) | Self::JobSummary(e) | Self::AuditLog(e) => Some(e),
+        }
+    }
+}
+
+/// Whether a response status is likely transient: 408, 429 or 5xx
+fn is_transient(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Http(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockServer;
+    use crate::{Endpoint, Error, Notification, Notifier};
+
+    /// A test to make sure transient failures are retryable and permanent ones aren't
+    #[tokio::test]
+    async fn classifies_retryable() {
+        let client = reqwest::Client::new();
+        let refused = client.post("http://127.0.0.1:1").send().await.unwrap_err();
+        assert!(Error::Http(refused).is_retryable());
+        let invalid = client.post("not a url").send().await.unwrap_err();
+        assert!(!Error::Http(invalid).is_retryable());
+
+        assert!(Error::CircuitOpen {
+            url: String::from("https://hooks.slack.com")
+        }
+        .is_retryable());
+        assert!(!Error::PayloadTooLarge {
+            limit: 3000,
+            size: 5000
+        }
+        .is_retryable());
+        assert!(!Error::Config(String::from("unknown destination")).is_retryable());
+    }
+
+    /// A test to make sure error responses of a destination are classified by status
+    #[tokio::test]
+    async fn classifies_error_statuses() {
+        let notifier = Notifier::new();
+        for (status, retryable) in [(404, false), (429, true), (503, true)] {
+            let server = MockServer::new(vec![(status, String::from("no"))]);
+            let result = notifier
+                .send(Notification::new("Disk full"), &Endpoint::new(server.url()))
+                .await;
+            let Err(e @ Error::Status { .. }) = result else {
+                panic!("expected an error status, got {result:?}");
+            };
+            assert_eq!(e.is_retryable(), retryable, "{status}");
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::time::Instant;
+use crate::{AlertState, Context, ContextKind, Notification, Notifier};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// A tracked notification waiting to be acknowledged
+struct Pending {
+    notification: Notification,
+    opened: Instant,
+    next_step: usize,
+}
+
+/// An escalation chain: if a notification isn't acknowledged within a while
+/// of being delivered, it's resent to a secondary named destination (e.g. SMS
+/// or PagerDuty), then the next, and so on
+///
+/// Needs `NotifierBuilder::alert_store` to know what was acknowledged.
+#[derive(Clone, Debug, Default)]
+pub struct Escalation {
+    steps: Vec<(Duration, String)>,
+}
+impl Escalation {
+    /// Create an `Escalation` without any steps
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resend to a named destination if still unacknowledged a given time
+    /// after the notification was first delivered
+    pub fn after(mut self, wait: Duration, destination: impl Into<String>) -> Self {
+        self.steps.push((wait, destination.into()));
+        self.steps.sort_by_key(|(wait, _)| *wait);
+        self
+    }
+}
+
+/// Tracks notifications pending acknowledgement against an `Escalation`
+pub(crate) struct Escalator {
+    policy: Escalation,
+    pending: Mutex<Vec<Pending>>,
+}
+impl Escalator {
+    /// Create an `Escalator` following a given `Escalation`
+    pub(crate) fn new(policy: Escalation) -> Self {
+        Self {
+            policy,
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Start the escalation chain of a newly opened notification
+    pub(crate) fn watch(&self, notification: Notification) {
+        if self.policy.steps.is_empty() {
+            return;
+        }
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.push(Pending {
+            notification,
+            opened: Instant::now(),
+            next_step: 0,
+        });
+    }
+
+    /// Take the notifications due for their next step, with the name of the
+    /// destination to escalate to, dropping those no longer open
+    fn due(&self, is_open: impl Fn(&str) -> bool) -> Vec<(Notification, String)> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|p| is_open(p.notification.alert_id()));
+
+        let mut due = Vec::new();
+        for p in pending.iter_mut() {
+            let Some((wait, destination)) = self.policy.steps.get(p.next_step) else {
+                continue;
+            };
+            if p.opened.elapsed() < *wait {
+                continue;
+            }
+            p.next_step += 1;
+
+            let mut notification = p.notification.clone();
+            notification.context.push(Context {
+                label: String::from("Escalated"),
+                value: format!("Not acknowledged within {}m", wait.as_secs() / 60),
+                kind: ContextKind::Text,
+            });
+            due.push((notification, destination.clone()));
+        }
+        pending.retain(|p| p.next_step < self.policy.steps.len());
+
+        due
+    }
+}
+
+impl Notifier {
+    /// Resend notifications that weren't acknowledged in time to the next
+    /// destination of their `Escalation`
+    ///
+    /// Returns how many were escalated.
+    pub async fn escalate(&self) -> usize {
+        let (Some(escalator), Some(store)) = (self.escalator(), self.alert_store()) else {
+            return 0;
+        };
+        let due = escalator.due(|id| {
+            store
+                .load(id)
+                .is_some_and(|record| record.state == AlertState::Open)
+        });
+
+        let mut escalated = 0;
+        for (notification, name) in due {
+            let Some(destination) = self.destination(&name) else {
+                continue;
+            };
+            if self.dispatch(notification, destination).await.is_ok() {
+                escalated += 1;
+            }
+        }
+
+        escalated
+    }
+
+    /// Spawn a task on the current tokio runtime calling `Notifier::escalate`
+    /// every `check_interval`
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn_escalations(self: &Arc<Self>, check_interval: Duration) -> JoinHandle<()> {
+        let notifier = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut checks = tokio::time::interval(check_interval);
+            loop {
+                checks.tick().await;
+                notifier.escalate().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Escalation;
+    use crate::testing::MockDestination;
+    use crate::{MemoryAlertStore, Notification, Notifier, Route};
+    use std::time::Duration;
+
+    /// A test to make sure unacknowledged notifications are resent to the next destination
+    #[tokio::test]
+    async fn escalates_unacknowledged() {
+        let (slack, pagerduty) = (MockDestination::new(), MockDestination::new());
+        let notifier = Notifier::builder()
+            .alert_store(MemoryAlertStore::new())
+            .escalation(Escalation::new().after(Duration::ZERO, "pagerduty"))
+            .destination("slack", slack.endpoint())
+            .destination("pagerduty", pagerduty.endpoint())
+            .route(Route::new(["slack"]))
+            .build()
+            .unwrap();
+
+        notifier.notify(Notification::new("Disk full")).await;
+        notifier.notify(Notification::new("CPU high")).await;
+        notifier.acknowledge("CPU high", None).await.unwrap();
+
+        assert_eq!(notifier.escalate().await, 1);
+        pagerduty.assert_delivered("Disk full");
+        let escalated = &pagerduty.delivered()[0];
+        assert_eq!(escalated.context[0].label, "Escalated");
+
+        // Each step is only taken once
+        assert_eq!(notifier.escalate().await, 0);
+        assert_eq!((slack.len(), pagerduty.len()), (2, 1));
+    }
+
+    /// A test to make sure escalation requires tracking acknowledgements
+    #[test]
+    fn requires_alert_store() {
+        let notifier = Notifier::builder()
+            .escalation(Escalation::new().after(Duration::from_secs(900), "pagerduty"))
+            .build();
+        assert!(notifier.is_err());
+    }
+}
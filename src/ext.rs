@@ -0,0 +1,152 @@
+use crate::{Notification, Severity, Worker};
+use std::fmt::{Debug, Display};
+
+/// Notify about errors inline, e.g.
+/// `capture(payment).await.notify_on_err(&worker, "payment capture")?`
+pub trait ResultExt {
+    /// Queue a `Notification` on a `Worker` if this is an `Err`, then
+    /// return the result unchanged
+    ///
+    /// The message is `"{action} failed: {error}"` (using `Display`), and
+    /// the `Debug` representation of the error is added as context.
+    fn notify_on_err(self, worker: &Worker, action: &str) -> Self;
+}
+impl<T, E: Display + Debug> ResultExt for Result<T, E> {
+    fn notify_on_err(self, worker: &Worker, action: &str) -> Self {
+        if let Err(e) = &self {
+            worker.notify(error_notification(action, e));
+        }
+        self
+    }
+}
+
+/// Describe a failed action as a `Notification`
+fn error_notification<E: Display + Debug>(action: &str, error: &E) -> Notification {
+    let mut notification = Notification::new(format!("{action} failed: {error}"))
+        .with_context("Error", format!("{error:?}"));
+    notification.severity = Severity::Error;
+
+    notification
+}
+
+/// Support for the `notify_on_error` attribute macro, not public API
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub mod __private {
+    use crate::{Notification, Severity, Worker};
+    use std::any::Any;
+    use std::fmt::Display;
+
+    /// Queue a `Notification` about a function returning an error
+    pub fn notify_error(
+        worker: &Worker,
+        function: &str,
+        args: Vec<(&'static str, String)>,
+        error: &dyn Display,
+    ) {
+        worker.notify(function_notification(
+            format!("{function} failed: {error}"),
+            args,
+        ));
+    }
+
+    /// Queue a `Notification` about a function panicking
+    pub fn notify_panic(
+        worker: &Worker,
+        function: &str,
+        args: Vec<(&'static str, String)>,
+        payload: &(dyn Any + Send),
+    ) {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("Box<dyn Any>"));
+        worker.notify(function_notification(
+            format!("{function} panicked: {message}"),
+            args,
+        ));
+    }
+
+    /// Describe a failed function call, with its arguments as context
+    fn function_notification(message: String, args: Vec<(&'static str, String)>) -> Notification {
+        let mut notification = Notification::new(message);
+        for (label, value) in args {
+            notification = notification.with_context(label, value);
+        }
+        notification.severity = Severity::Error;
+
+        notification
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockDestination;
+    use crate::{Notifier, ResultExt, Worker};
+    use std::sync::Arc;
+
+    /// A test to make sure only errors are notified and the result is unchanged
+    #[tokio::test]
+    async fn notifies_on_err() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+
+        let ok: Result<u32, std::fmt::Error> = Ok(1);
+        assert_eq!(ok.notify_on_err(&worker, "payment capture"), Ok(1));
+        let err: Result<u32, std::fmt::Error> = Err(std::fmt::Error);
+        assert!(err.notify_on_err(&worker, "payment capture").is_err());
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        let delivered = mock.delivered();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(
+            delivered[0].message,
+            "payment capture failed: an error occurred when formatting an argument"
+        );
+        assert_eq!(delivered[0].context[0].value, "Error");
+    }
+}
+
+#[cfg(all(test, feature = "macros"))]
+mod macro_tests {
+    use crate::testing::MockDestination;
+    use crate::{notify_on_error, Notifier, Worker};
+    use std::sync::Arc;
+
+    #[notify_on_error(worker = worker, args(customer_id))]
+    async fn capture_payment(
+        worker: &Worker,
+        customer_id: u64,
+        amount: u64,
+    ) -> Result<u64, String> {
+        if amount == 0 {
+            return Err(String::from("amount must be positive"));
+        }
+        Ok(amount)
+    }
+
+    /// A test to make sure the attribute notifies with the function name and arguments
+    #[tokio::test]
+    async fn notifies_on_error_return() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+
+        assert_eq!(capture_payment(&worker, 7, 100).await, Ok(100));
+        assert!(capture_payment(&worker, 7, 0).await.is_err());
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        let delivered = mock.delivered();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(
+            delivered[0].message,
+            "capture_payment failed: amount must be positive"
+        );
+        assert_eq!(delivered[0].context[0].label, "customer_id");
+        assert_eq!(delivered[0].context[0].value, "7");
+    }
+}
@@ -0,0 +1,121 @@
+use crate::Severity;
+use serde::Deserialize;
+
+/// How a `Notification` is rendered for a destination, so the same one can
+/// be sent tersely to some destinations and in full detail to others
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageFormat {
+    /// Slack Block Kit blocks, with sections as fields (the default with the
+    /// `slack` feature, or else `Text`)
+    #[cfg(feature = "slack")]
+    #[default]
+    Blocks,
+    /// Plain text with the full detail, for destinations without block support
+    #[cfg_attr(not(feature = "slack"), default)]
+    Text,
+    /// Only the message, e.g. for SMS gateways
+    Terse,
+    /// The message and top context without markdown, in a limited number of
+    /// SMS segments, to a phone number set as the channel
+    #[cfg(feature = "sms")]
+    Sms,
+    /// A Discord embed, with context as fields and the severity as its color
+    #[cfg(feature = "discord")]
+    Discord,
+    /// A Telegram message marked up with MarkdownV2, to a chat set as the channel
+    #[cfg(feature = "telegram")]
+    #[serde(rename = "telegram_markdown")]
+    TelegramMarkdown,
+    /// A Telegram message marked up with HTML, to a chat set as the channel
+    #[cfg(feature = "telegram")]
+    #[serde(rename = "telegram_html")]
+    TelegramHtml,
+    /// An email for HTTP email APIs, with an HTML body and a plain text
+    /// alternative, to a recipient set as the channel
+    #[cfg(feature = "email")]
+    Email,
+    /// A flat object of variables for a Slack Workflow Builder webhook: the
+    /// message, timestamp, severity and context by label
+    #[cfg(feature = "slack")]
+    Workflow,
+    /// A GitLab issue titled with the message and described in Markdown
+    #[cfg(feature = "gitlab")]
+    Gitlab,
+    /// A signal-cli-rest-api message with styled text, to the group or
+    /// numbers set as the channel
+    #[cfg(feature = "signal")]
+    Signal,
+    /// The service data of a Home Assistant `notify` service, to the target
+    /// set as the channel
+    #[cfg(feature = "home-assistant")]
+    #[serde(rename = "home_assistant")]
+    HomeAssistant,
+    /// A Bark push to the iOS device whose key is set as the channel
+    #[cfg(feature = "bark")]
+    Bark,
+    /// A Splunk On-Call (VictorOps) alert, typed by severity
+    #[cfg(feature = "victorops")]
+    #[serde(rename = "victorops")]
+    VictorOps,
+    /// A Honeycomb marker, typed by severity
+    #[cfg(feature = "markers")]
+    Honeycomb,
+    /// A New Relic custom event, with context as attributes
+    #[cfg(feature = "markers")]
+    #[serde(rename = "new_relic")]
+    NewRelic,
+    /// A Google Cloud Pub/Sub publish request of the serialized notification
+    #[cfg(feature = "pubsub")]
+    PubSub,
+    /// An Azure Service Bus message of the serialized notification
+    #[cfg(feature = "service-bus")]
+    #[serde(rename = "service_bus")]
+    ServiceBus,
+}
+
+/// What to do when a rendered payload is over a limit of its destination
+/// (e.g. Slack's 3000 characters per block, or 1600 for a terse SMS)
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OversizePolicy {
+    /// Truncate the offending text, marking it with an ellipsis
+    #[default]
+    Truncate,
+    /// Fail with `Error::PayloadTooLarge` before sending
+    Reject,
+}
+
+/// Emoji prefixed to the message by severity, set with
+/// `Endpoint::severity_emoji`, so channels can be scanned at a glance
+/// without Block Kit
+///
+/// Defaults to 🔴 for errors and critical, 🟠 for warnings and 🟢 below.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeverityEmoji {
+    emoji: [String; 5],
+}
+impl SeverityEmoji {
+    /// Create the default `SeverityEmoji` mapping
+    pub fn new() -> Self {
+        Self {
+            emoji: ["🟢", "🟢", "🟠", "🔴", "🔴"].map(String::from),
+        }
+    }
+
+    /// Use a given emoji (or Slack shortcode, like `:fire:`) for a severity
+    pub fn set(mut self, severity: Severity, emoji: impl Into<String>) -> Self {
+        self.emoji[severity as usize] = emoji.into();
+        self
+    }
+
+    /// The emoji for a given severity
+    pub(crate) fn get(&self, severity: Severity) -> &str {
+        &self.emoji[severity as usize]
+    }
+}
+impl Default for SeverityEmoji {
+    fn default() -> Self {
+        Self::new()
+    }
+}
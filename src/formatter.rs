@@ -0,0 +1,316 @@
+#[cfg(feature = "bark")]
+use crate::bark::BarkFormatter;
+#[cfg(feature = "discord")]
+use crate::discord::DiscordFormatter;
+#[cfg(feature = "email")]
+use crate::email::EmailFormatter;
+#[cfg(feature = "gitlab")]
+use crate::gitlab::GitlabFormatter;
+#[cfg(feature = "home-assistant")]
+use crate::home_assistant::HomeAssistantFormatter;
+#[cfg(feature = "markers")]
+use crate::markers::{HoneycombFormatter, NewRelicFormatter};
+#[cfg(feature = "pubsub")]
+use crate::pubsub::PubSubFormatter;
+use crate::render::fit_text;
+#[cfg(feature = "service-bus")]
+use crate::service_bus::ServiceBusFormatter;
+#[cfg(feature = "signal")]
+use crate::signal::SignalFormatter;
+#[cfg(feature = "sms")]
+use crate::sms::SmsFormatter;
+#[cfg(feature = "telegram")]
+use crate::telegram::TelegramFormatter;
+#[cfg(feature = "victorops")]
+use crate::victorops::VictorOpsFormatter;
+#[cfg(feature = "slack")]
+use crate::workflow::WorkflowFormatter;
+#[cfg(feature = "slack")]
+use crate::SlackFormatter;
+use crate::{Attachment, Endpoint, Error, MessageFormat, Notification, OversizePolicy};
+use serde_json::{json, Value};
+use std::fmt;
+
+/// The most characters Slack renders in the text of a message
+const MAX_MESSAGE_TEXT: usize = 40_000;
+
+/// The most characters of a terse message, the length SMS gateways
+/// (e.g. Twilio) split into segments up to
+const MAX_TERSE_TEXT: usize = 1600;
+
+/// The most characters of an attachment included inline, keeping its
+/// block under Slack's limit of 3000 characters
+const MAX_INLINE: usize = 2800;
+
+/// Renders notifications into the body of requests to a destination, for
+/// full control over the payload (e.g. for a bespoke internal tool)
+///
+/// Set on a destination with `Endpoint::formatter`, in place of its
+/// `MessageFormat` and the options rendering with it.
+pub trait Formatter: fmt::Debug + Send + Sync {
+    /// Render a `Notification` into a payload, failing if it can't be (e.g.
+    /// it's over a limit of the destination, which rejects oversized payloads)
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error>;
+}
+
+/// The body of a request to a destination, along with its content type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderedPayload {
+    pub body: String,
+    pub content_type: String,
+}
+impl RenderedPayload {
+    /// Create a `RenderedPayload` with a given body and content type
+    pub fn new(body: impl Into<String>, content_type: impl Into<String>) -> Self {
+        Self {
+            body: body.into(),
+            content_type: content_type.into(),
+        }
+    }
+
+    /// Create a `RenderedPayload` of JSON
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::new(body, "application/json")
+    }
+}
+
+/// Renders notifications as the text of a message, for Slack and compatible
+/// webhooks: in full as `MessageFormat::Text`, or only the message as
+/// `MessageFormat::Terse`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TextFormatter {
+    terse: bool,
+    policy: OversizePolicy,
+    unfurl: (Option<bool>, Option<bool>),
+}
+impl TextFormatter {
+    /// Create a `TextFormatter` with the format and options of a destination
+    pub(crate) fn for_destination(destination: &Endpoint) -> Self {
+        Self {
+            terse: destination.message_format() == MessageFormat::Terse,
+            policy: destination.oversize_policy(),
+            unfurl: destination.unfurl_config(),
+        }
+    }
+}
+impl Formatter for TextFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let mut notification = notification.clone();
+        let channel = notification.channel.take();
+        let id = notification.id.take();
+        let (mut payload, id) = if self.terse {
+            let mut text = notification.message;
+            fit_text(&mut text, MAX_TERSE_TEXT, self.policy)?;
+            (json!({ "text": text }), None)
+        } else {
+            let attachments = std::mem::take(&mut notification.attachments);
+            let mut text = notification.into_message();
+            for attachment in attachments {
+                text.push('\n');
+                text.push_str(&inline_attachment(attachment));
+            }
+            fit_text(&mut text, MAX_MESSAGE_TEXT, self.policy)?;
+            (json!({ "text": text }), id)
+        };
+        address_message(&mut payload, channel, id, self.unfurl);
+
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and render it for a given destination, with
+    /// its `Formatter` if it has one, or else the one of its format
+    pub(crate) fn render_for(mut self, destination: &Endpoint) -> Result<RenderedPayload, Error> {
+        if let Some(formatter) = destination.formatter_config() {
+            return formatter.render(&self);
+        }
+        if let Some(emoji) = destination.severity_emoji_config() {
+            self.message = format!("{} {}", emoji.get(self.severity), self.message);
+        }
+
+        let formatter: Box<dyn Formatter + '_> = match destination.message_format() {
+            #[cfg(feature = "slack")]
+            MessageFormat::Blocks => Box::new(SlackFormatter::for_destination(destination)),
+            MessageFormat::Text | MessageFormat::Terse => {
+                Box::new(TextFormatter::for_destination(destination))
+            }
+            #[cfg(feature = "sms")]
+            MessageFormat::Sms => Box::new(SmsFormatter::for_destination(destination)),
+            #[cfg(feature = "discord")]
+            MessageFormat::Discord => Box::new(DiscordFormatter::for_destination(destination)),
+            #[cfg(feature = "telegram")]
+            MessageFormat::TelegramMarkdown | MessageFormat::TelegramHtml => {
+                Box::new(TelegramFormatter::for_destination(destination))
+            }
+            #[cfg(feature = "email")]
+            MessageFormat::Email => Box::new(EmailFormatter::for_destination(destination)),
+            #[cfg(feature = "slack")]
+            MessageFormat::Workflow => Box::new(WorkflowFormatter),
+            #[cfg(feature = "gitlab")]
+            MessageFormat::Gitlab => Box::new(GitlabFormatter::for_destination(destination)),
+            #[cfg(feature = "signal")]
+            MessageFormat::Signal => Box::new(SignalFormatter::for_destination(destination)),
+            #[cfg(feature = "home-assistant")]
+            MessageFormat::HomeAssistant => {
+                Box::new(HomeAssistantFormatter::for_destination(destination))
+            }
+            #[cfg(feature = "bark")]
+            MessageFormat::Bark => Box::new(BarkFormatter::for_destination(destination)),
+            #[cfg(feature = "victorops")]
+            MessageFormat::VictorOps => Box::new(VictorOpsFormatter::for_destination(destination)),
+            #[cfg(feature = "markers")]
+            MessageFormat::Honeycomb => Box::new(HoneycombFormatter),
+            #[cfg(feature = "markers")]
+            MessageFormat::NewRelic => Box::new(NewRelicFormatter),
+            #[cfg(feature = "pubsub")]
+            MessageFormat::PubSub => Box::new(PubSubFormatter),
+            #[cfg(feature = "service-bus")]
+            MessageFormat::ServiceBus => Box::new(ServiceBusFormatter),
+        };
+
+        formatter.render(&self)
+    }
+
+    /// Consume the `Notification` and render the body of a request in a given
+    /// format, as for a destination with given options
+    #[cfg(test)]
+    pub(crate) fn into_payload(
+        self,
+        format: MessageFormat,
+        policy: OversizePolicy,
+    ) -> Result<String, Error> {
+        let destination = Endpoint::new("https://example.com")
+            .format(format)
+            .oversize(policy);
+
+        Ok(self.render_for(&destination)?.body)
+    }
+}
+
+/// Post a message to a given channel, previewing links and media as set,
+/// and keep a given notification ID with it as metadata (the Slack Web API
+/// keeps it, so messages can be correlated)
+pub(crate) fn address_message(
+    payload: &mut Value,
+    channel: Option<String>,
+    id: Option<String>,
+    (links, media): (Option<bool>, Option<bool>),
+) {
+    if let Some(channel) = channel {
+        payload["channel"] = Value::String(channel);
+    }
+    if let Some(id) = id {
+        payload["metadata"] = json!({
+            "event_type": "dev_notify_notification",
+            "event_payload": { "id": id },
+        });
+    }
+    if let Some(links) = links {
+        payload["unfurl_links"] = Value::Bool(links);
+    }
+    if let Some(media) = media {
+        payload["unfurl_media"] = Value::Bool(media);
+    }
+}
+
+/// Render an `Attachment` inline as a code block, truncated to fit in a block
+pub(crate) fn inline_attachment(attachment: Attachment) -> String {
+    let mut content = attachment.content;
+    if let Some((idx, _)) = content.char_indices().nth(MAX_INLINE) {
+        content.truncate(idx);
+        content.push('…');
+    }
+
+    format!("*{}*\n```{content}```", attachment.filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Formatter, RenderedPayload};
+    use crate::{Endpoint, Error, MessageFormat, Notification, OversizePolicy};
+    use serde_json::Value;
+
+    /// A formatter for an internal tool taking plain text
+    #[derive(Debug)]
+    struct PlainText;
+    impl Formatter for PlainText {
+        fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+            Ok(RenderedPayload::new(
+                format!("{:?}: {}", notification.severity, notification.message),
+                "text/plain",
+            ))
+        }
+    }
+
+    /// A test to make sure a destination's formatter replaces its format
+    #[test]
+    fn renders_with_formatter() {
+        let notification = Notification::new("Disk full");
+
+        let rendered = notification
+            .clone()
+            .render_for(&Endpoint::new("https://tools.internal/alerts").formatter(PlainText))
+            .unwrap();
+        assert_eq!(
+            rendered,
+            RenderedPayload::new("Error: Disk full", "text/plain")
+        );
+    }
+
+    /// A test to make sure text messages carry the channel, full ones the ID too,
+    /// and terse ones only the message fit within the limit
+    #[test]
+    fn renders_text_formats() {
+        let notification = Notification::new("x".repeat(2000))
+            .with_context("Service", "api")
+            .with_channel("#alerts");
+        let id = notification.id.clone().unwrap();
+
+        let text: Value = serde_json::from_str(
+            &notification
+                .clone()
+                .into_payload(MessageFormat::Text, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(text["channel"], "#alerts");
+        assert_eq!(text["metadata"]["event_payload"]["id"], id.as_str());
+        assert!(text["text"].as_str().unwrap().contains("Service"));
+
+        let terse: Value = serde_json::from_str(
+            &notification
+                .clone()
+                .into_payload(MessageFormat::Terse, OversizePolicy::Truncate)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(terse["channel"], "#alerts");
+        assert!(terse.get("metadata").is_none());
+        assert_eq!(terse["text"].as_str().unwrap().chars().count(), 1600);
+
+        assert!(matches!(
+            notification.into_payload(MessageFormat::Terse, OversizePolicy::Reject),
+            Err(Error::PayloadTooLarge { limit: 1600, .. })
+        ));
+    }
+
+    /// A test to make sure destinations render as Slack blocks by default
+    #[cfg(feature = "slack")]
+    #[test]
+    fn renders_blocks_by_default() {
+        let notification = Notification::new("Disk full");
+
+        let rendered = notification
+            .clone()
+            .render_for(&Endpoint::new("https://hooks.slack.com"))
+            .unwrap();
+        assert_eq!(
+            rendered,
+            crate::SlackFormatter::new().render(&notification).unwrap()
+        );
+        let payload: Value = serde_json::from_str(&rendered.body).unwrap();
+        assert!(payload["blocks"].is_array());
+        assert_eq!(rendered.content_type, "application/json");
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{Context, Error, Notification, Severity};
+use std::fs::OpenOptions;
+use std::io::Write;
+
+/// Set to `true` by GitHub Actions for every step
+const ACTIONS_VAR: &str = "GITHUB_ACTIONS";
+/// Path of the Markdown file rendered as the job summary
+const STEP_SUMMARY_VAR: &str = "GITHUB_STEP_SUMMARY";
+
+/// How notifications to an `Endpoint` are reported when running inside
+/// GitHub Actions, so CI failures show up in the workflow run too
+///
+/// Notifications are emitted as `::error::`, `::warning::` or `::notice::`
+/// workflow commands. Outside of GitHub Actions this has no effect.
+#[derive(Clone, Debug, Default)]
+pub struct GithubActions {
+    job_summary: bool,
+    instead_of_webhook: bool,
+}
+impl GithubActions {
+    /// Annotate the workflow run in addition to sending to the webhook
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also append every notification to the job summary
+    pub fn job_summary(mut self) -> Self {
+        self.job_summary = true;
+        self
+    }
+
+    /// Only annotate the workflow run when running inside GitHub Actions,
+    /// skipping the webhook (which is still sent to everywhere else)
+    pub fn instead_of_webhook(mut self) -> Self {
+        self.instead_of_webhook = true;
+        self
+    }
+
+    /// Whether the webhook is skipped when running inside GitHub Actions
+    pub(crate) fn replaces_webhook(&self) -> bool {
+        self.instead_of_webhook
+    }
+
+    /// Report a notification to the workflow run, if running inside GitHub Actions
+    ///
+    /// Returns whether it was reported.
+    pub(crate) fn report(&self, notification: &Notification) -> Result<bool, Error> {
+        if std::env::var(ACTIONS_VAR).as_deref() != Ok("true") {
+            return Ok(false);
+        }
+
+        println!("{}", workflow_command(notification));
+        if self.job_summary {
+            if let Ok(path) = std::env::var(STEP_SUMMARY_VAR) {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| file.write_all(job_summary(notification).as_bytes()))
+                    .map_err(Error::JobSummary)?;
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// The workflow command annotating the run with a notification
+fn workflow_command(notification: &Notification) -> String {
+    let command = match notification.severity {
+        Severity::Critical | Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Debug => "notice",
+    };
+    let mut lines = vec![notification.message.clone()];
+    let contexts = notification
+        .context
+        .iter()
+        .chain(notification.sections.iter().flat_map(|s| &s.context));
+    for ctx in contexts {
+        lines.push(format!("{}: {}", ctx.label, ctx.value));
+    }
+
+    format!("::{command}::{}", escape_data(&lines.join("\n")))
+}
+
+/// Escape the data of a workflow command, so it's a single line
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// A notification rendered as Markdown for the job summary
+fn job_summary(notification: &Notification) -> String {
+    let mut summary = format!(
+        "### {:?}: {}\n\n_{}_\n\n",
+        notification.severity, notification.message, notification.timestamp
+    );
+    summary.push_str(&table(&notification.context));
+    for section in &notification.sections {
+        summary.push_str(&format!("**{}**\n\n", section.title));
+        summary.push_str(&table(&section.context));
+    }
+
+    summary
+}
+
+/// Context rendered as a Markdown table, if there is any
+fn table(context: &[Context]) -> String {
+    if context.is_empty() {
+        return String::new();
+    }
+
+    let mut table = String::from("| | |\n| --- | --- |\n");
+    for ctx in context {
+        table.push_str(&format!(
+            "| **{}** | {} |\n",
+            escape_cell(&ctx.label),
+            escape_cell(&ctx.value)
+        ));
+    }
+    table.push('\n');
+
+    table
+}
+
+/// Escape text for a Markdown table cell
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{job_summary, workflow_command};
+    use crate::{Notification, Section, Severity};
+
+    /// A test to make sure notifications become single line workflow commands
+    #[test]
+    fn renders_workflow_command() {
+        let mut notification = Notification::new("Coverage dropped to 79%")
+            .with_context("Job", "test")
+            .with_section(Section::new("Runner").with_context("OS", "ubuntu-latest"));
+        notification.severity = Severity::Warning;
+        assert_eq!(
+            workflow_command(&notification),
+            "::warning::Coverage dropped to 79%25%0AJob: test%0AOS: ubuntu-latest"
+        );
+
+        notification.severity = Severity::Critical;
+        assert!(workflow_command(&notification).starts_with("::error::"));
+    }
+
+    /// A test to make sure the job summary renders context as a table
+    #[test]
+    fn renders_job_summary() {
+        let mut notification = Notification::new("Deploy failed")
+            .with_context("Step", "migrate | seed")
+            .with_section(Section::new("Runner").with_context("OS", "ubuntu-latest"));
+        notification.timestamp = String::from("2024-01-19 19:26:20.022233");
+        assert_eq!(
+            job_summary(&notification),
+            "### Error: Deploy failed\n\n_2024-01-19 19:26:20.022233_\n\n\
+             | | |\n| --- | --- |\n| **Step** | migrate \\| seed |\n\n\
+             **Runner**\n\n| | |\n| --- | --- |\n| **OS** | ubuntu-latest |\n\n"
+        );
+    }
+}
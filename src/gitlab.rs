@@ -0,0 +1,213 @@
+use crate::render::fit;
+use crate::{
+    Context, ContextKind, DeliveryReceipt, Endpoint, Error, Formatter, Notification,
+    OversizePolicy, RenderedPayload,
+};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// The most characters GitLab takes in the title of an issue
+const MAX_TITLE: usize = 255;
+
+/// How GitLab issues are opened for notifications to a destination: as
+/// issues or incidents, with given labels
+///
+/// The destination is the issues endpoint of a project, like
+/// `https://gitlab.example.com/api/v4/projects/42/issues`, authenticated
+/// with an access token through `Endpoint::bearer_auth`.
+#[derive(Clone, Debug, Default)]
+pub struct GitlabIssue {
+    incident: bool,
+    labels: Vec<String>,
+}
+impl GitlabIssue {
+    /// Create `GitlabIssue` options opening plain issues without labels
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open incidents instead of plain issues
+    pub fn incident(mut self) -> Self {
+        self.incident = true;
+        self
+    }
+
+    /// Add a label to every issue, e.g. `ops` or a scoped `severity::high`
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.labels.push(label.into());
+        self
+    }
+}
+
+/// The parts of a GitLab issue response we read
+#[derive(Deserialize)]
+struct IssueResponse {
+    web_url: String,
+}
+
+/// Renders notifications as GitLab issues, as `MessageFormat::Gitlab`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct GitlabFormatter<'a> {
+    issue: Option<&'a GitlabIssue>,
+    policy: OversizePolicy,
+}
+impl<'a> GitlabFormatter<'a> {
+    /// Create a `GitlabFormatter` with the options of a destination
+    pub(crate) fn for_destination(destination: &'a Endpoint) -> Self {
+        Self {
+            issue: destination.gitlab_config(),
+            policy: destination.oversize_policy(),
+        }
+    }
+}
+impl Formatter for GitlabFormatter<'_> {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let default = GitlabIssue::default();
+        let issue = self.issue.unwrap_or(&default);
+        let payload = notification
+            .clone()
+            .into_gitlab_payload(issue, self.policy)?;
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into a GitLab issue payload,
+    /// titled with the message and described in Markdown
+    pub(crate) fn into_gitlab_payload(
+        self,
+        issue: &GitlabIssue,
+        policy: OversizePolicy,
+    ) -> Result<Value, Error> {
+        let mut title = Value::String(format!("[{:?}] {}", self.severity, self.message));
+        fit(&mut title, MAX_TITLE, policy)?;
+
+        let labels = self.labels.unwrap_or_default();
+        let mut description = format!(
+            "**{}:** {}\n\n**{}:** {}\n",
+            labels.issue, self.message, labels.timestamp, self.timestamp
+        );
+        if !self.context.is_empty() {
+            description.push_str(&context_table(&self.context));
+        }
+        for section in &self.sections {
+            description.push_str(&format!("\n### {}\n", section.title));
+            description.push_str(&context_table(&section.context));
+        }
+        for attachment in &self.attachments {
+            description.push_str(&format!(
+                "\n<details><summary>{}</summary>\n\n```\n{}\n```\n</details>\n",
+                attachment.filename, attachment.content
+            ));
+        }
+
+        let mut payload = json!({
+            "title": title,
+            "description": description,
+            "issue_type": if issue.incident { "incident" } else { "issue" },
+        });
+        if !issue.labels.is_empty() {
+            payload["labels"] = Value::String(issue.labels.join(","));
+        }
+
+        Ok(payload)
+    }
+}
+
+/// Render context entries as a Markdown table
+fn context_table(context: &[Context]) -> String {
+    let mut table = String::from("\n| | |\n|---|---|\n");
+    for ctx in context {
+        let value = match &ctx.kind {
+            ContextKind::Text => ctx.value.replace('|', "\\|").replace('\n', "<br>"),
+            ContextKind::Code => format!(
+                "<pre>{}</pre>",
+                crate::render::escape_html(&ctx.value).replace('\n', "<br>")
+            ),
+            ContextKind::Link { text } => format!("[{text}]({})", ctx.value),
+        };
+        table.push_str(&format!("| **{}** | {value} |\n", ctx.label));
+    }
+
+    table
+}
+
+impl DeliveryReceipt {
+    /// Read a `DeliveryReceipt` from the response of GitLab, linking to the
+    /// issue it opened
+    pub(crate) fn from_gitlab_response(status: StatusCode, body: &str) -> Self {
+        let mut receipt = Self::new(status);
+        if let Ok(issue) = serde_json::from_str::<IssueResponse>(body) {
+            receipt.permalink = Some(issue.web_url);
+        }
+
+        receipt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GitlabIssue;
+    use crate::{
+        Context, DeliveryReceipt, Endpoint, Notification, OversizePolicy, Section, Severity,
+    };
+    use reqwest::StatusCode;
+    use serde_json::Value;
+
+    /// A test to make sure notifications open issues or incidents with a Markdown description
+    #[test]
+    fn can_parse_into_issue() {
+        let mut notification = Notification::new("Payment failed")
+            .with_context("Order ID", 1042)
+            .with_section(Section::new("Request").with_context("Path", "/checkout"))
+            .with_attachment("trace.log", "at checkout.rs:42");
+        notification.severity = Severity::Critical;
+        notification.timestamp = String::from("2024-01-19 19:26:20.022233");
+        notification
+            .context
+            .push(Context::code("Query", "SELECT *\nFROM orders"));
+
+        let destination = Endpoint::new("https://gitlab.example.com/api/v4/projects/42/issues")
+            .gitlab(
+                GitlabIssue::new()
+                    .incident()
+                    .label("ops")
+                    .label("severity::critical"),
+            );
+        let payload: Value =
+            serde_json::from_str(&notification.render_for(&destination).unwrap().body).unwrap();
+        assert_eq!(payload["title"], "[Critical] Payment failed");
+        assert_eq!(payload["issue_type"], "incident");
+        assert_eq!(payload["labels"], "ops,severity::critical");
+        let description = payload["description"].as_str().unwrap();
+        assert!(description.starts_with(
+            "**Issue:** Payment failed\n\n**Timestamp:** 2024-01-19 19:26:20.022233\n"
+        ));
+        assert!(description.contains("| **Order ID** | 1042 |\n"));
+        assert!(description.contains("| **Query** | <pre>SELECT *<br>FROM orders</pre> |\n"));
+        assert!(description.contains("\n### Request\n"));
+        assert!(description.contains("<summary>trace.log</summary>"));
+
+        let payload = Notification::new("x".repeat(300))
+            .into_gitlab_payload(&GitlabIssue::new(), OversizePolicy::Truncate)
+            .unwrap();
+        assert_eq!(payload["title"].as_str().unwrap().chars().count(), 255);
+        assert_eq!(payload["issue_type"], "issue");
+        assert!(payload.get("labels").is_none());
+    }
+
+    /// A test to make sure the link to an opened issue is read from the response
+    #[test]
+    fn reads_issue_url() {
+        let body =
+            r#"{"id": 7, "iid": 3, "web_url": "https://gitlab.example.com/ops/api/-/issues/3"}"#;
+        let receipt = DeliveryReceipt::from_gitlab_response(StatusCode::CREATED, body);
+        assert_eq!(
+            receipt.permalink.as_deref(),
+            Some("https://gitlab.example.com/ops/api/-/issues/3")
+        );
+        let receipt = DeliveryReceipt::from_gitlab_response(StatusCode::CREATED, r#"{"ok": true}"#);
+        assert!(receipt.permalink.is_none());
+    }
+}
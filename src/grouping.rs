@@ -0,0 +1,87 @@
+use crate::idle::{IdleMap, IDLE_TIMEOUT};
+use crate::time::Instant;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Occurrences of notifications sharing a fingerprint
+struct Group {
+    first_seen: String,
+    count: u64,
+    last_sent: Instant,
+}
+
+/// Groups notifications sharing a fingerprint, sending at most one per window
+pub(crate) struct Grouper {
+    window: Duration,
+    groups: Mutex<IdleMap<Group>>,
+}
+impl Grouper {
+    /// Create a `Grouper` sending at most one notification per fingerprint every `window`
+    ///
+    /// Groups unseen for an hour (or the window, if longer) are forgotten.
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            groups: Mutex::new(IdleMap::new(IDLE_TIMEOUT.max(window))),
+        }
+    }
+
+    /// Record an occurrence of a fingerprint at a given timestamp
+    ///
+    /// Returns the total number of occurrences and when the first was seen,
+    /// or `None` if this occurrence should be grouped into the last one sent.
+    pub(crate) fn admit(&self, fingerprint: &str, timestamp: &str) -> Option<(u64, String)> {
+        let mut groups = self.groups.lock().unwrap_or_else(|e| e.into_inner());
+        let group = groups.entry(fingerprint, || Group {
+            first_seen: timestamp.to_string(),
+            count: 0,
+            last_sent: Instant::now(),
+        });
+        group.count += 1;
+        if group.count == 1 {
+            return Some((1, timestamp.to_string()));
+        }
+
+        if group.last_sent.elapsed() < self.window {
+            return None;
+        }
+        group.last_sent = Instant::now();
+
+        Some((group.count, group.first_seen.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Grouper;
+    use std::time::Duration;
+
+    /// A test to make sure occurrences within the window are grouped and counted
+    #[test]
+    fn groups_within_window() {
+        let grouper = Grouper::new(Duration::from_secs(3600));
+        assert_eq!(
+            grouper.admit("db-timeout", "2024-01-19 19:26:20.022233"),
+            Some((1, String::from("2024-01-19 19:26:20.022233")))
+        );
+        assert_eq!(
+            grouper.admit("db-timeout", "2024-01-19 19:27:20.022233"),
+            None
+        );
+        assert!(grouper
+            .admit("cache-miss", "2024-01-19 19:28:20.022233")
+            .is_some());
+    }
+
+    /// A test to make sure the next occurrence after the window carries the count
+    #[test]
+    fn counts_after_window() {
+        let grouper = Grouper::new(Duration::ZERO);
+        grouper.admit("db-timeout", "2024-01-19 19:26:20.022233");
+        grouper.admit("db-timeout", "2024-01-19 19:27:20.022233");
+        assert_eq!(
+            grouper.admit("db-timeout", "2024-01-19 19:28:20.022233"),
+            Some((3, String::from("2024-01-19 19:26:20.022233")))
+        );
+    }
+}
@@ -0,0 +1,228 @@
+// A small gzip encoder: LZ77 matching over a hash chain, coded with the
+// fixed Huffman codes of DEFLATE (RFC 1951) in a gzip member (RFC 1952).
+// Payloads are mostly repetitive JSON and logs, which this compresses well
+// enough, without pulling in a compression library.
+
+/// The furthest back a match can refer to
+const WINDOW: usize = 32 * 1024;
+
+/// The shortest and longest matches DEFLATE can code
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+/// How many earlier positions with the same hash are tried for a match
+const MAX_CHAIN: usize = 64;
+
+const HASH_BITS: u32 = 15;
+
+/// The base lengths of length codes 257 to 285, and their extra bits
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// The base distances of distance codes 0 to 29, and their extra bits
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Compress data into a gzip member
+pub(crate) fn compress(data: &[u8]) -> Vec<u8> {
+    // Magic, deflate, no flags or modification time, unknown OS
+    let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255];
+    out.extend(deflate(data));
+    out.extend(crc32(data).to_le_bytes());
+    out.extend((data.len() as u32).to_le_bytes());
+
+    out
+}
+
+/// Compress data into a single final DEFLATE block with fixed Huffman codes
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; data.len()];
+    let mut pos = 0;
+    while pos < data.len() {
+        let (length, distance) = longest_match(data, pos, &head, &prev);
+        let step = if length >= MIN_MATCH {
+            write_match(&mut bits, length, distance);
+            length
+        } else {
+            write_literal(&mut bits, u16::from(data[pos]));
+            1
+        };
+        for insert in pos..pos + step {
+            if insert + MIN_MATCH <= data.len() {
+                let hash = hash(&data[insert..]);
+                prev[insert] = head[hash];
+                head[hash] = insert;
+            }
+        }
+        pos += step;
+    }
+    write_literal(&mut bits, 256);
+
+    bits.finish()
+}
+
+/// The longest earlier match of the data at a position, as its length and distance
+fn longest_match(data: &[u8], pos: usize, head: &[usize], prev: &[usize]) -> (usize, usize) {
+    if pos + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+    let max = MAX_MATCH.min(data.len() - pos);
+    let (mut best_length, mut best_distance) = (0, 0);
+    let mut candidate = head[hash(&data[pos..])];
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || pos - candidate > WINDOW {
+            break;
+        }
+        let length = data[candidate..]
+            .iter()
+            .zip(&data[pos..pos + max])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if length > best_length {
+            (best_length, best_distance) = (length, pos - candidate);
+            if length == max {
+                break;
+            }
+        }
+        candidate = prev[candidate];
+    }
+
+    (best_length, best_distance)
+}
+
+/// Hash the next three bytes
+fn hash(bytes: &[u8]) -> usize {
+    let value = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+    (value.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+/// Write a literal byte (or the end of block) with its fixed Huffman code
+fn write_literal(bits: &mut BitWriter, symbol: u16) {
+    let (code, len) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xc0 + symbol - 280, 8),
+    };
+    bits.write_code(code, len);
+}
+
+/// Write a match as its length and distance codes, with their extra bits
+fn write_match(bits: &mut BitWriter, length: usize, distance: usize) {
+    let index = LENGTH_BASE
+        .iter()
+        .rposition(|&base| usize::from(base) <= length)
+        .unwrap_or_default();
+    write_literal(bits, 257 + index as u16);
+    bits.write(
+        (length - usize::from(LENGTH_BASE[index])) as u32,
+        LENGTH_EXTRA[index],
+    );
+
+    let index = DISTANCE_BASE
+        .iter()
+        .rposition(|&base| usize::from(base) <= distance)
+        .unwrap_or_default();
+    bits.write_code(index as u16, 5);
+    bits.write(
+        (distance - usize::from(DISTANCE_BASE[index])) as u32,
+        DISTANCE_EXTRA[index],
+    );
+}
+
+/// Packs bits least significant first, as DEFLATE does
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u32,
+    len: u8,
+}
+impl BitWriter {
+    /// Write the low bits of a value
+    fn write(&mut self, value: u32, len: u8) {
+        self.buffer |= value << self.len;
+        self.len += len;
+        while self.len >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    /// Write a Huffman code, which is packed most significant bit first
+    fn write_code(&mut self, code: u16, len: u8) {
+        let reversed = code.reverse_bits() >> (16 - len);
+        self.write(u32::from(reversed), len);
+    }
+
+    /// Flush the remaining bits, padded to a byte
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+/// The CRC-32 (IEEE) checksum of data
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, crc32};
+
+    /// A test to make sure checksums match a known CRC-32
+    #[test]
+    fn can_checksum() {
+        // python3 -c "import zlib; print(hex(zlib.crc32(b'123456789')))"
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    /// A test to make sure repetitive payloads compress into a known gzip member
+    #[test]
+    fn can_compress() {
+        // python3 -c "import gzip; print(gzip.decompress(bytes([...])))"
+        assert_eq!(
+            compress(b"{\"text\":\"hi hi hi hi\"}"),
+            [
+                0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff, 0xab, 0x56, 0x2a, 0x49, 0xad, 0x28, 0x51,
+                0xb2, 0x52, 0xca, 0xc8, 0x54, 0x80, 0x21, 0xa5, 0x5a, 0, 0x55, 0x79, 0x49, 0x70,
+                0x16, 0, 0, 0,
+            ]
+        );
+
+        let log = "ERROR request failed: connection reset by peer\n".repeat(1000);
+        let compressed = compress(log.as_bytes());
+        assert!(compressed.len() < log.len() / 20);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{Context, ContextKind, Endpoint, Notification, Notifier, Severity};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// The state of a single named heartbeat
+struct Beat {
+    interval: Duration,
+    last_seen: Instant,
+    missed: bool,
+}
+
+/// A change in a heartbeat worth notifying about
+enum Change {
+    Missed {
+        name: String,
+        interval: Duration,
+        last_seen: Duration,
+    },
+    Recovered {
+        name: String,
+    },
+}
+
+/// A dead man's switch for recurring jobs: each job pings its named
+/// heartbeat, and a monitor notifies when one isn't seen within its interval
+///
+/// Cloning a `Heartbeat` shares the same heartbeats, so jobs can each hold one.
+#[derive(Clone, Default)]
+pub struct Heartbeat {
+    beats: Arc<Mutex<HashMap<String, Beat>>>,
+}
+impl Heartbeat {
+    /// Create a `Heartbeat` without any named heartbeats
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect a ping for a named heartbeat at least every `interval`,
+    /// counting from now
+    pub fn register(&self, name: impl Into<String>, interval: Duration) {
+        self.lock().insert(
+            name.into(),
+            Beat {
+                interval,
+                last_seen: Instant::now(),
+                missed: false,
+            },
+        );
+    }
+
+    /// Record that a named heartbeat was seen, unregistered names are ignored
+    pub fn ping(&self, name: &str) {
+        if let Some(beat) = self.lock().get_mut(name) {
+            beat.last_seen = Instant::now();
+        }
+    }
+
+    /// Spawn a task on the current tokio runtime checking every `check_interval`
+    /// for missed heartbeats, sending a notification through a given `Notifier`
+    /// when one is missed and again when it recovers
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn_monitor(
+        &self,
+        notifier: Arc<Notifier>,
+        destination: Endpoint,
+        check_interval: Duration,
+    ) -> JoinHandle<()> {
+        let heartbeat = self.clone();
+        tokio::spawn(async move {
+            let mut checks = tokio::time::interval(check_interval);
+            loop {
+                checks.tick().await;
+                for change in heartbeat.changes() {
+                    // Failures are already handled by the `Notifier`
+                    let _ = notifier
+                        .send(change.into_notification(), &destination)
+                        .await;
+                }
+            }
+        })
+    }
+
+    /// Heartbeats missed or recovered since the last check
+    fn changes(&self) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for (name, beat) in self.lock().iter_mut() {
+            let last_seen = beat.last_seen.elapsed();
+            let missed = last_seen > beat.interval;
+            if missed && !beat.missed {
+                changes.push(Change::Missed {
+                    name: name.clone(),
+                    interval: beat.interval,
+                    last_seen,
+                });
+            } else if !missed && beat.missed {
+                changes.push(Change::Recovered { name: name.clone() });
+            }
+            beat.missed = missed;
+        }
+
+        changes
+    }
+
+    /// Lock the heartbeats, even if a panicking job poisoned them
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Beat>> {
+        self.beats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Change {
+    /// Describe the change as a `Notification`
+    fn into_notification(self) -> Notification {
+        match self {
+            Self::Missed {
+                name,
+                interval,
+                last_seen,
+            } => {
+                let mut notification = Notification::new(format!("Heartbeat `{name}` missed"));
+                notification.context = vec![
+                    Context {
+                        label: String::from("Expected Every"),
+                        value: format!("{}s", interval.as_secs()),
+                        kind: ContextKind::Text,
+                    },
+                    Context {
+                        label: String::from("Last Seen"),
+                        value: format!("{}s ago", last_seen.as_secs()),
+                        kind: ContextKind::Text,
+                    },
+                ];
+                notification
+            }
+            Self::Recovered { name } => {
+                let mut notification = Notification::new(format!("Heartbeat `{name}` recovered"));
+                notification.severity = Severity::Info;
+                notification
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Change, Heartbeat};
+    use std::time::Duration;
+
+    /// A test to make sure missed heartbeats are reported once, then their recovery
+    #[test]
+    fn reports_missed_and_recovered() {
+        let heartbeat = Heartbeat::new();
+        heartbeat.register("nightly-etl", Duration::ZERO);
+        heartbeat.register("hourly-sync", Duration::from_secs(3600));
+        std::thread::sleep(Duration::from_millis(1));
+
+        let changes = heartbeat.changes();
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], Change::Missed { name, .. } if name == "nightly-etl"));
+        assert!(heartbeat.changes().is_empty());
+
+        heartbeat.register("nightly-etl", Duration::from_secs(3600));
+        heartbeat.ping("nightly-etl");
+        assert!(heartbeat.changes().is_empty());
+    }
+
+    /// A test to make sure a ping after a missed heartbeat reports its recovery
+    #[test]
+    fn reports_recovery_on_ping() {
+        let heartbeat = Heartbeat::new();
+        heartbeat.register("nightly-etl", Duration::from_millis(1));
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(heartbeat.changes().len(), 1);
+
+        heartbeat.ping("nightly-etl");
+        let changes = heartbeat.changes();
+        assert!(matches!(&changes[0], Change::Recovered { name } if name == "nightly-etl"));
+    }
+}
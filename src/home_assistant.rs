@@ -0,0 +1,107 @@
+use crate::render::{fit_text, plain_text};
+use crate::{Endpoint, Error, Formatter, Notification, OversizePolicy, RenderedPayload, Severity};
+use serde_json::{json, Value};
+
+/// The most characters in the title of a notification
+const MAX_TITLE: usize = 250;
+
+/// The most characters in the message of a notification, leaving room for
+/// the rest of it within the 4 KB mobile push services take
+const MAX_MESSAGE: usize = 3000;
+
+/// Renders notifications as Home Assistant service data, as `MessageFormat::HomeAssistant`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct HomeAssistantFormatter {
+    policy: OversizePolicy,
+}
+impl HomeAssistantFormatter {
+    /// Create a `HomeAssistantFormatter` with the options of a destination
+    pub(crate) fn for_destination(destination: &Endpoint) -> Self {
+        Self {
+            policy: destination.oversize_policy(),
+        }
+    }
+}
+impl Formatter for HomeAssistantFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let payload = notification
+            .clone()
+            .into_home_assistant_payload(self.policy)?;
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into the service data of a
+    /// Home Assistant `notify` service, titled with the message, to the
+    /// target set as the channel
+    ///
+    /// The destination is the service's URL, like
+    /// `http://homeassistant.local:8123/api/services/notify/mobile_app_phone`,
+    /// authenticated with a long-lived access token through
+    /// `Endpoint::bearer_auth`. Critical notifications are sent with high
+    /// priority, so mobile apps alert even on silent or do not disturb.
+    pub(crate) fn into_home_assistant_payload(
+        self,
+        policy: OversizePolicy,
+    ) -> Result<Value, Error> {
+        let labels = self.labels.clone().unwrap_or_default();
+        let mut title = format!("{}: {}", labels.issue, self.message);
+        fit_text(&mut title, MAX_TITLE, policy)?;
+        let mut payload = json!({
+            "title": title,
+            "message": plain_text(&self, MAX_MESSAGE, policy)?,
+        });
+        if self.severity == Severity::Critical {
+            payload["data"] = json!({
+                "ttl": 0,
+                "priority": "high",
+                "push": {
+                    "sound": { "name": "default", "critical": 1, "volume": 1.0 },
+                },
+            });
+        }
+        if let Some(target) = self.channel {
+            payload["target"] = Value::String(target);
+        }
+
+        Ok(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MessageFormat, Notification, OversizePolicy, Severity};
+    use serde_json::Value;
+
+    /// A test to make sure notifications map onto notify service data, critical ones with priority
+    #[test]
+    fn can_parse_into_service_data() {
+        let mut notification = Notification::new("Backup failed")
+            .with_context("NAS", "diskstation")
+            .with_channel("mobile_app_pixel");
+        notification.timestamp = String::from("2024-01-19 19:26:20.022233");
+
+        let payload: Value = serde_json::from_str(
+            &notification
+                .clone()
+                .into_payload(MessageFormat::HomeAssistant, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(payload["title"], "Issue: Backup failed");
+        assert_eq!(
+            payload["message"],
+            "Timestamp: 2024-01-19 19:26:20.022233\nNAS: diskstation"
+        );
+        assert_eq!(payload["target"], "mobile_app_pixel");
+        assert!(payload.get("data").is_none());
+
+        notification.severity = Severity::Critical;
+        let payload = notification
+            .into_home_assistant_payload(OversizePolicy::Reject)
+            .unwrap();
+        assert_eq!(payload["data"]["priority"], "high");
+        assert_eq!(payload["data"]["push"]["sound"]["critical"], 1);
+    }
+}
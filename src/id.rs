@@ -0,0 +1,41 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Generate a random (version 4) UUID, like `0b6f3c1e-5a2d-4f8e-9c41-7d2e8a6b3f10`
+///
+/// The randomness comes from the keys std seeds each `RandomState` with,
+/// which is plenty to tell notifications apart.
+pub(crate) fn new_id() -> String {
+    let random = || RandomState::new().build_hasher().finish();
+    let bytes = (u128::from(random()) << 64 | u128::from(random())).to_be_bytes();
+    let mut uuid = [0; 16];
+    uuid.copy_from_slice(&bytes);
+    // Version 4, variant 1
+    uuid[6] = (uuid[6] & 0x0f) | 0x40;
+    uuid[8] = (uuid[8] & 0x3f) | 0x80;
+
+    let hex: String = uuid.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::new_id;
+
+    /// A test to make sure generated IDs are unique version 4 UUIDs
+    #[test]
+    fn generates_uuids() {
+        let (first, second) = (new_id(), new_id());
+        assert_ne!(first, second);
+        assert_eq!(first.len(), 36);
+        assert_eq!(&first[14..15], "4");
+        assert!(matches!(&first[19..20], "8" | "9" | "a" | "b"));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::time::Instant;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How long state kept per key (fingerprint, message or URL) lives unused
+/// by default, before it's evicted
+pub(crate) const IDLE_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// State kept per key, evicting keys that go unused for an idle timeout so
+/// ever changing keys (e.g. messages with IDs in them) can't grow it forever
+///
+/// Idle keys are swept at most once per timeout, so they live for at
+/// most twice as long.
+pub(crate) struct IdleMap<V> {
+    idle_timeout: Duration,
+    entries: HashMap<String, (V, Instant)>,
+    last_swept: Instant,
+}
+impl<V> IdleMap<V> {
+    /// Create an empty `IdleMap` evicting keys unused for a given time
+    pub(crate) fn new(idle_timeout: Duration) -> Self {
+        Self {
+            idle_timeout,
+            entries: HashMap::new(),
+            last_swept: Instant::now(),
+        }
+    }
+
+    /// The state of a key, created with `default` if there's none, marking
+    /// the key as used now
+    pub(crate) fn entry(&mut self, key: &str, default: impl FnOnce() -> V) -> &mut V {
+        self.sweep();
+        let now = Instant::now();
+        // Only allocate the key when it's new
+        if !self.entries.contains_key(key) {
+            self.entries.insert(key.to_string(), (default(), now));
+        }
+        let (value, used_at) = self
+            .entries
+            .get_mut(key)
+            .expect("the entry was just inserted");
+        *used_at = now;
+
+        value
+    }
+
+    /// The state of a key, if there is any, without marking it as used
+    pub(crate) fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.entries.get_mut(key).map(|(value, _)| value)
+    }
+
+    /// Forget the state of a key
+    pub(crate) fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+
+    /// Evict keys unused for the idle timeout, if it has passed since the last sweep
+    fn sweep(&mut self) {
+        if self.last_swept.elapsed() < self.idle_timeout {
+            return;
+        }
+        let idle_timeout = self.idle_timeout;
+        self.entries
+            .retain(|_, (_, used_at)| used_at.elapsed() < idle_timeout);
+        self.last_swept = Instant::now();
+    }
+
+    /// The number of keys with state
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IdleMap;
+    use std::time::Duration;
+
+    /// A test to make sure state is kept per key while used
+    #[test]
+    fn keeps_used_keys() {
+        let mut map = IdleMap::new(Duration::from_secs(3600));
+        *map.entry("db-timeout", || 0) += 1;
+        *map.entry("db-timeout", || 0) += 1;
+        *map.entry("cache-miss", || 0) += 1;
+        assert_eq!(map.get_mut("db-timeout"), Some(&mut 2));
+        assert_eq!(map.len(), 2);
+
+        map.remove("db-timeout");
+        assert_eq!(map.get_mut("db-timeout"), None);
+    }
+
+    /// A test to make sure keys that go unused are evicted
+    #[test]
+    fn evicts_idle_keys() {
+        let mut map = IdleMap::new(Duration::from_millis(10));
+        for id in 0..100 {
+            *map.entry(&format!("order {id} failed"), || 0) += 1;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+
+        *map.entry("db-timeout", || 0) += 1;
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get_mut("order 0 failed"), None);
+    }
+}
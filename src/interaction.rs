@@ -0,0 +1,385 @@
+use crate::{AlertState, Error, Notifier};
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+/// The block holding the buttons added by `Endpoint::action_buttons`
+const ACTIONS_BLOCK_ID: &str = "dev_notify_actions";
+
+/// Identifies clicks on the "Acknowledge" button
+const ACKNOWLEDGE_ACTION_ID: &str = "dev_notify_acknowledge";
+
+/// Identifies clicks on the "Resolve" button
+const RESOLVE_ACTION_ID: &str = "dev_notify_resolve";
+
+/// The most characters Slack accepts in the value of a button
+const MAX_BUTTON_VALUE: usize = 2000;
+
+/// How old (or far ahead) a signed request may be, in seconds, before
+/// it's rejected as a possible replay
+const MAX_REQUEST_AGE: u64 = 5 * 60;
+
+/// The host Slack sends response URLs on
+const RESPONSE_HOST: &str = "hooks.slack.com";
+
+/// A button added to Slack messages by `Endpoint::action_buttons`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Someone picked up the alert
+    Acknowledge,
+    /// Someone resolved the alert
+    Resolve,
+}
+impl Action {
+    /// The past tense of the action, e.g. "Acknowledged by ..."
+    fn done(self) -> &'static str {
+        match self {
+            Self::Acknowledge => "Acknowledged",
+            Self::Resolve => "Resolved",
+        }
+    }
+}
+
+/// A click on an "Acknowledge" or "Resolve" button, parsed from the
+/// interaction payload Slack posts to the app's Request URL
+///
+/// Parse it with `Interaction::from_request`, which verifies Slack signed the
+/// request, then pass it to `Notifier::respond` to update the message with
+/// who picked it up.
+#[derive(Clone, Debug)]
+pub struct Interaction {
+    /// The button that was clicked
+    pub action: Action,
+    /// The Slack ID of the user who clicked it
+    pub user_id: String,
+    /// The Slack username of the user who clicked it
+    pub user_name: String,
+    /// Identifies the notification: its fingerprint, idempotency key or message
+    pub value: String,
+    /// The channel the message was posted to
+    pub channel: Option<String>,
+    /// The timestamp identifying the message
+    pub message_ts: Option<String>,
+    response_url: Option<String>,
+    blocks: Vec<Value>,
+}
+impl Interaction {
+    /// Parse an `Interaction` from a request Slack posted to the app's
+    /// Request URL, after verifying it was signed with the app's signing
+    /// secret in the last 5 minutes
+    ///
+    /// `timestamp` and `signature` are the values of the
+    /// `X-Slack-Request-Timestamp` and `X-Slack-Signature` headers, and
+    /// `body` is the raw form encoded body (`payload=...`).
+    ///
+    /// Returns `Error::InvalidSignature` if the request can't be verified,
+    /// and `Ok(None)` if it isn't a click on one of the buttons added by
+    /// `Endpoint::action_buttons`.
+    pub fn from_request(
+        signing_secret: &str,
+        timestamp: &str,
+        signature: &str,
+        body: &str,
+    ) -> Result<Option<Self>, Error> {
+        let now = crate::time::SystemTime::now()
+            .duration_since(crate::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        verify(signing_secret, timestamp, signature, body, now)?;
+
+        Ok(Self::from_form(body))
+    }
+
+    /// Parse an `Interaction` from the form encoded body Slack posts (`payload=...`)
+    ///
+    /// Returns `None` if it isn't a click on one of the buttons added by
+    /// `Endpoint::action_buttons`.
+    fn from_form(body: &str) -> Option<Self> {
+        let form = Url::parse(&format!("http://localhost/?{body}")).ok()?;
+        let (_, payload) = form.query_pairs().find(|(name, _)| name == "payload")?;
+        Self::from_payload(&payload)
+    }
+
+    /// Parse an `Interaction` from the JSON interaction payload
+    ///
+    /// Returns `None` if it isn't a click on one of the buttons added by
+    /// `Endpoint::action_buttons`.
+    fn from_payload(payload: &str) -> Option<Self> {
+        let payload: Value = serde_json::from_str(payload).ok()?;
+        let clicked = payload.pointer("/actions/0")?;
+        let action = match clicked.get("action_id")?.as_str()? {
+            ACKNOWLEDGE_ACTION_ID => Action::Acknowledge,
+            RESOLVE_ACTION_ID => Action::Resolve,
+            _ => return None,
+        };
+        let text = |pointer: &str| payload.pointer(pointer)?.as_str().map(String::from);
+
+        Some(Self {
+            action,
+            user_id: text("/user/id")?,
+            user_name: text("/user/username")
+                .or_else(|| text("/user/name"))
+                .unwrap_or_default(),
+            value: clicked.get("value")?.as_str()?.to_string(),
+            channel: text("/channel/id").or_else(|| text("/container/channel_id")),
+            message_ts: text("/message/ts").or_else(|| text("/container/message_ts")),
+            response_url: text("/response_url"),
+            blocks: payload
+                .pointer("/message/blocks")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+
+    /// The blocks of the message once updated: noting who acted on it, and
+    /// only leaving the "Resolve" button once it's acknowledged
+    fn updated_blocks(&self) -> Vec<Value> {
+        let mut blocks: Vec<Value> = self
+            .blocks
+            .iter()
+            .filter(|block| block.get("block_id").and_then(Value::as_str) != Some(ACTIONS_BLOCK_ID))
+            .cloned()
+            .collect();
+        blocks.push(json!({
+            "type": "context",
+            "elements": [{
+                "type": "mrkdwn",
+                "text": format!("{} by <@{}>", self.action.done(), self.user_id),
+            }]
+        }));
+        if self.action == Action::Acknowledge {
+            blocks.push(actions_block(&self.value, &[Action::Resolve]));
+        }
+
+        blocks
+    }
+}
+
+impl Notifier {
+    /// Update the message an `Interaction` came from, noting who acknowledged
+    /// or resolved it, so the channel shows who picked up an alert
+    ///
+    /// The notification's record is updated too, if it's tracked by an `AlertStore`.
+    ///
+    /// Returns `Error::Config` if Slack didn't provide a URL to respond to,
+    /// or it isn't on `hooks.slack.com`.
+    pub async fn respond(&self, interaction: &Interaction) -> Result<(), Error> {
+        let Some(response_url) = &interaction.response_url else {
+            return Err(Error::Config(String::from(
+                "interaction has no response URL to update the message through",
+            )));
+        };
+        if !is_slack_response_url(response_url) {
+            return Err(Error::Config(format!(
+                "interaction response URL isn't on {RESPONSE_HOST}"
+            )));
+        }
+
+        let body = json!({
+            "replace_original": true,
+            "blocks": interaction.updated_blocks(),
+        });
+        let request = self
+            .http_client()
+            .post(response_url)
+            .header("Content-type", "application/json")
+            .body(body.to_string());
+        crate::runtime::send_checked(request).await?;
+
+        let state = match interaction.action {
+            Action::Acknowledge => AlertState::Acknowledged,
+            Action::Resolve => AlertState::Resolved,
+        };
+        self.transition(&interaction.value, state, Some(&interaction.user_name));
+
+        Ok(())
+    }
+}
+
+/// Verify a request was signed by Slack with a given signing secret, at a
+/// timestamp within `MAX_REQUEST_AGE` of `now`
+///
+/// The signature is `v0=<hex>`, an HMAC-SHA256 of `v0:{timestamp}:{body}`,
+/// compared in constant time.
+fn verify(
+    signing_secret: &str,
+    timestamp: &str,
+    signature: &str,
+    body: &str,
+    now: u64,
+) -> Result<(), Error> {
+    let signed_at: u64 = timestamp.parse().map_err(|_| Error::InvalidSignature)?;
+    if now.abs_diff(signed_at) > MAX_REQUEST_AGE {
+        return Err(Error::InvalidSignature);
+    }
+    let signature = signature
+        .strip_prefix("v0=")
+        .and_then(decode_hex)
+        .ok_or(Error::InvalidSignature)?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(signing_secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(b"v0:");
+    mac.update(timestamp.as_bytes());
+    mac.update(b":");
+    mac.update(body.as_bytes());
+    mac.verify_slice(&signature)
+        .map_err(|_| Error::InvalidSignature)
+}
+
+/// Decode a hex string into bytes, if it's valid hex
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok())
+        .collect()
+}
+
+/// Whether a response URL is one Slack issued, so responding can't be
+/// pointed at arbitrary hosts
+fn is_slack_response_url(url: &str) -> bool {
+    Url::parse(url)
+        .is_ok_and(|url| url.scheme() == "https" && url.host_str() == Some(RESPONSE_HOST))
+}
+
+/// A Block Kit actions block with buttons for given actions on a notification
+pub(crate) fn actions_block(value: &str, actions: &[Action]) -> Value {
+    let value: String = value.chars().take(MAX_BUTTON_VALUE).collect();
+    let buttons: Vec<Value> = actions
+        .iter()
+        .map(|action| {
+            let (text, action_id) = match action {
+                Action::Acknowledge => ("Acknowledge", ACKNOWLEDGE_ACTION_ID),
+                Action::Resolve => ("Resolve", RESOLVE_ACTION_ID),
+            };
+            let mut button = json!({
+                "type": "button",
+                "text": { "type": "plain_text", "text": text },
+                "action_id": action_id,
+                "value": value,
+            });
+            if *action == Action::Resolve {
+                button["style"] = json!("primary");
+            }
+            button
+        })
+        .collect();
+
+    json!({
+        "type": "actions",
+        "block_id": ACTIONS_BLOCK_ID,
+        "elements": buttons,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{actions_block, is_slack_response_url, verify, Action, Interaction};
+    use crate::{Error, Notifier};
+    use serde_json::json;
+
+    /// An interaction payload for a click on a given action
+    fn payload(action_id: &str) -> String {
+        json!({
+            "type": "block_actions",
+            "user": { "id": "U024BE7LH", "username": "jane" },
+            "channel": { "id": "C0123456" },
+            "message": {
+                "ts": "1705692380.000100",
+                "blocks": [
+                    { "type": "section", "text": { "type": "mrkdwn", "text": "`Issue`: Disk full" } },
+                    actions_block("disk-full", &[Action::Acknowledge, Action::Resolve]),
+                ],
+            },
+            "response_url": "https://hooks.slack.com/actions/T0/1/XXXX",
+            "actions": [{ "action_id": action_id, "value": "disk-full", "type": "button" }],
+        })
+        .to_string()
+    }
+
+    /// A test to make sure only requests signed by Slack recently are accepted
+    #[test]
+    fn verifies_signature() {
+        // From the example in Slack's guide to verifying requests
+        let secret = "8f742231b10e8888abcd99yyyzzz85a5";
+        let body = "token=xyzz0WbapA4vBCDEFasx0q6G&team_id=T1DC2JH3J&team_domain=testteamnow&channel_id=G8PSS9T3V&channel_name=foobar&user_id=U2CERLKJA&user_name=roadrunner&command=%2Fwebhook-collect&text=&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2FT1DC2JH3J%2F397700885554%2F96rGlfmibIGlgcZRskXaIFfN&trigger_id=398738663015.47445629121.803a0bc887a14d10d2c447fce8b6703c";
+        let signature = "v0=a2114d57b48eac39b9ad189dd8316235a7b4a8d21a10bd27519666489c69b503";
+        let now = 1_531_420_618;
+        assert!(verify(secret, "1531420618", signature, body, now).is_ok());
+
+        let tampered = body.replace("roadrunner", "coyote");
+        let stale = verify(secret, "1531420618", signature, body, now + 600);
+        for result in [
+            verify(secret, "1531420618", signature, &tampered, now),
+            verify("another secret", "1531420618", signature, body, now),
+            verify(secret, "1531420618", "v0=zz", body, now),
+            stale,
+        ] {
+            assert!(matches!(result, Err(Error::InvalidSignature)));
+        }
+
+        let unsigned = Interaction::from_request(secret, "1531420618", "", body);
+        assert!(matches!(unsigned, Err(Error::InvalidSignature)));
+    }
+
+    /// A test to make sure responses only go to Slack's response URLs
+    #[tokio::test]
+    async fn rejects_foreign_response_url() {
+        assert!(is_slack_response_url(
+            "https://hooks.slack.com/actions/T0/1/XXXX"
+        ));
+        assert!(!is_slack_response_url("http://hooks.slack.com/actions/T0"));
+        assert!(!is_slack_response_url(
+            "https://hooks.slack.com.evil.example/actions"
+        ));
+        assert!(!is_slack_response_url("http://169.254.169.254/latest"));
+
+        let payload = payload("dev_notify_resolve").replace(
+            "https://hooks.slack.com/actions/T0/1/XXXX",
+            "http://169.254.169.254/latest",
+        );
+        let interaction = Interaction::from_payload(&payload).unwrap();
+        let result = Notifier::new().respond(&interaction).await;
+        assert!(matches!(result, Err(Error::Config(_))));
+    }
+
+    /// A test to make sure button clicks are parsed from the form Slack posts
+    #[test]
+    fn can_parse_interaction() {
+        let mut form = reqwest::Url::parse("http://localhost").unwrap();
+        form.query_pairs_mut()
+            .append_pair("payload", &payload("dev_notify_acknowledge"));
+        let interaction = Interaction::from_form(form.query().unwrap()).unwrap();
+        assert_eq!(interaction.action, Action::Acknowledge);
+        assert_eq!(interaction.user_id, "U024BE7LH");
+        assert_eq!(interaction.user_name, "jane");
+        assert_eq!(interaction.value, "disk-full");
+        assert_eq!(interaction.channel.as_deref(), Some("C0123456"));
+
+        assert!(Interaction::from_payload(&payload("someone_elses_button")).is_none());
+    }
+
+    /// A test to make sure acknowledging notes who did and leaves only "Resolve"
+    #[test]
+    fn updates_blocks() {
+        let acknowledged = Interaction::from_payload(&payload("dev_notify_acknowledge")).unwrap();
+        let blocks = acknowledged.updated_blocks();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(
+            blocks[1]["elements"][0]["text"],
+            "Acknowledged by <@U024BE7LH>"
+        );
+        assert_eq!(blocks[2]["elements"].as_array().unwrap().len(), 1);
+        assert_eq!(blocks[2]["elements"][0]["action_id"], "dev_notify_resolve");
+
+        let resolved = Interaction::from_payload(&payload("dev_notify_resolve")).unwrap();
+        let blocks = resolved.updated_blocks();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1]["elements"][0]["text"], "Resolved by <@U024BE7LH>");
+    }
+}
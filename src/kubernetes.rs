@@ -0,0 +1,367 @@
+use crate::{Error, Notification, Notifier, Severity};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Where pods find the credentials of their service account
+const SERVICE_ACCOUNT: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// How long to wait before reconnecting a dropped watch
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// The event reasons notified on by default: crash loops, OOM kills,
+/// failed probes and evictions
+const DEFAULT_REASONS: [&str; 4] = ["BackOff", "OOMKilling", "Unhealthy", "Evicted"];
+
+/// Watches Kubernetes events, notifying on warnings with given reasons
+/// (e.g. `BackOff` for pods in `CrashLoopBackOff`) with the namespace and
+/// object as context, routed like any other notification
+///
+/// Repeats of an event share a fingerprint, so grouping can fold them.
+/// The service account needs to `list` and `watch` events.
+///
+/// Events are watched with plain list and watch requests rather than through
+/// `kube`, as notifying on them takes a single resource and a few fields,
+/// which isn't worth its dependency tree. Kubeconfig files aren't read: run
+/// in the cluster, or connect through `kubectl proxy` to use one.
+#[derive(Clone, Debug)]
+pub struct KubernetesWatcher {
+    http_client: Client,
+    api_url: String,
+    token: Option<String>,
+    namespace: Option<String>,
+    reasons: Vec<String>,
+}
+impl KubernetesWatcher {
+    /// Create a `KubernetesWatcher` for an API server, e.g.
+    /// `http://127.0.0.1:8001` through `kubectl proxy`, watching every
+    /// namespace for the default reasons
+    pub fn new(api_url: impl Into<String>) -> Self {
+        Self {
+            http_client: Client::new(),
+            api_url: api_url.into().trim_end_matches('/').to_string(),
+            token: None,
+            namespace: None,
+            reasons: DEFAULT_REASONS.map(String::from).to_vec(),
+        }
+    }
+
+    /// Create a `KubernetesWatcher` for the cluster it runs in, authenticated
+    /// as the service account of its pod
+    pub fn in_cluster() -> Result<Self, Error> {
+        let var = |name| {
+            std::env::var(name)
+                .map_err(|_| Error::Config(format!("{name} isn't set, not running in a cluster")))
+        };
+        let host = var("KUBERNETES_SERVICE_HOST")?;
+        let port = var("KUBERNETES_SERVICE_PORT")?;
+        let read = |file| {
+            std::fs::read(format!("{SERVICE_ACCOUNT}/{file}"))
+                .map_err(|e| Error::Config(format!("couldn't read service account {file}: {e}")))
+        };
+        let token = String::from_utf8_lossy(&read("token")?).trim().to_string();
+
+        #[allow(unused_mut)]
+        let mut client_builder = Client::builder();
+        #[cfg(feature = "__tls")]
+        {
+            let certificate = reqwest::Certificate::from_pem(&read("ca.crt")?)?;
+            client_builder = client_builder.add_root_certificate(certificate);
+        }
+        let host = if host.contains(':') {
+            format!("[{host}]")
+        } else {
+            host
+        };
+
+        Ok(Self {
+            http_client: client_builder.build()?,
+            token: Some(token),
+            ..Self::new(format!("https://{host}:{port}"))
+        })
+    }
+
+    /// Authenticate with a given bearer token
+    pub fn bearer_auth(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Only watch events in a given namespace
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Notify on warnings with given reasons instead of the defaults
+    /// (`BackOff`, `OOMKilling`, `Unhealthy` and `Evicted`)
+    pub fn reasons<I, S>(mut self, reasons: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.reasons = reasons.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Spawn a task on the current tokio runtime watching events, sending
+    /// a notification through a given `Notifier` (routed by it) for each
+    /// matching warning, and reconnecting whenever the watch drops
+    ///
+    /// Watches that end normally, or that are too far behind and have to
+    /// list events again, are restarted right away, and failed ones after
+    /// a delay.
+    ///
+    /// Only events from after it starts are notified. Aborting the returned
+    /// task stops watching.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn(self, notifier: Arc<Notifier>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut resource_version = None;
+            loop {
+                if let Err(e) = self.watch(&notifier, &mut resource_version).await {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(error = %e, "kubernetes event watch dropped");
+                    #[cfg(not(feature = "tracing"))]
+                    let _ = e;
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        })
+    }
+
+    /// Watch events until the watch ends, starting from a resource version
+    /// (or the current one) and keeping track of it
+    async fn watch(
+        &self,
+        notifier: &Notifier,
+        resource_version: &mut Option<String>,
+    ) -> Result<(), Error> {
+        if resource_version.is_none() {
+            let body = self
+                .get(&self.events_url())
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+            let list: EventList = serde_json::from_str(&body)
+                .map_err(|e| Error::Config(format!("unexpected event list: {e}")))?;
+            *resource_version = Some(list.metadata.resource_version);
+        }
+
+        let mut response = self
+            .get(&self.events_url())
+            .query(&[
+                ("watch", "true"),
+                ("resourceVersion", resource_version.as_deref().unwrap_or("")),
+            ])
+            .send()
+            .await?;
+        if response.status() == StatusCode::GONE {
+            // Too far behind, list events again for the current version
+            *resource_version = None;
+            return Ok(());
+        }
+        response = response.error_for_status()?;
+
+        let mut buffer = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let Ok(watch_event) = serde_json::from_slice::<WatchEvent>(&line) else {
+                    continue;
+                };
+                if watch_event.kind == "ERROR" {
+                    *resource_version = None;
+                    return Ok(());
+                }
+                let Ok(event) = serde_json::from_value::<Event>(watch_event.object) else {
+                    continue;
+                };
+                *resource_version = Some(event.metadata.resource_version.clone());
+                if let Some(notification) = self.notification(&event) {
+                    // Failures are already handled by the `Notifier`
+                    let _ = notifier.notify(notification).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Describe an event as a `Notification`, if it's a warning to notify on
+    fn notification(&self, event: &Event) -> Option<Notification> {
+        if event.kind != "Warning" || !self.reasons.contains(&event.reason) {
+            return None;
+        }
+        let object = &event.involved_object;
+        let namespace = object.namespace.as_deref().unwrap_or("default");
+
+        let mut notification = Notification::new(format!(
+            "{} {namespace}/{}: {}",
+            object.kind, object.name, event.reason
+        ))
+        .with_context("Namespace", namespace)
+        .with_context(&object.kind, &object.name)
+        .with_context("Reason", &event.reason)
+        .with_context("Message", &event.message);
+        if let Some(count) = event.count.filter(|count| *count > 1) {
+            notification = notification.with_context("Count", count);
+        }
+        notification.severity = Severity::Error;
+        notification.fingerprint = Some(format!(
+            "kubernetes:{namespace}/{}/{}",
+            object.name, event.reason
+        ));
+
+        Some(notification)
+    }
+
+    /// The URL of the events to watch
+    fn events_url(&self) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{}/api/v1/namespaces/{namespace}/events", self.api_url),
+            None => format!("{}/api/v1/events", self.api_url),
+        }
+    }
+
+    /// Start an authenticated GET request
+    fn get(&self, url: &str) -> RequestBuilder {
+        let request = self.http_client.get(url);
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+/// The parts of an event list we read
+#[derive(Deserialize)]
+struct EventList {
+    metadata: ObjectMeta,
+}
+
+/// A change to an event, as streamed by a watch
+#[derive(Deserialize)]
+struct WatchEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    object: serde_json::Value,
+}
+
+/// The parts of an event we read
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Event {
+    metadata: ObjectMeta,
+    involved_object: ObjectReference,
+    #[serde(default)]
+    reason: String,
+    #[serde(default)]
+    message: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+    count: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ObjectMeta {
+    #[serde(default)]
+    resource_version: String,
+}
+
+#[derive(Deserialize)]
+struct ObjectReference {
+    kind: String,
+    name: String,
+    namespace: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KubernetesWatcher, WatchEvent};
+    use crate::testing::MockServer;
+    use crate::{Notifier, Severity};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const BACK_OFF: &str = r#"{"type":"MODIFIED","object":{"kind":"Event","apiVersion":"v1","metadata":{"name":"api-7d9f.17a","namespace":"shop","resourceVersion":"48213"},"involvedObject":{"kind":"Pod","namespace":"shop","name":"api-7d9f","fieldPath":"spec.containers{api}"},"reason":"BackOff","message":"Back-off restarting failed container api in pod api-7d9f","count":6,"type":"Warning"}}"#;
+
+    /// A test to make sure matching warnings become notifications with the object as context
+    #[test]
+    fn can_parse_watched_event() {
+        let watch_event: WatchEvent = serde_json::from_str(BACK_OFF).unwrap();
+        let event = serde_json::from_value(watch_event.object).unwrap();
+
+        let watcher = KubernetesWatcher::new("http://127.0.0.1:8001/");
+        let notification = watcher.notification(&event).unwrap();
+        assert_eq!(notification.message, "Pod shop/api-7d9f: BackOff");
+        assert_eq!(notification.severity, Severity::Error);
+        assert_eq!(
+            notification.fingerprint.as_deref(),
+            Some("kubernetes:shop/api-7d9f/BackOff")
+        );
+        let context: Vec<_> = notification
+            .context
+            .iter()
+            .map(|c| (c.label.as_str(), c.value.as_str()))
+            .collect();
+        assert_eq!(context[0], ("Namespace", "shop"));
+        assert_eq!(context[1], ("Pod", "api-7d9f"));
+        assert_eq!(context[4], ("Count", "6"));
+
+        let watcher = watcher.reasons(["FailedMount"]);
+        assert!(watcher.notification(&event).is_none());
+    }
+
+    /// A test to make sure an expired watch lists events again right away
+    #[tokio::test]
+    async fn relists_when_gone() {
+        let list = |version: &str| {
+            (
+                200,
+                format!(r#"{{"metadata":{{"resourceVersion":"{version}"}}}}"#),
+            )
+        };
+        let server = MockServer::new(vec![
+            list("100"),
+            (410, String::new()),
+            list("200"),
+            (200, format!("{BACK_OFF}\n")),
+        ]);
+        let watcher = KubernetesWatcher::new(server.url()).spawn(Arc::new(Notifier::new()));
+        // Well within the delay before reconnecting a failed watch
+        for _ in 0..100 {
+            if server.requests().len() >= 4 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        watcher.abort();
+
+        let requests = server.requests();
+        assert!(requests[0].starts_with("GET /api/v1/events HTTP"));
+        assert!(requests[1].contains("watch=true&resourceVersion=100"));
+        assert!(requests[2].starts_with("GET /api/v1/events HTTP"));
+        assert!(requests[3].contains("watch=true&resourceVersion=200"));
+    }
+
+    /// A test to make sure a namespace scopes the watch
+    #[test]
+    fn scopes_to_namespace() {
+        let watcher = KubernetesWatcher::new("http://127.0.0.1:8001/");
+        assert_eq!(watcher.events_url(), "http://127.0.0.1:8001/api/v1/events");
+        assert_eq!(
+            watcher.namespace("shop").events_url(),
+            "http://127.0.0.1:8001/api/v1/namespaces/shop/events"
+        );
+    }
+}
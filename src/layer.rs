@@ -0,0 +1,160 @@
+use crate::{Context, ContextKind, Notification, Severity, Worker};
+use std::fmt;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer;
+
+/// A `tracing_subscriber` layer turning events at or above a level
+/// (`ERROR` by default) into notifications, delivered by a `Worker`
+///
+/// The event message becomes the `Notification` message and every
+/// other field becomes `Context`. Events from this crate are ignored,
+/// so a failing delivery can't notify about itself.
+pub struct DevNotifyLayer {
+    worker: Worker,
+    level: Level,
+}
+impl DevNotifyLayer {
+    /// Create a `DevNotifyLayer` queueing notifications on a given `Worker`
+    pub fn new(worker: Worker) -> Self {
+        Self {
+            worker,
+            level: Level::ERROR,
+        }
+    }
+
+    /// Notify on events at or above a given level instead of only `ERROR`
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+}
+impl<S: Subscriber> tracing_subscriber::Layer<S> for DevNotifyLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: layer::Context<'_, S>) {
+        let metadata = event.metadata();
+        // More verbose levels compare as greater
+        if *metadata.level() > self.level || metadata.target().starts_with("dev_notify") {
+            return;
+        }
+
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+        visitor.context.push(Context {
+            label: String::from("Target"),
+            value: metadata.target().to_string(),
+            kind: ContextKind::Text,
+        });
+
+        let mut notification = Notification::new(visitor.message);
+        notification.context.extend(visitor.context);
+        notification.severity = severity(metadata.level());
+        self.worker.notify(notification);
+    }
+}
+
+/// Map a `tracing` level onto a `Severity`
+fn severity(level: &Level) -> Severity {
+    match *level {
+        Level::ERROR => Severity::Error,
+        Level::WARN => Severity::Warning,
+        Level::INFO => Severity::Info,
+        _ => Severity::Debug,
+    }
+}
+
+/// Collects the fields of an event into a message and `Context`
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    context: Vec<Context>,
+}
+impl Visit for EventVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.to_string())
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, format!("{value:?}"))
+    }
+}
+impl EventVisitor {
+    /// Record a field as the message or as `Context`
+    fn record(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = value;
+        } else {
+            self.context.push(Context {
+                label: field.name().to_string(),
+                value,
+                kind: ContextKind::Text,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockDestination;
+    use crate::{DevNotifyLayer, Notifier, Severity, Worker};
+    use std::sync::Arc;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// A test to make sure error events become notifications with context
+    #[tokio::test]
+    async fn notifies_on_error_events() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+        let subscriber = tracing_subscriber::registry().with(DevNotifyLayer::new(worker));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "payments", "Starting up");
+            tracing::error!(target: "payments", customer_id = 0, "Failed to capture transaction");
+        });
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        let delivered = mock.delivered();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(delivered[0].message, "Failed to capture transaction");
+        assert_eq!(delivered[0].severity, Severity::Error);
+        assert_eq!(delivered[0].context[0].label, "customer_id");
+        assert_eq!(delivered[0].context[0].value, "0");
+        assert_eq!(delivered[0].context[1].value, "payments");
+    }
+
+    /// A test to make sure notifications from events link to the active trace
+    #[cfg(feature = "otel")]
+    #[tokio::test]
+    async fn attaches_trace_context() {
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+        };
+
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+        let subscriber = tracing_subscriber::registry().with(DevNotifyLayer::new(worker));
+        let span = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _guard = opentelemetry::Context::current()
+                .with_remote_span_context(span)
+                .attach();
+            tracing::error!(target: "payments", "Failed to capture transaction");
+        });
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        let context = &mock.delivered()[0].context;
+        assert_eq!(context[0].label, "trace_id");
+        assert_eq!(context[0].value, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(context[1].label, "span_id");
+    }
+}
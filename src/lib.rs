@@ -1,86 +1,549 @@
-use reqwest::{self, Error};
-use serde::Deserialize;
-use serde_json::json;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize)]
+// Lets code generated by `notify_on_error` refer to `::dev_notify` within this crate
+#[cfg(feature = "macros")]
+extern crate self as dev_notify;
+
+mod audit;
+#[cfg(feature = "bark")]
+mod bark;
+mod batch;
+mod borrowed;
+mod circuit;
+#[cfg(feature = "config")]
+mod config;
+mod deploy;
+#[cfg(feature = "worker")]
+mod digest;
+#[cfg(feature = "discord")]
+mod discord;
+#[cfg(feature = "docker")]
+mod docker;
+#[cfg(feature = "email")]
+mod email;
+mod endpoint;
+mod env;
+mod error;
+#[cfg(feature = "escalation")]
+mod escalation;
+#[cfg(feature = "worker")]
+mod ext;
+mod format;
+mod formatter;
+#[cfg(feature = "github-actions")]
+mod github;
+#[cfg(feature = "gitlab")]
+mod gitlab;
+mod grouping;
+mod gzip;
+#[cfg(feature = "heartbeat")]
+mod heartbeat;
+#[cfg(feature = "home-assistant")]
+mod home_assistant;
+mod id;
+mod idle;
+#[cfg(feature = "slack")]
+mod interaction;
+#[cfg(feature = "kubernetes")]
+mod kubernetes;
+#[cfg(feature = "tracing-layer")]
+mod layer;
+mod locale;
+#[cfg(feature = "log")]
+mod logger;
+mod maintenance;
+#[cfg(feature = "markers")]
+mod markers;
+mod metadata;
+mod metrics;
+#[cfg(feature = "tower")]
+mod middleware;
+#[cfg(feature = "monitor")]
+mod monitor;
+mod notifier;
+mod ordering;
+#[cfg(feature = "otel")]
+mod otel;
+#[cfg(feature = "panic-hook")]
+mod panic;
+#[cfg(feature = "pubsub")]
+mod pubsub;
+mod quiet;
+mod rate_limit;
+mod receipt;
+#[cfg(feature = "redaction")]
+mod redaction;
+mod render;
+mod routing;
+mod runtime;
+mod sampling;
+#[cfg(feature = "scrubbing")]
+mod scrubbing;
+#[cfg(feature = "service-bus")]
+mod service_bus;
+mod severity;
+#[cfg(feature = "signal")]
+mod signal;
+#[cfg(feature = "signing")]
+mod signing;
+#[cfg(feature = "slack")]
+mod slack;
+#[cfg(feature = "sms")]
+mod sms;
+mod spool;
+#[cfg(all(feature = "systemd", target_os = "linux"))]
+mod systemd;
+#[cfg(feature = "worker")]
+mod task;
+#[cfg(feature = "telegram")]
+mod telegram;
+mod test_report;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod time;
+mod timestamp;
+mod tracking;
+mod truncation;
+mod validation;
+#[cfg(feature = "victorops")]
+mod victorops;
+#[cfg(feature = "web-push")]
+mod web_push;
+#[cfg(feature = "worker")]
+mod worker;
+#[cfg(feature = "slack")]
+mod workflow;
+
+pub use audit::AuditEntry;
+pub use borrowed::NotificationRef;
+#[cfg(feature = "config")]
+pub use config::Config;
+pub use deploy::DeployNotification;
+#[cfg(feature = "macros")]
+pub use dev_notify_macros::notify_on_error;
+#[cfg(feature = "worker")]
+pub use digest::Digest;
+#[cfg(feature = "docker")]
+pub use docker::DockerWatcher;
+#[cfg(feature = "email")]
+pub use email::{EmailApi, EmailTemplate};
+pub use endpoint::Endpoint;
+pub use error::Error;
+#[cfg(feature = "escalation")]
+pub use escalation::Escalation;
+#[cfg(feature = "macros")]
+pub use ext::__private;
+#[cfg(feature = "worker")]
+pub use ext::ResultExt;
+pub use format::{MessageFormat, OversizePolicy, SeverityEmoji};
+pub use formatter::{Formatter, RenderedPayload};
+#[cfg(feature = "github-actions")]
+pub use github::GithubActions;
+#[cfg(feature = "gitlab")]
+pub use gitlab::GitlabIssue;
+#[cfg(feature = "heartbeat")]
+pub use heartbeat::Heartbeat;
+#[cfg(feature = "slack")]
+pub use interaction::{Action, Interaction};
+#[cfg(feature = "kubernetes")]
+pub use kubernetes::KubernetesWatcher;
+#[cfg(feature = "tracing-layer")]
+pub use layer::DevNotifyLayer;
+pub use locale::Locale;
+#[cfg(feature = "log")]
+pub use logger::DevNotifyLogger;
+pub use maintenance::{MaintenanceAction, MaintenanceWindow};
+pub use metadata::HostMetadata;
+pub use metrics::DeliveryMetrics;
+#[cfg(feature = "axum")]
+pub use middleware::notify_on_server_error;
+#[cfg(feature = "tower")]
+pub use middleware::{NotifyLayer, NotifyService};
+#[cfg(feature = "monitor")]
+pub use monitor::Monitor;
+pub use notifier::{Notifier, NotifierBuilder};
+pub use ordering::ContextOrder;
+#[cfg(feature = "panic-hook")]
+pub use panic::install_panic_hook;
+#[cfg(feature = "pubsub")]
+pub use pubsub::PubSub;
+pub use quiet::QuietHours;
+pub use rate_limit::{Overflow, RateLimit};
+pub use receipt::DeliveryReceipt;
+#[cfg(feature = "redaction")]
+pub use redaction::Redactor;
+pub use routing::Route;
+pub use sampling::SamplingPolicy;
+#[cfg(feature = "scrubbing")]
+pub use scrubbing::{Scrub, Scrubber};
+#[cfg(feature = "service-bus")]
+pub use service_bus::ServiceBus;
+pub use severity::{ParseSeverityError, Severity};
+#[cfg(feature = "signal")]
+pub use signal::Signal;
+#[cfg(feature = "slack")]
+pub use slack::SlackFormatter;
+#[cfg(feature = "sms")]
+pub use sms::Sms;
+#[cfg(all(feature = "systemd", target_os = "linux"))]
+pub use systemd::SystemdWatcher;
+pub use test_report::{FailedTest, TestReport};
+pub use timestamp::TimestampFormat;
+pub use tracking::{AlertRecord, AlertState, AlertStore, MemoryAlertStore};
+pub use truncation::ContextLimit;
+pub use validation::{NotificationBuilder, ValidationError};
+#[cfg(feature = "web-push")]
+pub use web_push::{Vapid, WebPush};
+#[cfg(feature = "sink")]
+pub use worker::WorkerSink;
+#[cfg(feature = "worker")]
+pub use worker::{Scheduled, Worker};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Context {
     pub label: String,
     pub value: String,
+    /// Defaults to `ContextKind::Text` when omitted
+    #[serde(default)]
+    pub kind: ContextKind,
 }
 impl Context {
+    /// Create a `Context` whose value is rendered in a code block, preserving
+    /// its newlines, e.g. for a backtrace or command output
+    pub fn code(label: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            value: value.into(),
+            kind: ContextKind::Code,
+        }
+    }
+
+    /// Create a `Context` whose value is a URL, rendered as a link with a given text
+    pub fn link(label: impl Into<String>, url: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            value: url.into(),
+            kind: ContextKind::Link { text: text.into() },
+        }
+    }
+
     /// Convert into formatted string
+    #[cfg(test)]
     fn formatted(&self) -> String {
-        format!(">`{}`: {}\n", self.label, self.value)
+        let mut formatted = String::with_capacity(self.formatted_len());
+        self.write_formatted(&mut formatted);
+        formatted
+    }
+
+    /// Append the formatted context to a message, without allocating
+    fn write_formatted(&self, message: &mut String) {
+        message.push_str(">`");
+        message.push_str(&self.label);
+        message.push_str(match self.kind {
+            ContextKind::Code => "`:\n",
+            ContextKind::Text | ContextKind::Link { .. } => "`: ",
+        });
+        self.write_value(message);
+        message.push('\n');
+    }
+
+    /// Append the formatted value to a message, without allocating
+    fn write_value(&self, message: &mut String) {
+        match &self.kind {
+            ContextKind::Text => message.push_str(&self.value),
+            ContextKind::Code => {
+                message.push_str("```\n");
+                message.push_str(&self.value);
+                message.push_str("\n```");
+            }
+            ContextKind::Link { text } => {
+                message.push('<');
+                message.push_str(&self.value);
+                message.push('|');
+                message.push_str(text);
+                message.push('>');
+            }
+        }
+    }
+
+    /// The length of the formatted context, at most, to size messages up front
+    fn formatted_len(&self) -> usize {
+        let text = match &self.kind {
+            ContextKind::Link { text } => text.len(),
+            ContextKind::Text | ContextKind::Code => 0,
+        };
+        self.label.len() + self.value.len() + text + 16
+    }
+}
+
+/// How the value of a `Context` is rendered
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContextKind {
+    /// Inline text
+    #[default]
+    Text,
+    /// A code block, preserving newlines
+    Code,
+    /// A link to the value (a URL), with a given text
+    Link { text: String },
+}
+
+/// Context grouped under a title, e.g. "Request" or "Environment"
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Section {
+    pub title: String,
+    pub context: Vec<Context>,
+}
+impl Section {
+    /// Create a `Section` with a given title and no context
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            context: Vec::new(),
+        }
+    }
+
+    /// Add a labelled value to the section
+    pub fn with_context(mut self, label: impl Into<String>, value: impl ToString) -> Self {
+        self.context.push(Context {
+            label: label.into(),
+            value: value.to_string(),
+            kind: ContextKind::Text,
+        });
+        self
+    }
+}
+
+/// The built-in labels of a rendered message, "Issue" and "Timestamp" by default
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Labels {
+    /// Labels the message, e.g. "Alert" or "Event"
+    pub issue: String,
+    /// Labels the timestamp, e.g. "Occurred at"
+    pub timestamp: String,
+}
+impl Labels {
+    /// Create `Labels` for the message and timestamp
+    pub fn new(issue: impl Into<String>, timestamp: impl Into<String>) -> Self {
+        Self {
+            issue: issue.into(),
+            timestamp: timestamp.into(),
+        }
+    }
+}
+impl Default for Labels {
+    fn default() -> Self {
+        Self::new("Issue", "Timestamp")
     }
 }
 
-#[derive(Deserialize)]
+/// A text payload too large to fit in a message, e.g. a log excerpt or JSON body
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Attachment {
+    pub filename: String,
+    pub content: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Notification {
     pub message: String,
     pub timestamp: String,
     pub context: Vec<Context>,
+    /// Defaults to `Severity::Error` when omitted
+    #[serde(default)]
+    pub severity: Severity,
+    /// Identifies the `Notification` across retries, so destinations that
+    /// support it (via the `Idempotency-Key` header) deliver it only once
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Groups notifications for the same underlying issue when the `Notifier`
+    /// groups notifications, defaults to grouping by message
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+    /// Context grouped under titles, rendered after the ungrouped `context`
+    #[serde(default)]
+    pub sections: Vec<Section>,
+    /// Uploaded and linked from the message when sent through the Slack
+    /// Web API, otherwise included inline (truncated)
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// Overrides the built-in labels of the message, otherwise those of the
+    /// `Notifier` (or the defaults) are used
+    #[serde(default)]
+    pub labels: Option<Labels>,
+    /// The channel to post to, overriding the channel of the destination
+    /// (Slack Web API, or webhooks that accept a `channel`)
+    #[serde(default)]
+    pub channel: Option<String>,
+    /// Identifies the `Notification` in payloads, delivery receipts and audit
+    /// logs, generated (a UUID) when it's created or first sent
+    #[serde(default)]
+    pub id: Option<String>,
 }
 impl Notification {
-    /// Consume the `Notification` and send it to a given destination (API endpoint)
-    pub async fn send(self, destination: &str) -> Result<(), Error> {
-        // Initiate the HTTP client
-        let http_client = reqwest::Client::new();
+    /// Create a `Notification` with a given message, timestamped now (UTC)
+    /// with `Severity::Error` and no context
+    ///
+    /// With the `otel` feature, the IDs of the active trace and span (if any)
+    /// are added as `trace_id` and `span_id` context.
+    pub fn new(message: impl Into<String>) -> Self {
+        let notification = Self {
+            message: message.into(),
+            timestamp: timestamp::now(),
+            context: Vec::new(),
+            severity: Severity::default(),
+            idempotency_key: None,
+            fingerprint: None,
+            sections: Vec::new(),
+            attachments: Vec::new(),
+            labels: None,
+            channel: None,
+            id: Some(id::new_id()),
+        };
+        #[cfg(feature = "otel")]
+        let notification = notification.with_trace_context();
+
+        notification
+    }
+
+    /// Create a `Notification` describing an error, with each error in its
+    /// chain of sources as a "Caused By" context entry
+    ///
+    /// This isn't a blanket `From` impl since it would conflict with `From<&str>`.
+    pub fn from_error<E: std::error::Error + ?Sized>(error: &E) -> Self {
+        let mut notification = Self::new(error.to_string());
+        let causes = std::iter::successors(error.source(), |cause| cause.source());
+        for cause in causes {
+            notification = notification.with_context("Caused By", cause);
+        }
+
+        notification
+    }
+
+    /// Add a labelled value to the context, e.g. as an error propagates up the stack
+    pub fn with_context(mut self, label: impl Into<String>, value: impl ToString) -> Self {
+        self.context.push(Context {
+            label: label.into(),
+            value: value.to_string(),
+            kind: ContextKind::Text,
+        });
+        self
+    }
+
+    /// Add a `Section` of context, rendered as its own block
+    pub fn with_section(mut self, section: Section) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    /// Render the message with given built-in labels, e.g. "Alert" instead of "Issue"
+    pub fn with_labels(mut self, labels: Labels) -> Self {
+        self.labels = Some(labels);
+        self
+    }
 
-        // Parse the `Notification` into a slack message
-        let slack_message = self.into_slack_message();
+    /// Post to a given channel (e.g. `#deploys` or `C0123456`) instead of
+    /// the channel of the destination
+    pub fn with_channel(mut self, channel: impl Into<String>) -> Self {
+        self.channel = Some(channel.into());
+        self
+    }
 
-        // Build and send the HTTP request to a given destination
-        // with the payload being our derived slack message
-        http_client
-            .post(destination)
-            .header("Content-type", "application/json")
-            .body(slack_message)
-            .send()
-            .await?;
+    /// Attach a text payload under a given filename, e.g. a log excerpt
+    pub fn with_attachment(
+        mut self,
+        filename: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        self.attachments.push(Attachment {
+            filename: filename.into(),
+            content: content.into(),
+        });
+        self
+    }
 
-        Ok(())
+    /// Consume the `Notification` and send it to a given destination (API endpoint)
+    ///
+    /// Uses a default `Notifier`, see `Notifier::builder` for proxy configuration
+    /// and `Endpoint` for sending custom headers.
+    pub async fn send(self, destination: &str) -> Result<Option<DeliveryReceipt>, Error> {
+        Notifier::new()
+            .send(self, &Endpoint::new(destination))
+            .await
     }
 
     /// Consume the `Notification` and parse it into a message (String)
+    ///
+    /// Renders into a single `String` sized up front, since this runs for
+    /// every notification sent.
     fn into_message(self) -> String {
-        let mut message = format!(
-            "`Issue`: {}\n>`Timestamp`: _{}_\n",
-            self.message, self.timestamp
-        );
-        for ctx in self.context {
-            message.push_str(&ctx.formatted());
+        let labels = self.labels.unwrap_or_default();
+        let contexts = self
+            .sections
+            .iter()
+            .flat_map(|section| &section.context)
+            .chain(&self.context);
+        let capacity = labels.issue.len()
+            + self.message.len()
+            + labels.timestamp.len()
+            + self.timestamp.len()
+            + 16
+            + contexts.map(Context::formatted_len).sum::<usize>()
+            + self
+                .sections
+                .iter()
+                .map(|section| section.title.len() + 4)
+                .sum::<usize>();
+
+        let mut message = String::with_capacity(capacity);
+        for part in [
+            "`",
+            &labels.issue,
+            "`: ",
+            &self.message,
+            "\n>`",
+            &labels.timestamp,
+            "`: _",
+            &self.timestamp,
+            "_\n",
+        ] {
+            message.push_str(part);
+        }
+        for ctx in &self.context {
+            ctx.write_formatted(&mut message);
+        }
+        for section in &self.sections {
+            message.push_str("\n*");
+            message.push_str(&section.title);
+            message.push_str("*\n");
+            for ctx in &section.context {
+                ctx.write_formatted(&mut message);
+            }
         }
 
         message
     }
-
-    /// Consume the `Notification` and parse it into a slack message (JSON String)
-    fn into_slack_message(self) -> String {
-        let message = self.into_message();
-
-        // Build the JSON payload required for a slack message
-        json!({
-            "blocks": vec![
-                json!({
-                    "type": "section",
-                    "text": {
-                        "type": "mrkdwn",
-                        "text": message,
-                    }
-                })
-            ]
-        })
-        .to_string()
+}
+impl From<&str> for Notification {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+impl From<String> for Notification {
+    fn from(message: String) -> Self {
+        Self::new(message)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Context, Notification};
+    use crate::{Context, ContextKind, Labels, Notification, Section, Severity};
 
     /// Test Case Structure
     struct TestCase {
         expected_message: String,
+        #[cfg_attr(not(feature = "slack"), allow(dead_code))]
         expected_slack_message: String,
         expected_context: Vec<String>,
         notification: Notification,
@@ -111,6 +574,7 @@ mod tests {
     }
 
     /// A test to make sure slack message parsing is working
+    #[cfg(feature = "slack")]
     #[test]
     fn can_parse_into_slack_message() {
         let scenarios = get_scenarios();
@@ -121,6 +585,108 @@ mod tests {
         }
     }
 
+    /// A test to make sure new notifications are timestamped with defaults
+    #[test]
+    fn can_create_notification() {
+        let notification = Notification::new("Build failed");
+        assert_eq!(notification.message, "Build failed");
+        assert_eq!(
+            notification.timestamp.len(),
+            "2024-01-19 19:26:20.022233".len()
+        );
+        assert_eq!(notification.severity, Severity::Error);
+        assert!(notification.context.is_empty());
+    }
+
+    /// A test to make sure the built-in labels can be overridden
+    #[test]
+    fn can_override_labels() {
+        let mut notification =
+            Notification::new("Deploy failed").with_labels(Labels::new("Alert", "Occurred at"));
+        notification.timestamp = String::from("2024-01-19 19:26:20.022233");
+        assert_eq!(
+            notification.into_message(),
+            "`Alert`: Deploy failed\n>`Occurred at`: _2024-01-19 19:26:20.022233_\n"
+        );
+    }
+
+    /// A test to make sure context can be chained onto a notification in order
+    #[test]
+    fn can_chain_context() {
+        let notification = Notification::new("Payment failed")
+            .with_context("Order ID", 1042)
+            .with_context("Customer ID", "cus_0d738c01");
+        let context: Vec<_> = notification
+            .context
+            .iter()
+            .map(|c| (c.label.as_str(), c.value.as_str()))
+            .collect();
+        assert_eq!(
+            context,
+            [("Order ID", "1042"), ("Customer ID", "cus_0d738c01")]
+        );
+    }
+
+    /// A test to make sure sections are rendered as separate quoted blocks
+    #[test]
+    fn can_parse_sections_into_message() {
+        let mut notification = Notification::new("Payment failed")
+            .with_context("Order ID", 1042)
+            .with_section(Section::new("Request").with_context("Path", "/checkout"))
+            .with_section(Section::new("Environment").with_context("Region", "us-east-1"));
+        notification.timestamp = String::from("2024-01-19 19:26:20.022233");
+        assert_eq!(
+            notification.into_message(),
+            "`Issue`: Payment failed\n>`Timestamp`: _2024-01-19 19:26:20.022233_\n>`Order ID`: 1042\n\n*Request*\n>`Path`: /checkout\n\n*Environment*\n>`Region`: us-east-1\n"
+        );
+    }
+
+    /// A test to make sure code context keeps its newlines in a code block
+    #[test]
+    fn can_parse_code_context() {
+        let context = Context::code("Backtrace", "0: main\n1: start");
+        assert_eq!(
+            context.formatted(),
+            ">`Backtrace`:\n```\n0: main\n1: start\n```\n"
+        );
+    }
+
+    /// A test to make sure link context renders as a Slack link
+    #[test]
+    fn can_parse_link_context() {
+        let context = Context::link("Runbook", "https://wiki.internal/db", "Open runbook");
+        assert_eq!(
+            context.formatted(),
+            ">`Runbook`: <https://wiki.internal/db|Open runbook>\n"
+        );
+    }
+
+    /// A test to make sure notifications can be created from messages and errors
+    #[test]
+    fn can_convert_into_notification() {
+        let notification = Notification::from("Build failed");
+        assert_eq!(notification.message, "Build failed");
+
+        #[derive(Debug)]
+        struct CaptureError(std::io::Error);
+        impl std::fmt::Display for CaptureError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "payment capture failed")
+            }
+        }
+        impl std::error::Error for CaptureError {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                Some(&self.0)
+            }
+        }
+
+        let error = CaptureError(std::io::Error::other("connection reset"));
+        let notification = Notification::from_error(&error);
+        assert_eq!(notification.message, "payment capture failed");
+        assert_eq!(notification.context[0].label, "Caused By");
+        assert_eq!(notification.context[0].value, "connection reset");
+    }
+
     /// Test case scenarios for each test to use
     fn get_scenarios() -> Vec<TestCase> {
         vec![
@@ -134,7 +700,16 @@ mod tests {
                     context: vec![Context {
                         label: String::from("Customer ID"),
                         value: String::from("0"),
+                        kind: ContextKind::Text,
                     }],
+                    severity: Severity::Error,
+                    idempotency_key: None,
+                    fingerprint: None,
+                    sections: Vec::new(),
+                    attachments: Vec::new(),
+                    labels: None,
+                    channel: None,
+                    id: None,
                 },
             },
             TestCase {
@@ -148,12 +723,22 @@ mod tests {
                         Context {
                             label: String::from("Customer ID"),
                             value: String::from("0"),
+                            kind: ContextKind::Text,
                         },
                         Context {
                             label: String::from("Transaction ID"),
                             value: String::from("0d738c014b6a00ddb68edafc"),
+                            kind: ContextKind::Text,
                         }
                     ],
+                    severity: Severity::Error,
+                    idempotency_key: None,
+                    fingerprint: None,
+                    sections: Vec::new(),
+                    attachments: Vec::new(),
+                    labels: None,
+                    channel: None,
+                    id: None,
                 }
             },
             TestCase {
@@ -167,12 +752,22 @@ mod tests {
                         Context {
                             label: String::from("Customer ID"),
                             value: String::from("0"),
+                            kind: ContextKind::Text,
                         },
                         Context {
                             label: String::from("Payment Link"),
                             value: String::from("7ea9ab4001d87d81207be05"),
+                            kind: ContextKind::Text,
                         }
                     ],
+                    severity: Severity::Error,
+                    idempotency_key: None,
+                    fingerprint: None,
+                    sections: Vec::new(),
+                    attachments: Vec::new(),
+                    labels: None,
+                    channel: None,
+                    id: None,
                 },
             }
         ]
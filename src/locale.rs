@@ -0,0 +1,269 @@
+use crate::{Labels, Notification};
+use std::collections::HashMap;
+
+/// Month names used by `%B` in timestamp patterns, unless a `Locale` has its own
+const ENGLISH_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Weekday names used by `%A` in timestamp patterns, Monday first,
+/// unless a `Locale` has its own
+const ENGLISH_WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// A built-in translation table: labels, then month and weekday names
+type Table = (
+    &'static [(&'static str, &'static str)],
+    [&'static str; 12],
+    [&'static str; 7],
+);
+
+const GERMAN: Table = (
+    &[
+        ("Issue", "Problem"),
+        ("Timestamp", "Zeitpunkt"),
+        ("First Seen", "Zuerst gesehen"),
+        ("Last Seen", "Zuletzt gesehen"),
+        ("Count", "Anzahl"),
+        ("Suppressed", "Unterdrückt"),
+        ("Caused By", "Verursacht durch"),
+        ("Maintenance", "Wartung"),
+    ],
+    [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    [
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+        "Sonntag",
+    ],
+);
+
+const SPANISH: Table = (
+    &[
+        ("Issue", "Problema"),
+        ("Timestamp", "Fecha"),
+        ("First Seen", "Visto por primera vez"),
+        ("Last Seen", "Visto por última vez"),
+        ("Count", "Recuento"),
+        ("Suppressed", "Suprimidas"),
+        ("Caused By", "Causado por"),
+        ("Maintenance", "Mantenimiento"),
+    ],
+    [
+        "enero",
+        "febrero",
+        "marzo",
+        "abril",
+        "mayo",
+        "junio",
+        "julio",
+        "agosto",
+        "septiembre",
+        "octubre",
+        "noviembre",
+        "diciembre",
+    ],
+    [
+        "lunes",
+        "martes",
+        "miércoles",
+        "jueves",
+        "viernes",
+        "sábado",
+        "domingo",
+    ],
+);
+
+const FRENCH: Table = (
+    &[
+        ("Issue", "Problème"),
+        ("Timestamp", "Horodatage"),
+        ("First Seen", "Vu pour la première fois"),
+        ("Last Seen", "Vu pour la dernière fois"),
+        ("Count", "Nombre"),
+        ("Suppressed", "Supprimées"),
+        ("Caused By", "Causé par"),
+    ],
+    [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    [
+        "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+    ],
+);
+
+/// Renders built-in labels (e.g. "Issue", "Timestamp", "Count") and the month
+/// and weekday names of timestamps (`%B` and `%A`) in another language, for
+/// non-English ops teams
+///
+/// Any context label found in the translation table is translated too, so
+/// labels used across services can be registered alongside the built-in ones.
+#[derive(Clone, Debug, Default)]
+pub struct Locale {
+    translations: HashMap<String, String>,
+    months: Option<Vec<String>>,
+    weekdays: Option<Vec<String>>,
+}
+impl Locale {
+    /// Create an English `Locale` with an empty translation table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A built-in `Locale` by language code: `en`, `de`, `es` or `fr`
+    pub fn builtin(language: &str) -> Option<Self> {
+        let (labels, months, weekdays) = match language.to_ascii_lowercase().as_str() {
+            "en" => return Some(Self::new()),
+            "de" => GERMAN,
+            "es" => SPANISH,
+            "fr" => FRENCH,
+            _ => return None,
+        };
+
+        let mut locale = Self::new().month_names(months).weekday_names(weekdays);
+        for (english, translated) in labels {
+            locale = locale.translate(*english, *translated);
+        }
+        Some(locale)
+    }
+
+    /// Register the translation of a label, e.g. `("Issue", "Problem")`
+    pub fn translate(mut self, label: impl Into<String>, translated: impl Into<String>) -> Self {
+        self.translations.insert(label.into(), translated.into());
+        self
+    }
+
+    /// The names of the months, January first, rendered by `%B`
+    pub fn month_names(mut self, names: [&str; 12]) -> Self {
+        self.months = Some(names.map(String::from).to_vec());
+        self
+    }
+
+    /// The names of the weekdays, Monday first, rendered by `%A`
+    pub fn weekday_names(mut self, names: [&str; 7]) -> Self {
+        self.weekdays = Some(names.map(String::from).to_vec());
+        self
+    }
+
+    /// The name of a month, from 1 (January) to 12
+    pub(crate) fn month(&self, month: usize) -> &str {
+        match &self.months {
+            Some(months) => &months[month - 1],
+            None => ENGLISH_MONTHS[month - 1],
+        }
+    }
+
+    /// The name of a weekday, from 0 (Monday) to 6
+    pub(crate) fn weekday(&self, weekday: usize) -> &str {
+        match &self.weekdays {
+            Some(weekdays) => &weekdays[weekday],
+            None => ENGLISH_WEEKDAYS[weekday],
+        }
+    }
+
+    /// Translate the built-in labels and context labels of a `Notification`
+    pub(crate) fn localize(&self, mut notification: Notification) -> Notification {
+        let labels = notification.labels.take().unwrap_or_default();
+        notification.labels = Some(Labels::new(
+            self.label(labels.issue),
+            self.label(labels.timestamp),
+        ));
+        let contexts = notification.context.iter_mut().chain(
+            notification
+                .sections
+                .iter_mut()
+                .flat_map(|s| &mut s.context),
+        );
+        for ctx in contexts {
+            ctx.label = self.label(std::mem::take(&mut ctx.label));
+        }
+
+        notification
+    }
+
+    /// Translate a label, if it's in the translation table
+    fn label(&self, label: String) -> String {
+        self.translations.get(&label).cloned().unwrap_or(label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Locale;
+    use crate::{Labels, Notification};
+
+    /// A test to make sure built-in and registered labels are translated
+    #[test]
+    fn translates_labels() {
+        let locale = Locale::builtin("de")
+            .unwrap()
+            .translate("Order ID", "Bestellnummer");
+        let notification = Notification::new("Zahlung fehlgeschlagen")
+            .with_context("Order ID", 1042)
+            .with_context("Count", 3)
+            .with_context("Region", "eu-central-1");
+
+        let localized = locale.localize(notification);
+        assert_eq!(localized.labels, Some(Labels::new("Problem", "Zeitpunkt")));
+        let labels: Vec<_> = localized.context.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, ["Bestellnummer", "Anzahl", "Region"]);
+        assert!(Locale::builtin("xx").is_none());
+    }
+
+    /// A test to make sure month and weekday names fall back to English
+    #[test]
+    fn names_months_and_weekdays() {
+        let english = Locale::new();
+        assert_eq!(
+            (english.month(1), english.weekday(6)),
+            ("January", "Sunday")
+        );
+        let french = Locale::builtin("fr").unwrap();
+        assert_eq!((french.month(8), french.weekday(0)), ("août", "lundi"));
+    }
+}
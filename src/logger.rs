@@ -0,0 +1,141 @@
+use crate::{Context, ContextKind, Notification, Severity, Worker};
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// A `log` backend turning records at or above a level (`Error` by
+/// default) into notifications, delivered by a `Worker`
+///
+/// Since `log` only allows a single logger, records can be passed on to
+/// another logger too (e.g. one writing to the console) with `with_inner`.
+pub struct DevNotifyLogger {
+    worker: Worker,
+    level: Level,
+    inner: Option<Box<dyn Log>>,
+}
+impl DevNotifyLogger {
+    /// Create a `DevNotifyLogger` queueing notifications on a given `Worker`
+    pub fn new(worker: Worker) -> Self {
+        Self {
+            worker,
+            level: Level::Error,
+            inner: None,
+        }
+    }
+
+    /// Notify on records at or above a given level instead of only `Error`
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Pass every record on to another logger as well
+    pub fn with_inner(mut self, inner: impl Log + 'static) -> Self {
+        self.inner = Some(Box::new(inner));
+        self
+    }
+
+    /// Install as the global logger
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        // The inner logger filters its own records through `enabled`
+        let max_level = match self.inner {
+            Some(_) => LevelFilter::Trace,
+            None => self.level.to_level_filter(),
+        };
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max_level);
+
+        Ok(())
+    }
+
+    /// Whether a record at a given level and target should become a notification,
+    /// ignoring this crate so a failing delivery can't notify about itself
+    fn notifies(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.level && !metadata.target().starts_with("dev_notify")
+    }
+}
+impl Log for DevNotifyLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.notifies(metadata)
+            || self
+                .inner
+                .as_ref()
+                .is_some_and(|inner| inner.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if let Some(inner) = &self.inner {
+            inner.log(record);
+        }
+        if !self.notifies(record.metadata()) {
+            return;
+        }
+
+        let mut context = vec![Context {
+            label: String::from("Target"),
+            value: record.target().to_string(),
+            kind: ContextKind::Text,
+        }];
+        if let (Some(file), Some(line)) = (record.file(), record.line()) {
+            context.push(Context {
+                label: String::from("Location"),
+                value: format!("{file}:{line}"),
+                kind: ContextKind::Text,
+            });
+        }
+
+        let mut notification = Notification::new(record.args().to_string());
+        notification.context.extend(context);
+        notification.severity = severity(record.level());
+        self.worker.notify(notification);
+    }
+
+    fn flush(&self) {
+        if let Some(inner) = &self.inner {
+            inner.flush();
+        }
+    }
+}
+
+/// Map a `log` level onto a `Severity`
+fn severity(level: Level) -> Severity {
+    match level {
+        Level::Error => Severity::Error,
+        Level::Warn => Severity::Warning,
+        Level::Info => Severity::Info,
+        Level::Debug | Level::Trace => Severity::Debug,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockDestination;
+    use crate::{DevNotifyLogger, Notifier, Severity, Worker};
+    use log::{Level, Log, Record};
+    use std::sync::Arc;
+
+    /// A test to make sure error records become notifications
+    #[tokio::test]
+    async fn notifies_on_error_records() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+        let logger = DevNotifyLogger::new(worker).with_level(Level::Warn);
+
+        for level in [Level::Info, Level::Warn] {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("Failed to capture transaction"))
+                    .level(level)
+                    .target("payments")
+                    .build(),
+            );
+        }
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        let delivered = mock.delivered();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(delivered[0].message, "Failed to capture transaction");
+        assert_eq!(delivered[0].severity, Severity::Warning);
+        assert_eq!(delivered[0].context[0].value, "payments");
+    }
+}
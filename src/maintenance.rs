@@ -0,0 +1,173 @@
+use crate::time::{SystemTime, UNIX_EPOCH};
+use crate::{Context, ContextKind, Error, Notification, Route, Severity};
+use serde::Deserialize;
+
+/// What happens to notifications matching a `MaintenanceWindow` while it's open
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MaintenanceAction {
+    /// Drop them, nothing is sent
+    Suppress,
+    /// Send them with a "Maintenance" context entry, so readers know
+    /// they're expected
+    Tag,
+}
+
+/// A planned period (e.g. a deploy) during which matching notifications
+/// are suppressed or tagged, so it doesn't page anyone
+///
+/// Matches every notification until conditions are added, which work
+/// like those of a `Route`.
+#[derive(Clone, Debug)]
+pub struct MaintenanceWindow {
+    start: u64,
+    end: u64,
+    conditions: Route,
+    action: MaintenanceAction,
+    reason: Option<String>,
+}
+impl MaintenanceWindow {
+    /// Create a `MaintenanceWindow` suppressing notifications from `start`
+    /// to `end`, both in UTC like `2024-01-19 22:00:00`
+    ///
+    /// Returns `Error::Config` if either time is invalid or it ends before it starts.
+    pub fn new(start: &str, end: &str) -> Result<Self, Error> {
+        let (start, end) = (unix_secs(start)?, unix_secs(end)?);
+        if end <= start {
+            return Err(Error::Config(String::from(
+                "maintenance window must end after it starts",
+            )));
+        }
+
+        Ok(Self {
+            start,
+            end,
+            conditions: Route::new(Vec::<String>::new()),
+            action: MaintenanceAction::Suppress,
+            reason: None,
+        })
+    }
+
+    /// What to do with matching notifications (defaults to `MaintenanceAction::Suppress`)
+    pub fn action(mut self, action: MaintenanceAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Why there's maintenance (e.g. "Database upgrade"), shown on tagged notifications
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Only match notifications at or above a given severity
+    pub fn min_severity(mut self, severity: Severity) -> Self {
+        self.conditions = self.conditions.min_severity(severity);
+        self
+    }
+
+    /// Only match notifications whose message contains the given text
+    pub fn message_contains(mut self, text: impl Into<String>) -> Self {
+        self.conditions = self.conditions.message_contains(text);
+        self
+    }
+
+    /// Only match notifications whose whole message matches a glob pattern,
+    /// where `*` matches any text and `?` any single character
+    pub fn message_matches(mut self, pattern: impl Into<String>) -> Self {
+        self.conditions = self.conditions.message_matches(pattern);
+        self
+    }
+
+    /// Only match notifications with a context entry labeled `label` (ignoring case)
+    pub fn has_context(mut self, label: impl Into<String>) -> Self {
+        self.conditions = self.conditions.has_context(label);
+        self
+    }
+
+    /// Only match notifications with a context entry labeled `label`
+    /// (ignoring case) holding exactly `value`, e.g. `("Service", "billing")`
+    pub fn context_equals(mut self, label: impl Into<String>, value: impl Into<String>) -> Self {
+        self.conditions = self.conditions.context_equals(label, value);
+        self
+    }
+
+    /// Suppress or tag a `Notification` if the window is open and it matches
+    pub(crate) fn apply(&self, notification: Notification) -> Option<Notification> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.apply_at(notification, now)
+    }
+
+    /// Suppress or tag a `Notification` at a given time (seconds since the epoch)
+    fn apply_at(&self, mut notification: Notification, now: u64) -> Option<Notification> {
+        if !(self.start..self.end).contains(&now) || !self.conditions.matches(&notification) {
+            return Some(notification);
+        }
+
+        match self.action {
+            MaintenanceAction::Suppress => None,
+            MaintenanceAction::Tag => {
+                notification.context.push(Context {
+                    label: String::from("Maintenance"),
+                    value: self
+                        .reason
+                        .clone()
+                        .unwrap_or_else(|| String::from("Planned maintenance in progress")),
+                    kind: ContextKind::Text,
+                });
+                Some(notification)
+            }
+        }
+    }
+}
+
+/// Parse a UTC time like `2024-01-19 22:00:00` into seconds since the epoch
+fn unix_secs(time: &str) -> Result<u64, Error> {
+    crate::timestamp::parse(&format!("{time}.000000"))
+        .map(|(secs, _)| secs)
+        .ok_or_else(|| {
+            Error::Config(format!(
+                "invalid maintenance time `{time}`, expected like `2024-01-19 22:00:00`"
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MaintenanceAction, MaintenanceWindow};
+    use crate::Notification;
+
+    /// A test to make sure only matching notifications inside the window are affected
+    #[test]
+    fn applies_inside_window() {
+        // 2024-01-19 22:30:00 UTC
+        let during = 1_705_703_400;
+        let window = MaintenanceWindow::new("2024-01-19 22:00:00", "2024-01-19 23:00:00")
+            .unwrap()
+            .context_equals("Service", "billing");
+        let billing = Notification::new("Connection refused").with_context("Service", "billing");
+        let search = Notification::new("Connection refused").with_context("Service", "search");
+
+        assert!(window.apply_at(billing.clone(), during).is_none());
+        assert!(window.apply_at(search, during).is_some());
+        assert!(window.apply_at(billing.clone(), during + 3600).is_some());
+
+        let tagged = window
+            .action(MaintenanceAction::Tag)
+            .reason("Database upgrade")
+            .apply_at(billing, during)
+            .unwrap();
+        assert_eq!(tagged.context[1].label, "Maintenance");
+        assert_eq!(tagged.context[1].value, "Database upgrade");
+    }
+
+    /// A test to make sure invalid windows are rejected
+    #[test]
+    fn rejects_invalid_windows() {
+        assert!(MaintenanceWindow::new("2024-01-19 23:00:00", "2024-01-19 22:00:00").is_err());
+        assert!(MaintenanceWindow::new("2024-01-19 22:00", "2024-01-19 23:00:00").is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::render::variable_name;
+use crate::{
+    ContextKind, Endpoint, Error, Formatter, MessageFormat, Notification, RenderedPayload,
+};
+use serde_json::{json, Map, Value};
+
+/// The Honeycomb API, in the US region
+const HONEYCOMB_API: &str = "https://api.honeycomb.io/1/markers";
+
+/// The New Relic Event API, in the US region
+const NEW_RELIC_API: &str = "https://insights-collector.newrelic.com/v1/accounts";
+
+/// The type of the custom events recorded in New Relic
+const NEW_RELIC_EVENT_TYPE: &str = "DevNotifyNotification";
+
+impl Endpoint {
+    /// Create an `Endpoint` recording notifications as markers on a
+    /// Honeycomb dataset (or `__all__` for the environment), authenticated
+    /// with a configuration API key
+    pub fn honeycomb(api_key: &str, dataset: &str) -> Self {
+        Self::new(format!("{HONEYCOMB_API}/{dataset}"))
+            .header("X-Honeycomb-Team", api_key)
+            .format(MessageFormat::Honeycomb)
+    }
+
+    /// Create an `Endpoint` recording notifications as custom events of a
+    /// New Relic account, authenticated with a license key
+    pub fn new_relic(account_id: &str, license_key: &str) -> Self {
+        Self::new(format!("{NEW_RELIC_API}/{account_id}/events"))
+            .header("Api-Key", license_key)
+            .format(MessageFormat::NewRelic)
+    }
+}
+
+/// Renders notifications as Honeycomb markers, as `MessageFormat::Honeycomb`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct HoneycombFormatter;
+impl Formatter for HoneycombFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let payload = notification.clone().into_honeycomb_payload();
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+/// Renders notifications as New Relic events, as `MessageFormat::NewRelic`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct NewRelicFormatter;
+impl Formatter for NewRelicFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let payload = notification.clone().into_new_relic_payload();
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into a Honeycomb marker,
+    /// typed by severity and linking to the first link in the context
+    pub(crate) fn into_honeycomb_payload(self) -> Value {
+        let mut payload = json!({
+            "message": format!("[{:?}] {}", self.severity, self.message),
+            "type": self.severity,
+        });
+        let link = self
+            .context
+            .iter()
+            .find(|ctx| matches!(ctx.kind, ContextKind::Link { .. }));
+        if let Some(link) = link {
+            payload["url"] = Value::String(link.value.clone());
+        }
+
+        payload
+    }
+
+    /// Consume the `Notification` and parse it into a New Relic Event API
+    /// request of a single `DevNotifyNotification` event
+    ///
+    /// The attributes are `message`, `severity`, `id` and `fingerprint`, then
+    /// one per context entry named after its label in snake case, so they can
+    /// be queried with NRQL. Labels repeating an attribute are skipped.
+    pub(crate) fn into_new_relic_payload(self) -> Value {
+        let mut attributes = Map::new();
+        attributes.insert(
+            String::from("eventType"),
+            Value::String(String::from(NEW_RELIC_EVENT_TYPE)),
+        );
+        attributes.insert(String::from("message"), Value::String(self.message));
+        attributes.insert(String::from("severity"), json!(self.severity));
+        if let Some(id) = self.id {
+            attributes.insert(String::from("id"), Value::String(id));
+        }
+        if let Some(fingerprint) = self.fingerprint {
+            attributes.insert(String::from("fingerprint"), Value::String(fingerprint));
+        }
+
+        let contexts = self
+            .context
+            .into_iter()
+            .chain(self.sections.into_iter().flat_map(|s| s.context));
+        for ctx in contexts {
+            let name = variable_name(&ctx.label);
+            if !name.is_empty() {
+                attributes.entry(name).or_insert(Value::String(ctx.value));
+            }
+        }
+
+        json!([attributes])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Endpoint, Notification, Section, Severity};
+    use serde_json::Value;
+
+    /// A test to make sure notifications are recorded as Honeycomb markers
+    #[test]
+    fn can_parse_into_honeycomb_marker() {
+        let mut notification = Notification::new("Deployed api v1.4.2").with_channel("ops");
+        notification.severity = Severity::Info;
+        notification.context.push(Context::link(
+            "Release",
+            "https://github.com/acme/api/releases/v1.4.2",
+            "v1.4.2",
+        ));
+
+        let destination = Endpoint::honeycomb("key", "api");
+        assert_eq!(destination.url(), "https://api.honeycomb.io/1/markers/api");
+        let payload: Value =
+            serde_json::from_str(&notification.render_for(&destination).unwrap().body).unwrap();
+        assert_eq!(payload["message"], "[Info] Deployed api v1.4.2");
+        assert_eq!(payload["type"], "info");
+        assert_eq!(
+            payload["url"],
+            "https://github.com/acme/api/releases/v1.4.2"
+        );
+    }
+
+    /// A test to make sure notifications are recorded as New Relic events with queryable attributes
+    #[test]
+    fn can_parse_into_new_relic_event() {
+        let mut notification = Notification::new("Payment failed")
+            .with_context("Order ID", 1042)
+            .with_context("Message", "ignored")
+            .with_section(Section::new("Request").with_context("Path", "/checkout"))
+            .with_channel("ops");
+        notification.fingerprint = Some(String::from("payments"));
+        let id = notification.id.clone().unwrap();
+
+        let destination = Endpoint::new_relic("1234567", "key");
+        assert_eq!(
+            destination.url(),
+            "https://insights-collector.newrelic.com/v1/accounts/1234567/events"
+        );
+        let payload: Value =
+            serde_json::from_str(&notification.render_for(&destination).unwrap().body).unwrap();
+        let event = &payload[0];
+        assert_eq!(event["eventType"], "DevNotifyNotification");
+        assert_eq!(event["message"], "Payment failed");
+        assert_eq!(event["severity"], "error");
+        assert_eq!(event["id"], id.as_str());
+        assert_eq!(event["fingerprint"], "payments");
+        assert_eq!(event["order_id"], "1042");
+        assert_eq!(event["path"], "/checkout");
+        assert!(event.get("channel").is_none());
+    }
+}
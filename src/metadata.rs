@@ -0,0 +1,127 @@
+use crate::{Context, ContextKind, Notification};
+
+/// Describes the host and process sending notifications (hostname, pid,
+/// service name, version and git SHA), appended to the context of every
+/// notification by `NotifierBuilder::host_metadata`
+///
+/// Use the `host_metadata!` macro to take the service name and version
+/// from the calling crate, and the git SHA from a `GIT_SHA` variable set
+/// at build time (e.g. `cargo:rustc-env=GIT_SHA=...` in a build script).
+#[derive(Clone, Debug)]
+pub struct HostMetadata {
+    context: Vec<Context>,
+}
+impl HostMetadata {
+    /// Describe the current host and process, running a given service
+    ///
+    /// The hostname is read from the `HOSTNAME` or `COMPUTERNAME` variables,
+    /// or `/proc/sys/kernel/hostname` and `/etc/hostname`, and left out if
+    /// none of them are set.
+    pub fn new(service: impl Into<String>) -> Self {
+        let mut metadata = Self {
+            context: Vec::new(),
+        };
+        if let Some(hostname) = hostname() {
+            metadata = metadata.with("Host", hostname);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            metadata = metadata.with("PID", std::process::id().to_string());
+        }
+
+        metadata.with("Service", service.into())
+    }
+
+    /// The version of the service
+    pub fn version(self, version: impl Into<String>) -> Self {
+        self.with("Version", version.into())
+    }
+
+    /// The git SHA the service was built from
+    pub fn git_sha(self, sha: impl Into<String>) -> Self {
+        self.with("Git SHA", sha.into())
+    }
+
+    /// Append the metadata to the context of a `Notification`
+    pub(crate) fn enrich(&self, mut notification: Notification) -> Notification {
+        notification.context.extend(self.context.iter().cloned());
+        notification
+    }
+
+    /// Add a labelled value to the metadata
+    fn with(mut self, label: &str, value: String) -> Self {
+        self.context.push(Context {
+            label: label.to_string(),
+            value,
+            kind: ContextKind::Text,
+        });
+        self
+    }
+}
+
+/// Create a `HostMetadata` for the calling crate, with its package name as
+/// the service name, its version, and the `GIT_SHA` build-time variable (if set)
+#[macro_export]
+macro_rules! host_metadata {
+    () => {{
+        let metadata =
+            $crate::HostMetadata::new(env!("CARGO_PKG_NAME")).version(env!("CARGO_PKG_VERSION"));
+        match option_env!("GIT_SHA") {
+            Some(sha) => metadata.git_sha(sha),
+            None => metadata,
+        }
+    }};
+}
+
+/// The name of the current host, if it can be found
+fn hostname() -> Option<String> {
+    let from_env = ["HOSTNAME", "COMPUTERNAME"]
+        .into_iter()
+        .find_map(|name| std::env::var(name).ok());
+    let from_file = || {
+        ["/proc/sys/kernel/hostname", "/etc/hostname"]
+            .into_iter()
+            .find_map(|path| std::fs::read_to_string(path).ok())
+    };
+
+    from_env
+        .or_else(from_file)
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Notification;
+
+    /// A test to make sure metadata is appended after the existing context
+    #[test]
+    fn appends_metadata() {
+        let metadata = host_metadata!().git_sha("4f2a9c1");
+        let notification =
+            metadata.enrich(Notification::new("Job failed").with_context("Job", "nightly"));
+
+        let labels: Vec<_> = notification
+            .context
+            .iter()
+            .map(|c| c.label.as_str())
+            .collect();
+        assert_eq!(labels[0], "Job");
+        assert!(labels.contains(&"PID"));
+        assert_eq!(
+            labels[labels.len() - 3..],
+            ["Service", "Version", "Git SHA"]
+        );
+
+        let value = |label| {
+            notification
+                .context
+                .iter()
+                .find(|c| c.label == label)
+                .map(|c| c.value.as_str())
+        };
+        assert_eq!(value("Service"), Some("dev_notify"));
+        assert_eq!(value("Version"), Some(env!("CARGO_PKG_VERSION")));
+        assert_eq!(value("PID"), Some(std::process::id().to_string().as_str()));
+    }
+}
@@ -0,0 +1,132 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds (in milliseconds) of the delivery latency histogram buckets
+const LATENCY_BUCKETS_MS: [u64; 8] = [50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000];
+
+/// A snapshot of delivery metrics for a `Notifier`, see `Notifier::metrics`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeliveryMetrics {
+    /// Notifications delivered successfully
+    pub sent: u64,
+    /// Notifications that failed to deliver (including open circuits)
+    pub failed: u64,
+    /// Notifications resent from the spool with `Notifier::replay`
+    pub retried: u64,
+    /// Notifications dropped by hooks, severity thresholds or sampling
+    pub skipped: u64,
+    /// Notifications held for quiet hours, to be sent as a digest once they're over
+    pub suppressed: u64,
+    /// Notifications dropped over the rate limit of their destination
+    pub dropped: u64,
+    /// Delivery attempts per latency bucket, as (upper bound, count) pairs
+    /// with a final `Duration::MAX` bucket for anything slower
+    pub latency: Vec<(Duration, u64)>,
+    /// Total time spent on delivery attempts, for averaging
+    pub latency_total: Duration,
+}
+
+/// Delivery counters, updated as a `Notifier` sends
+#[derive(Default)]
+pub(crate) struct Metrics {
+    sent: AtomicU64,
+    failed: AtomicU64,
+    retried: AtomicU64,
+    skipped: AtomicU64,
+    suppressed: AtomicU64,
+    dropped: AtomicU64,
+    latency: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+    latency_total_us: AtomicU64,
+}
+impl Metrics {
+    /// Record a delivery attempt and how long it took
+    pub(crate) fn record_delivery(&self, succeeded: bool, latency: Duration) {
+        if succeeded {
+            self.sent.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let latency_ms = u64::try_from(latency.as_millis()).unwrap_or(u64::MAX);
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| latency_ms <= bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.latency[bucket].fetch_add(1, Ordering::Relaxed);
+        self.latency_total_us.fetch_add(
+            u64::try_from(latency.as_micros()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Record a notification resent from the spool
+    pub(crate) fn record_retry(&self) {
+        self.retried.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a notification that was dropped before delivery
+    pub(crate) fn record_skip(&self) {
+        self.skipped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a notification held for quiet hours
+    pub(crate) fn record_suppress(&self) {
+        self.suppressed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a notification dropped over a rate limit
+    pub(crate) fn record_drop(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Take a snapshot of the current metrics
+    pub(crate) fn snapshot(&self) -> DeliveryMetrics {
+        let bounds = LATENCY_BUCKETS_MS
+            .iter()
+            .map(|&bound| Duration::from_millis(bound))
+            .chain([Duration::MAX]);
+
+        DeliveryMetrics {
+            sent: self.sent.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            retried: self.retried.load(Ordering::Relaxed),
+            skipped: self.skipped.load(Ordering::Relaxed),
+            suppressed: self.suppressed.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            latency: bounds
+                .zip(&self.latency)
+                .map(|(bound, count)| (bound, count.load(Ordering::Relaxed)))
+                .collect(),
+            latency_total: Duration::from_micros(self.latency_total_us.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Metrics;
+    use std::time::Duration;
+
+    /// A test to make sure deliveries land in the right latency buckets
+    #[test]
+    fn records_latency_buckets() {
+        let metrics = Metrics::default();
+        metrics.record_delivery(true, Duration::from_millis(20));
+        metrics.record_delivery(false, Duration::from_millis(300));
+        metrics.record_delivery(true, Duration::from_secs(60));
+        metrics.record_skip();
+        metrics.record_suppress();
+        metrics.record_drop();
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.sent, 2);
+        assert_eq!(snapshot.failed, 1);
+        assert_eq!(snapshot.skipped, 1);
+        assert_eq!(snapshot.suppressed, 1);
+        assert_eq!(snapshot.dropped, 1);
+        assert_eq!(snapshot.latency[0], (Duration::from_millis(50), 1));
+        assert_eq!(snapshot.latency[3], (Duration::from_millis(500), 1));
+        assert_eq!(snapshot.latency[8], (Duration::MAX, 1));
+        assert_eq!(snapshot.latency_total, Duration::from_millis(60_320));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::time::Instant;
+use crate::{Notification, Severity, Worker};
+use futures_util::FutureExt;
+use http::{Method, Request, Response, StatusCode};
+use std::any::Any;
+use std::fmt::Display;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A `tower` layer queueing a `Notification` on a `Worker` when the wrapped
+/// service responds with a 5xx status, fails or panics, for any tower-based
+/// stack (e.g. hyper, tonic or axum services)
+///
+/// The notification carries the method, path, status, latency and the
+/// `x-request-id` header (if any) as context. Panics are notified as
+/// `Severity::Critical`, then resumed so the server handles them as before.
+#[derive(Clone)]
+pub struct NotifyLayer {
+    worker: Worker,
+}
+impl NotifyLayer {
+    /// Create a `NotifyLayer` queueing notifications on a given `Worker`
+    pub fn new(worker: Worker) -> Self {
+        Self { worker }
+    }
+}
+impl<S> Layer<S> for NotifyLayer {
+    type Service = NotifyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        NotifyService {
+            inner,
+            worker: self.worker.clone(),
+        }
+    }
+}
+
+/// A service wrapped by a `NotifyLayer`
+#[derive(Clone)]
+pub struct NotifyService<S> {
+    inner: S,
+    worker: Worker,
+}
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for NotifyService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: Display,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let described = RequestInfo::new(&request);
+        let worker = self.worker.clone();
+        let response = self.inner.call(request);
+        Box::pin(async move { observe(&worker, described, response).await })
+    }
+}
+
+/// `axum` middleware queueing a `Notification` on a `Worker` when a handler
+/// responds with a 5xx status or panics, e.g.
+/// `router.layer(axum::middleware::from_fn_with_state(worker, notify_on_server_error))`
+///
+/// This notifies like a `NotifyLayer`, for routers already built around
+/// `axum::middleware`.
+#[cfg(feature = "axum")]
+pub async fn notify_on_server_error(
+    axum::extract::State(worker): axum::extract::State<Worker>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let described = RequestInfo::new(&request);
+    let response = async { Ok::<_, std::convert::Infallible>(next.run(request).await) };
+    match observe(&worker, described, response).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    }
+}
+
+/// Wait for the response to a request, notifying if it's a server error,
+/// fails or panics
+async fn observe<F, ResBody, E>(
+    worker: &Worker,
+    request: RequestInfo,
+    response: F,
+) -> Result<Response<ResBody>, E>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+    E: Display,
+{
+    let started = Instant::now();
+    let result = AssertUnwindSafe(response).catch_unwind().await;
+    let latency = started.elapsed();
+
+    match result {
+        Ok(Ok(response)) => {
+            let status = response.status();
+            if status.is_server_error() {
+                let message = format!("{} {} responded {status}", request.method, request.path);
+                worker.notify(request.describe(message, Some(status), latency));
+            }
+            Ok(response)
+        }
+        Ok(Err(e)) => {
+            let message = format!("{} {} failed: {e}", request.method, request.path);
+            worker.notify(request.describe(message, None, latency));
+            Err(e)
+        }
+        Err(payload) => {
+            let message = format!(
+                "{} {} panicked: {}",
+                request.method,
+                request.path,
+                panic_message(payload.as_ref())
+            );
+            let mut notification = request.describe(message, None, latency);
+            notification.severity = Severity::Critical;
+            worker.notify(notification);
+            std::panic::resume_unwind(payload)
+        }
+    }
+}
+
+/// The message of a panic payload, if it has one
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("Box<dyn Any>"))
+}
+
+/// The parts of a request to describe in a `Notification`
+struct RequestInfo {
+    method: Method,
+    path: String,
+    request_id: Option<String>,
+}
+impl RequestInfo {
+    /// Take the parts of a request to describe
+    fn new<B>(request: &Request<B>) -> Self {
+        Self {
+            method: request.method().clone(),
+            path: request.uri().path().to_string(),
+            request_id: request
+                .headers()
+                .get("x-request-id")
+                .and_then(|id| id.to_str().ok())
+                .map(String::from),
+        }
+    }
+
+    /// A `Notification` with a given message and the request as context
+    fn describe(
+        self,
+        message: String,
+        status: Option<StatusCode>,
+        latency: Duration,
+    ) -> Notification {
+        let mut notification = Notification::new(message)
+            .with_context("Method", &self.method)
+            .with_context("Path", &self.path);
+        if let Some(status) = status {
+            notification = notification.with_context("Status", status.as_u16());
+        }
+        notification = notification.with_context("Latency", format!("{}ms", latency.as_millis()));
+        if let Some(request_id) = self.request_id {
+            notification = notification.with_context("Request ID", request_id);
+        }
+
+        notification
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NotifyLayer;
+    use crate::testing::MockDestination;
+    use crate::{Notifier, Severity, Worker};
+    use http::{Request, Response, StatusCode};
+    use std::convert::Infallible;
+    use std::sync::Arc;
+    use tower::{service_fn, Layer, ServiceExt};
+
+    /// A test to make sure 5xx responses are notified with the request as context
+    #[tokio::test]
+    async fn notifies_on_server_error() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+        let service =
+            NotifyLayer::new(worker).layer(service_fn(|request: Request<()>| async move {
+                let status = match request.uri().path() {
+                    "/broken" => StatusCode::BAD_GATEWAY,
+                    _ => StatusCode::OK,
+                };
+                Ok::<_, Infallible>(Response::builder().status(status).body(()).unwrap())
+            }));
+
+        let request = Request::get("/ok").body(()).unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::get("/broken")
+            .header("x-request-id", "req-42")
+            .body(())
+            .unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        mock.assert_delivered("GET /broken responded 502 Bad Gateway");
+        let delivered = mock.delivered();
+        assert_eq!(mock.len(), 1);
+        assert_eq!(delivered[0].severity, Severity::Error);
+        let labels: Vec<_> = delivered[0]
+            .context
+            .iter()
+            .map(|c| c.label.as_str())
+            .collect();
+        assert_eq!(
+            labels,
+            ["Method", "Path", "Status", "Latency", "Request ID"]
+        );
+    }
+
+    /// A test to make sure service errors are notified and passed on
+    #[tokio::test]
+    async fn notifies_on_service_error() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+        let service = NotifyLayer::new(worker).layer(service_fn(|_: Request<()>| async {
+            Err::<Response<()>, _>("connection refused")
+        }));
+
+        let request = Request::post("/checkout").body(()).unwrap();
+        assert!(service.oneshot(request).await.is_err());
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        mock.assert_delivered("POST /checkout failed: connection refused");
+    }
+
+    /// A test to make sure panics are notified as critical, then resumed
+    #[tokio::test]
+    async fn notifies_on_panic() {
+        use futures_util::FutureExt;
+        use std::panic::AssertUnwindSafe;
+
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+        let service = NotifyLayer::new(worker).layer(service_fn(|_: Request<()>| async {
+            if true {
+                panic!("Failed to capture transaction");
+            }
+            Ok::<_, Infallible>(Response::new(()))
+        }));
+
+        let request = Request::post("/checkout").body(()).unwrap();
+        let result = AssertUnwindSafe(service.oneshot(request))
+            .catch_unwind()
+            .await;
+        assert!(result.is_err());
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        mock.assert_delivered("POST /checkout panicked: Failed to capture transaction");
+        assert_eq!(mock.delivered()[0].severity, Severity::Critical);
+    }
+
+    /// A test to make sure the axum middleware notifies on 5xx responses
+    #[cfg(feature = "axum")]
+    #[tokio::test]
+    async fn axum_notifies_on_server_error() {
+        use axum::body::Body;
+        use axum::routing::get;
+        use axum::Router;
+
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+        let router = Router::new()
+            .route(
+                "/broken",
+                get(|| async { (StatusCode::BAD_GATEWAY, "upstream down") }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                worker,
+                super::notify_on_server_error,
+            ));
+
+        let request = Request::get("/broken").body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        mock.assert_delivered("GET /broken responded 502 Bad Gateway");
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{Endpoint, Notification, Notifier, Severity};
+use reqwest::{Client, StatusCode};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::{JoinHandle, JoinSet};
+
+/// The longest a single check waits for a response before counting as down
+const MAX_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A URL checked on an interval
+#[derive(Clone)]
+struct Target {
+    url: String,
+    interval: Duration,
+    expected_status: StatusCode,
+}
+
+/// The outcome of a single check
+struct Check {
+    latency: Duration,
+    /// The status responded with, or why the request failed
+    outcome: Result<StatusCode, String>,
+}
+
+/// The state of a URL between checks
+#[derive(Default)]
+struct State {
+    down_since: Option<Instant>,
+}
+
+/// A lightweight uptime monitor: each registered URL is requested on its
+/// interval, notifying when it goes down (doesn't respond with the expected
+/// status) and again when it comes back up
+pub struct Monitor {
+    http_client: Client,
+    targets: Vec<Target>,
+}
+impl Monitor {
+    /// Create a `Monitor` without any URLs
+    pub fn new() -> Self {
+        Self::with_client(Client::new())
+    }
+
+    /// Create a `Monitor` checking URLs through a given HTTP client
+    pub fn with_client(http_client: Client) -> Self {
+        Self {
+            http_client,
+            targets: Vec::new(),
+        }
+    }
+
+    /// Check a URL every `interval`, expecting a given status (e.g. 200)
+    ///
+    /// Invalid status codes are treated as 200.
+    pub fn register(
+        mut self,
+        url: impl Into<String>,
+        interval: Duration,
+        expected_status: u16,
+    ) -> Self {
+        self.targets.push(Target {
+            url: url.into(),
+            interval,
+            expected_status: StatusCode::from_u16(expected_status).unwrap_or(StatusCode::OK),
+        });
+        self
+    }
+
+    /// Spawn a task on the current tokio runtime checking every URL on its
+    /// interval, sending a notification through a given `Notifier` when one
+    /// goes down or comes back up
+    ///
+    /// Aborting the returned task stops checking every URL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn(self, notifier: Arc<Notifier>, destination: Endpoint) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut checks = JoinSet::new();
+            for target in self.targets {
+                let http_client = self.http_client.clone();
+                let notifier = Arc::clone(&notifier);
+                let destination = destination.clone();
+                checks.spawn(async move {
+                    let mut state = State::default();
+                    let mut ticks = tokio::time::interval(target.interval);
+                    loop {
+                        ticks.tick().await;
+                        let check = target.check(&http_client).await;
+                        if let Some(notification) = state.record(&target, check) {
+                            // Failures are already handled by the `Notifier`
+                            let _ = notifier.send(notification, &destination).await;
+                        }
+                    }
+                });
+            }
+            while checks.join_next().await.is_some() {}
+        })
+    }
+}
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Target {
+    /// Request the URL once
+    async fn check(&self, http_client: &Client) -> Check {
+        let started = Instant::now();
+        let outcome = http_client
+            .get(&self.url)
+            .timeout(self.interval.min(MAX_CHECK_TIMEOUT))
+            .send()
+            .await
+            .map(|response| response.status())
+            .map_err(|e| e.to_string());
+
+        Check {
+            latency: started.elapsed(),
+            outcome,
+        }
+    }
+}
+
+impl State {
+    /// Record a check of a target, describing the change if it went down or came back up
+    fn record(&mut self, target: &Target, check: Check) -> Option<Notification> {
+        let up = check
+            .outcome
+            .as_ref()
+            .is_ok_and(|status| *status == target.expected_status);
+        let latency = format!("{}ms", check.latency.as_millis());
+
+        match (self.down_since, up) {
+            (None, false) => {
+                self.down_since = Some(Instant::now());
+                let mut notification = Notification::new(format!("`{}` is down", target.url))
+                    .with_context("Expected Status", target.expected_status.as_u16());
+                notification = match check.outcome {
+                    Ok(status) => notification.with_context("Status", status.as_u16()),
+                    Err(e) => notification.with_context("Error", e),
+                };
+                Some(notification.with_context("Latency", latency))
+            }
+            (Some(down_since), true) => {
+                self.down_since = None;
+                let mut notification = Notification::new(format!("`{}` is back up", target.url))
+                    .with_context("Latency", latency)
+                    .with_context("Down For", format!("{}s", down_since.elapsed().as_secs()));
+                notification.severity = Severity::Info;
+                Some(notification)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Check, State, Target};
+    use crate::Severity;
+    use reqwest::StatusCode;
+    use std::time::Duration;
+
+    /// A target to check in tests
+    fn target() -> Target {
+        Target {
+            url: String::from("https://status.internal/health"),
+            interval: Duration::from_secs(60),
+            expected_status: StatusCode::OK,
+        }
+    }
+
+    /// A check with a given outcome
+    fn check(outcome: Result<StatusCode, String>) -> Check {
+        Check {
+            latency: Duration::from_millis(42),
+            outcome,
+        }
+    }
+
+    /// A test to make sure only changes between up and down are notified
+    #[test]
+    fn notifies_on_state_change() {
+        let target = target();
+        let mut state = State::default();
+        assert!(state.record(&target, check(Ok(StatusCode::OK))).is_none());
+
+        let down = state
+            .record(&target, check(Ok(StatusCode::SERVICE_UNAVAILABLE)))
+            .unwrap();
+        assert_eq!(down.message, "`https://status.internal/health` is down");
+        assert_eq!(down.context[1].value, "503");
+        assert_eq!(down.context[2].value, "42ms");
+        assert!(state
+            .record(&target, check(Err(String::from("connection refused"))))
+            .is_none());
+
+        let up = state.record(&target, check(Ok(StatusCode::OK))).unwrap();
+        assert_eq!(up.message, "`https://status.internal/health` is back up");
+        assert_eq!(up.severity, Severity::Info);
+        assert!(state.record(&target, check(Ok(StatusCode::OK))).is_none());
+    }
+
+    /// A test to make sure unreachable URLs are checked as down
+    #[tokio::test]
+    async fn checks_unreachable_as_down() {
+        let mut target = target();
+        target.url = String::from("http://localhost:0/health");
+        let check = target.check(&reqwest::Client::new()).await;
+        assert!(check.outcome.is_err());
+
+        let down = State::default().record(&target, check).unwrap();
+        assert_eq!(down.context[1].label, "Error");
+    }
+}
@@ -0,0 +1,1606 @@
+use crate::audit::AuditLog;
+use crate::circuit::CircuitBreaker;
+#[cfg(feature = "escalation")]
+use crate::escalation::Escalator;
+use crate::grouping::Grouper;
+use crate::metrics::Metrics;
+use crate::rate_limit::RateLimiter;
+#[cfg(feature = "redaction")]
+use crate::redaction::Redactor;
+use crate::sampling::Sampler;
+#[cfg(feature = "scrubbing")]
+use crate::scrubbing::Scrubber;
+use crate::spool::Spool;
+#[cfg(any(test, feature = "testing"))]
+use crate::testing::MockDestination;
+use crate::time::Instant;
+use crate::AlertStore;
+#[cfg(feature = "escalation")]
+use crate::Escalation;
+use crate::QuietHours;
+use crate::{
+    Context, ContextKind, ContextLimit, ContextOrder, DeliveryMetrics, DeliveryReceipt, Endpoint,
+    Error, HostMetadata, Labels, Locale, MaintenanceWindow, Notification, NotificationRef, Route,
+    SamplingPolicy, Severity, TimestampFormat,
+};
+#[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+use reqwest::Certificate;
+#[cfg(not(target_arch = "wasm32"))]
+use reqwest::Proxy;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Hook run before delivery, returning `None` drops the `Notification`
+type BeforeSend = Box<dyn Fn(Notification) -> Option<Notification> + Send + Sync>;
+
+/// Hook run after delivery, observing the result for a given destination
+type AfterSend = Box<dyn Fn(&Endpoint, &Result<DeliveryReceipt, Error>) + Send + Sync>;
+
+/// A reusable sender of `Notification`s, holding a configured HTTP client
+pub struct Notifier {
+    http_client: reqwest::Client,
+    min_severity: Option<Severity>,
+    sampler: Option<Sampler>,
+    grouper: Option<Grouper>,
+    timestamp_format: Option<TimestampFormat>,
+    labels: Option<Labels>,
+    locale: Option<Locale>,
+    #[cfg(feature = "redaction")]
+    redactor: Option<Redactor>,
+    #[cfg(feature = "scrubbing")]
+    scrubber: Option<Scrubber>,
+    context_limit: Option<ContextLimit>,
+    context_order: Option<ContextOrder>,
+    circuit_breaker: Option<CircuitBreaker>,
+    spool: Option<Spool>,
+    audit_log: Option<AuditLog>,
+    alert_store: Option<Arc<dyn AlertStore>>,
+    #[cfg(feature = "escalation")]
+    escalator: Option<Escalator>,
+    quiet_hours: Option<QuietHours>,
+    rate_limiter: RateLimiter,
+    maintenance_windows: Vec<MaintenanceWindow>,
+    metrics: Metrics,
+    destinations: Vec<(String, Endpoint)>,
+    routes: Vec<Route>,
+    before_send: Vec<BeforeSend>,
+    after_send: Vec<AfterSend>,
+    #[cfg(any(test, feature = "testing"))]
+    recorder: Option<MockDestination>,
+}
+impl Notifier {
+    /// Create a `Notifier` with the default HTTP client
+    ///
+    /// The default client honors the `HTTP_PROXY`, `HTTPS_PROXY`
+    /// and `NO_PROXY` environment variables.
+    pub fn new() -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            min_severity: None,
+            sampler: None,
+            grouper: None,
+            timestamp_format: None,
+            labels: None,
+            locale: None,
+            #[cfg(feature = "redaction")]
+            redactor: None,
+            #[cfg(feature = "scrubbing")]
+            scrubber: None,
+            context_limit: None,
+            context_order: None,
+            circuit_breaker: None,
+            spool: None,
+            audit_log: None,
+            alert_store: None,
+            #[cfg(feature = "escalation")]
+            escalator: None,
+            quiet_hours: None,
+            rate_limiter: RateLimiter::default(),
+            maintenance_windows: Vec::new(),
+            metrics: Metrics::default(),
+            destinations: Vec::new(),
+            routes: Vec::new(),
+            before_send: Vec::new(),
+            after_send: Vec::new(),
+            #[cfg(any(test, feature = "testing"))]
+            recorder: None,
+        }
+    }
+
+    /// Create a `Notifier` sending through a given, already configured HTTP client
+    ///
+    /// Clients share their connection pool when cloned, so one can be reused
+    /// across the application.
+    pub fn with_client(http_client: reqwest::Client) -> Self {
+        Self {
+            http_client,
+            ..Self::new()
+        }
+    }
+
+    /// Start building a `Notifier` with custom configuration
+    pub fn builder() -> NotifierBuilder {
+        NotifierBuilder::default()
+    }
+
+    /// Deliver every notification to a `MockDestination` instead of its
+    /// destination, adding it as the only destination if there are none
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn record_to(mut self, mock: MockDestination) -> Self {
+        if self.destinations.is_empty() {
+            self.destinations
+                .push((String::from("recording"), mock.endpoint()));
+        }
+        self.recorder = Some(mock);
+        self
+    }
+
+    /// Consume a `Notification` and send it to a given destination (API endpoint)
+    ///
+    /// The `Notification` is passed through any `before_send` hooks first,
+    /// if one of them drops it, or it's below the minimum severity of the
+    /// `Notifier` or destination, or it's sampled out, nothing is sent and
+    /// `Ok(None)` is returned. The same goes if it's held for quiet hours, or
+    /// held or dropped over the rate limit of the destination.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "dev_notify.send",
+            skip_all,
+            fields(destination = %destination.origin(), severity = ?notification.severity)
+        )
+    )]
+    pub async fn send(
+        &self,
+        notification: Notification,
+        destination: &Endpoint,
+    ) -> Result<Option<DeliveryReceipt>, Error> {
+        let notification = self
+            .run_before_send(notification)
+            .filter(|n| destination.accepts(n.severity))
+            .and_then(|n| self.prepare(n));
+        let Some(notification) = notification else {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("notification skipped by hooks, severity or sampling");
+            self.metrics.record_skip();
+            return Ok(None);
+        };
+        let Some(notification) = self.hold(notification, destination) else {
+            return Ok(None);
+        };
+
+        self.dispatch(notification, destination).await.map(Some)
+    }
+
+    /// Send a `NotificationRef` to a given destination like `send`, only
+    /// copying its borrowed data once it's above the minimum severity of the
+    /// `Notifier` and destination
+    pub async fn send_ref(
+        &self,
+        notification: NotificationRef<'_>,
+        destination: &Endpoint,
+    ) -> Result<Option<DeliveryReceipt>, Error> {
+        if !self.accepts(notification.severity) || !destination.accepts(notification.severity) {
+            self.metrics.record_skip();
+            return Ok(None);
+        }
+
+        self.send(notification.into_owned(), destination).await
+    }
+
+    /// Consume a `Notification` and send it to each named destination the
+    /// routes of the `Notifier` match, or every destination if it has no routes
+    ///
+    /// Returns the result for each destination it was sent to, by name.
+    /// Hooks, thresholds and sampling apply as in `send`, but only once.
+    pub async fn notify(
+        &self,
+        notification: Notification,
+    ) -> Vec<(String, Result<Option<DeliveryReceipt>, Error>)> {
+        let Some(notification) = self
+            .run_before_send(notification)
+            .and_then(|n| self.prepare(n))
+        else {
+            self.metrics.record_skip();
+            return Vec::new();
+        };
+
+        let mut results = Vec::new();
+        for (name, destination) in self.routed_destinations(&notification) {
+            let sendable = destination
+                .accepts(notification.severity)
+                .then(|| self.hold(notification.clone(), destination))
+                .flatten();
+            let result = match sendable {
+                Some(notification) => self.dispatch(notification, destination).await.map(Some),
+                None => Ok(None),
+            };
+            results.push((name.clone(), result));
+        }
+
+        results
+    }
+
+    /// The named destinations a `Notification` is routed to, in the order
+    /// they were added
+    fn routed_destinations(&self, notification: &Notification) -> Vec<&(String, Endpoint)> {
+        if self.routes.is_empty() {
+            return self.destinations.iter().collect();
+        }
+
+        let matching: Vec<&Route> = self
+            .routes
+            .iter()
+            .filter(|route| route.matches(notification))
+            .collect();
+        self.destinations
+            .iter()
+            .filter(|(name, _)| {
+                matching
+                    .iter()
+                    .any(|route| route.destinations().contains(name))
+            })
+            .collect()
+    }
+
+    /// Apply the severity threshold, maintenance windows, timestamp format,
+    /// labels, grouping, sampling, PII scrubbing, redaction, context limit and
+    /// order, and locale of the `Notifier`
+    fn prepare(&self, notification: Notification) -> Option<Notification> {
+        Some(notification)
+            .filter(|n| self.accepts(n.severity))
+            .and_then(|n| self.maintain(n))
+            .map(|n| self.format_timestamp(n))
+            .map(|n| self.label(n))
+            .and_then(|n| self.group(n))
+            .and_then(|n| self.sample(n))
+            .map(|n| self.scrub(n))
+            .map(|n| self.redact(n))
+            .map(|n| self.limit(n))
+            .map(|n| self.order(n))
+            .map(|n| self.localize(n))
+    }
+
+    /// Suppress or tag a `Notification` matching an open maintenance window
+    fn maintain(&self, notification: Notification) -> Option<Notification> {
+        self.maintenance_windows
+            .iter()
+            .try_fold(notification, |notification, window| {
+                window.apply(notification)
+            })
+    }
+
+    /// Translate labels with the `Locale` of the `Notifier`, if any
+    fn localize(&self, notification: Notification) -> Notification {
+        match &self.locale {
+            Some(locale) => locale.localize(notification),
+            None => notification,
+        }
+    }
+
+    /// Scrub PII with the `Scrubber` of the `Notifier`, if any
+    fn scrub(&self, notification: Notification) -> Notification {
+        #[cfg(feature = "scrubbing")]
+        if let Some(scrubber) = &self.scrubber {
+            return scrubber.scrub(notification);
+        }
+
+        notification
+    }
+
+    /// Mask secrets with the `Redactor` of the `Notifier`, if any
+    fn redact(&self, notification: Notification) -> Notification {
+        #[cfg(feature = "redaction")]
+        if let Some(redactor) = &self.redactor {
+            return redactor.redact(notification);
+        }
+
+        notification
+    }
+
+    /// Truncate long context values with the `ContextLimit` of the `Notifier`, if any
+    fn limit(&self, notification: Notification) -> Notification {
+        match &self.context_limit {
+            Some(limit) => limit.apply(notification),
+            None => notification,
+        }
+    }
+
+    /// Reorder context entries with the `ContextOrder` of the `Notifier`, if any
+    fn order(&self, notification: Notification) -> Notification {
+        match &self.context_order {
+            Some(order) => order.apply(notification),
+            None => notification,
+        }
+    }
+
+    /// The named destination added with `NotifierBuilder::destination`, if any
+    pub fn destination(&self, name: &str) -> Option<&Endpoint> {
+        self.destinations
+            .iter()
+            .find(|(destination, _)| destination == name)
+            .map(|(_, endpoint)| endpoint)
+    }
+
+    /// Every named destination, by name, in the order they were added
+    pub fn destinations(&self) -> impl Iterator<Item = (&str, &Endpoint)> {
+        self.destinations
+            .iter()
+            .map(|(name, endpoint)| (name.as_str(), endpoint))
+    }
+
+    /// Send a canary notification straight to a destination, to check it's
+    /// reachable (e.g. after rotating a webhook URL)
+    ///
+    /// Hooks, thresholds, sampling, quiet hours, rate limits and the circuit
+    /// breaker don't apply, and a failed canary isn't spooled.
+    pub async fn send_canary(&self, destination: &Endpoint) -> Result<DeliveryReceipt, Error> {
+        let mut canary = Notification::new("Canary notification from dev-notify, no action needed");
+        canary.severity = Severity::Info;
+        self.timed_deliver(canary, destination).await
+    }
+
+    /// The HTTP client the `Notifier` sends through
+    pub(crate) fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
+
+    /// The store tracking delivered notifications, if any
+    pub(crate) fn alert_store(&self) -> Option<&dyn AlertStore> {
+        self.alert_store.as_deref()
+    }
+
+    /// The escalation chain followed by unacknowledged notifications, if any
+    #[cfg(feature = "escalation")]
+    pub(crate) fn escalator(&self) -> Option<&Escalator> {
+        self.escalator.as_ref()
+    }
+
+    /// The quiet hours notifications are held during, if any
+    pub(crate) fn quiet_hours(&self) -> Option<&QuietHours> {
+        self.quiet_hours.as_ref()
+    }
+
+    /// The rate limit state of each destination
+    pub(crate) fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
+    /// Hold a `Notification` for a destination during quiet hours, or hold
+    /// or drop it over the rate limit of the destination, handing it back if
+    /// it should be sent now
+    fn hold(&self, notification: Notification, destination: &Endpoint) -> Option<Notification> {
+        let notification = match &self.quiet_hours {
+            Some(quiet_hours) => {
+                let Some(notification) = quiet_hours.hold(notification, destination) else {
+                    self.metrics.record_suppress();
+                    return None;
+                };
+                notification
+            }
+            None => notification,
+        };
+
+        self.rate_limiter
+            .admit(notification, destination, &self.metrics)
+    }
+
+    /// The named destination with a given URL, if any
+    pub(crate) fn destination_at(&self, url: &str) -> Option<&Endpoint> {
+        self.destinations
+            .iter()
+            .map(|(_, destination)| destination)
+            .find(|destination| destination.url() == url)
+    }
+
+    /// A snapshot of delivery metrics since the `Notifier` was created, so
+    /// the alerting pipeline itself can be monitored
+    pub fn metrics(&self) -> DeliveryMetrics {
+        self.metrics.snapshot()
+    }
+
+    /// Resend every notification in the spool to its destination, returning
+    /// how many were delivered
+    ///
+    /// Only destination URLs are spooled, so spooled notifications are resent
+    /// through the given `Endpoint` with the same URL (and its headers).
+    /// Notifications without a matching `Endpoint`, or that fail again, stay
+    /// in the spool.
+    pub async fn replay(&self, destinations: &[Endpoint]) -> Result<usize, Error> {
+        let Some(spool) = &self.spool else {
+            return Ok(0);
+        };
+
+        let mut delivered = 0;
+        for spooled in spool.take().map_err(Error::Spool)? {
+            let destination = destinations.iter().find(|d| d.url() == spooled.url);
+            match destination {
+                Some(destination) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(destination = %destination.origin(), "replaying notification");
+                    self.metrics.record_retry();
+                    // Failures are spooled again by `dispatch`
+                    if self
+                        .dispatch(spooled.notification, destination)
+                        .await
+                        .is_ok()
+                    {
+                        delivered += 1;
+                    }
+                }
+                None => spool
+                    .append(&spooled.url, &spooled.notification)
+                    .map_err(Error::Spool)?,
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    /// Deliver a `Notification` through any circuit breaker, spooling it if
+    /// delivery fails and reporting the result to `after_send` hooks
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "dev_notify.deliver",
+            skip_all,
+            fields(destination = %destination.origin(), status)
+        )
+    )]
+    pub(crate) async fn dispatch(
+        &self,
+        mut notification: Notification,
+        destination: &Endpoint,
+    ) -> Result<DeliveryReceipt, Error> {
+        let id = notification
+            .id
+            .get_or_insert_with(crate::id::new_id)
+            .clone();
+        let spooled = self
+            .spool
+            .as_ref()
+            .map(|spool| (spool, notification.clone()));
+        let tracked = self.alert_store.is_some().then(|| notification.clone());
+        let audited = self
+            .audit_log
+            .as_ref()
+            .map(|log| (log, notification.clone()));
+
+        let result = match &self.circuit_breaker {
+            Some(breaker) if !breaker.allows(destination.url()) => Err(Error::CircuitOpen {
+                url: destination.url().to_string(),
+            }),
+            Some(breaker) => {
+                let result = self.timed_deliver(notification, destination).await;
+                match &result {
+                    Ok(_) => breaker.record(destination.url(), true),
+                    // A rejected payload or invalid configuration says nothing of the destination
+                    Err(e) if e.is_retryable() => breaker.record(destination.url(), false),
+                    Err(_) => breaker.release(destination.url()),
+                }
+                result
+            }
+            None => self.timed_deliver(notification, destination).await,
+        };
+        if let Err(Error::CircuitOpen { .. }) = result {
+            self.metrics.record_delivery(false, Duration::ZERO);
+        }
+        let result = result.map(|mut receipt| {
+            receipt.notification_id = Some(id);
+            receipt
+        });
+        #[cfg(feature = "tracing")]
+        match &result {
+            Ok(receipt) => {
+                tracing::Span::current().record("status", receipt.status.as_u16());
+                tracing::debug!("notification delivered");
+            }
+            Err(e) => tracing::warn!(error = %e, "notification delivery failed"),
+        }
+
+        // Keep the delivery error over any spooling error,
+        // it's the one the caller needs to know about
+        if let (Err(e), Some((spool, notification))) = (&result, spooled) {
+            // Permanent failures (e.g. oversized payloads) would only fail again when replayed
+            if e.is_retryable() {
+                let _ = spool.append(destination.url(), &notification);
+            }
+        }
+        if let Some((audit_log, notification)) = audited {
+            let _ = audit_log.record(destination.url(), &notification, &result);
+        }
+        if let (Ok(receipt), Some(notification)) = (&result, tracked) {
+            self.track(notification, destination, receipt);
+        }
+        for hook in &self.after_send {
+            hook(destination, &result);
+        }
+
+        result
+    }
+
+    /// Whether the `Notifier` accepts notifications of a given severity
+    fn accepts(&self, severity: Severity) -> bool {
+        self.min_severity.is_none_or(|min| severity >= min)
+    }
+
+    /// Apply any sampling policy to a `Notification`, noting how many
+    /// similar notifications were suppressed since the last one sent
+    fn sample(&self, mut notification: Notification) -> Option<Notification> {
+        let Some(sampler) = &self.sampler else {
+            return Some(notification);
+        };
+
+        let fingerprint = notification
+            .fingerprint
+            .as_deref()
+            .unwrap_or(&notification.message);
+        let suppressed = sampler.admit(fingerprint)?;
+        if suppressed > 0 {
+            notification.context.push(Context {
+                label: String::from("Suppressed"),
+                value: format!("{suppressed} since last sent"),
+                kind: ContextKind::Text,
+            });
+        }
+
+        Some(notification)
+    }
+
+    /// Render a generated timestamp in the format of the `Notifier`, if any
+    fn format_timestamp(&self, mut notification: Notification) -> Notification {
+        let english = Locale::new();
+        let locale = self.locale.as_ref().unwrap_or(&english);
+        let formatted = self
+            .timestamp_format
+            .as_ref()
+            .and_then(|format| format.reformat(&notification.timestamp, locale));
+        if let Some(formatted) = formatted {
+            notification.timestamp = formatted;
+        }
+
+        notification
+    }
+
+    /// Apply the labels of the `Notifier`, unless the `Notification` has its own
+    fn label(&self, mut notification: Notification) -> Notification {
+        if notification.labels.is_none() {
+            notification.labels.clone_from(&self.labels);
+        }
+
+        notification
+    }
+
+    /// Apply the grouping of the `Notifier`, noting how often the group was seen
+    fn group(&self, mut notification: Notification) -> Option<Notification> {
+        let Some(grouper) = &self.grouper else {
+            return Some(notification);
+        };
+
+        let fingerprint = notification
+            .fingerprint
+            .as_deref()
+            .unwrap_or(&notification.message);
+        let (count, first_seen) = grouper.admit(fingerprint, &notification.timestamp)?;
+        if count > 1 {
+            notification.context.extend([
+                Context {
+                    label: String::from("First Seen"),
+                    value: first_seen,
+                    kind: ContextKind::Text,
+                },
+                Context {
+                    label: String::from("Last Seen"),
+                    value: notification.timestamp.clone(),
+                    kind: ContextKind::Text,
+                },
+                Context {
+                    label: String::from("Count"),
+                    value: count.to_string(),
+                    kind: ContextKind::Text,
+                },
+            ]);
+        }
+
+        Some(notification)
+    }
+
+    /// Pass a `Notification` through each `before_send` hook in order
+    fn run_before_send(&self, notification: Notification) -> Option<Notification> {
+        self.before_send
+            .iter()
+            .try_fold(notification, |notification, hook| hook(notification))
+    }
+
+    /// Deliver a `Notification`, recording the outcome and latency in the metrics
+    async fn timed_deliver(
+        &self,
+        notification: Notification,
+        destination: &Endpoint,
+    ) -> Result<DeliveryReceipt, Error> {
+        let started = Instant::now();
+        let result = self.deliver(notification, destination).await;
+        self.metrics
+            .record_delivery(result.is_ok(), started.elapsed());
+
+        result
+    }
+
+    /// Deliver a `Notification` to a given destination (API endpoint)
+    async fn deliver(
+        &self,
+        notification: Notification,
+        destination: &Endpoint,
+    ) -> Result<DeliveryReceipt, Error> {
+        #[cfg(any(test, feature = "testing"))]
+        if let Some(mock) = destination.mock_destination().or(self.recorder.as_ref()) {
+            return Ok(mock.record(notification));
+        }
+
+        // Annotate the workflow run when inside GitHub Actions, possibly instead of the webhook
+        #[cfg(feature = "github-actions")]
+        if let Some(github_actions) = destination.github_actions_config() {
+            if github_actions.report(&notification)? && github_actions.replaces_webhook() {
+                return Ok(DeliveryReceipt::new(reqwest::StatusCode::OK));
+            }
+        }
+
+        // Push the serialized notification, encrypted, to a browser subscription
+        #[cfg(feature = "web-push")]
+        if let Some(web_push) = destination.web_push_config() {
+            let request = web_push.request(&self.http_client, destination, notification)?;
+            let (status, body) = crate::runtime::send(request).await?;
+            if !status.is_success() {
+                return Err(Error::Status { status, body });
+            }
+            return Ok(DeliveryReceipt::new(status));
+        }
+
+        let idempotency_key = notification.idempotency_key.clone();
+        let notification_id = notification.id.clone();
+
+        // Upload attachments through the Slack Web API, if that's the destination,
+        // so they're linked from the message instead of included inline
+        #[cfg(feature = "slack")]
+        let notification =
+            crate::slack::upload_attachments(&self.http_client, destination, notification).await;
+        let mut notification = notification;
+        if notification.channel.is_none() {
+            notification.channel = destination.default_channel().map(String::from);
+        }
+
+        #[cfg(feature = "service-bus")]
+        let service_bus_headers = destination
+            .service_bus_config()
+            .map(|service_bus| service_bus.headers(destination.url(), &notification))
+            .unwrap_or_default();
+
+        // Parse the `Notification` into a slack message (or the format of the destination)
+        let payload = notification.render_for(destination)?;
+        #[cfg(feature = "debug-http")]
+        tracing::debug!(
+            destination = %destination.origin(),
+            payload = %payload.body,
+            "sending notification"
+        );
+
+        // Build and send the HTTP request to a given destination
+        // with the payload being our derived slack message
+        let mut request = destination
+            .post_body(&self.http_client, payload.body)
+            .header("Content-type", payload.content_type);
+        if let Some(id) = &notification_id {
+            request = request.header("X-Dev-Notify-Id", id);
+        }
+        if let Some(key) = idempotency_key {
+            request = request.header("Idempotency-Key", key);
+        }
+        #[cfg(feature = "pubsub")]
+        if let Some(pubsub) = destination.pubsub_config() {
+            if let Some(token) = pubsub.access_token(&self.http_client).await? {
+                request = request.bearer_auth(token);
+            }
+        }
+        #[cfg(feature = "service-bus")]
+        for (name, value) in service_bus_headers {
+            request = request.header(name, value);
+        }
+        let (status, body) = crate::runtime::send(request).await?;
+        #[cfg(feature = "debug-http")]
+        tracing::debug!(
+            destination = %destination.origin(),
+            status = status.as_u16(),
+            body = %body,
+            "destination responded"
+        );
+        if !status.is_success() {
+            return Err(Error::Status { status, body });
+        }
+        // GitLab responds with the opened issue, link to it
+        #[cfg(feature = "gitlab")]
+        if destination.message_format() == crate::MessageFormat::Gitlab {
+            return Ok(DeliveryReceipt::from_gitlab_response(status, &body));
+        }
+        // Slack Web API responses identify the posted message, look up
+        // its permalink too so callers can link to it
+        #[cfg(feature = "slack")]
+        let receipt = crate::slack::with_permalink(
+            &self.http_client,
+            destination,
+            DeliveryReceipt::from_slack_response(status, &body),
+        )
+        .await;
+        #[cfg(not(feature = "slack"))]
+        let receipt = DeliveryReceipt::new(status);
+
+        Ok(receipt)
+    }
+}
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Proxy settings for a `NotifierBuilder`
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+struct ProxyConfig {
+    url: String,
+    credentials: Option<(String, String)>,
+}
+
+/// An additional trusted root certificate for a `NotifierBuilder`
+#[cfg(feature = "__tls")]
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+enum RootCertificate {
+    Pem(Vec<u8>),
+    Der(Vec<u8>),
+}
+#[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+impl RootCertificate {
+    /// Parse into a certificate the HTTP client can trust
+    fn parse(&self) -> Result<Certificate, reqwest::Error> {
+        match self {
+            Self::Pem(pem) => Certificate::from_pem(pem),
+            Self::Der(der) => Certificate::from_der(der),
+        }
+    }
+}
+
+/// Builder for a `Notifier` with custom configuration
+#[derive(Default)]
+pub struct NotifierBuilder {
+    http_client: Option<reqwest::Client>,
+    proxy: Option<ProxyConfig>,
+    no_proxy: bool,
+    #[cfg(feature = "__tls")]
+    root_certificates: Vec<RootCertificate>,
+    #[cfg(feature = "__tls")]
+    accept_invalid_certs: bool,
+    min_severity: Option<Severity>,
+    sampling: Option<SamplingPolicy>,
+    grouping: Option<Duration>,
+    timestamp_format: Option<TimestampFormat>,
+    labels: Option<Labels>,
+    locale: Option<Locale>,
+    #[cfg(feature = "redaction")]
+    redactor: Option<Redactor>,
+    #[cfg(feature = "scrubbing")]
+    scrubber: Option<Scrubber>,
+    context_limit: Option<ContextLimit>,
+    context_order: Option<ContextOrder>,
+    circuit_breaker: Option<(u32, Duration)>,
+    spool: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    alert_store: Option<Arc<dyn AlertStore>>,
+    #[cfg(feature = "escalation")]
+    escalation: Option<Escalation>,
+    quiet_hours: Option<QuietHours>,
+    maintenance_windows: Vec<MaintenanceWindow>,
+    destinations: Vec<(String, Endpoint)>,
+    routes: Vec<Route>,
+    before_send: Vec<BeforeSend>,
+    after_send: Vec<AfterSend>,
+}
+impl NotifierBuilder {
+    /// Send through a given, already configured HTTP client instead of building one
+    ///
+    /// Proxy and TLS settings can't be applied to a provided client,
+    /// configure them on the client itself.
+    pub fn http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// Route all requests through the proxy at a given URL,
+    /// instead of any proxy set through the environment
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(ProxyConfig {
+            url: url.into(),
+            credentials: None,
+        });
+        self
+    }
+
+    /// Authenticate against the configured proxy with basic auth
+    ///
+    /// Has no effect unless a proxy was set with `proxy`.
+    pub fn proxy_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        if let Some(proxy) = self.proxy.as_mut() {
+            proxy.credentials = Some((username.into(), password.into()));
+        }
+        self
+    }
+
+    /// Ignore any proxy set through the environment and connect directly
+    pub fn no_proxy(mut self) -> Self {
+        self.no_proxy = true;
+        self
+    }
+
+    /// Trust an additional root certificate (PEM encoded), e.g. for
+    /// internal webhook relays signed by a private certificate authority
+    #[cfg(feature = "__tls")]
+    pub fn add_root_certificate_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates
+            .push(RootCertificate::Pem(pem.into()));
+        self
+    }
+
+    /// Trust an additional root certificate (DER encoded)
+    #[cfg(feature = "__tls")]
+    pub fn add_root_certificate_der(mut self, der: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates
+            .push(RootCertificate::Der(der.into()));
+        self
+    }
+
+    /// Toggle certificate verification off (or back on)
+    ///
+    /// **Warning**: with verification off any certificate is trusted, including
+    /// expired or self-signed ones. Only use this against endpoints you control.
+    #[cfg(feature = "__tls")]
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.accept_invalid_certs = accept_invalid_certs;
+        self
+    }
+
+    /// Only send notifications at or above a given severity, to any destination
+    ///
+    /// Use `Endpoint::min_severity` to set a threshold per destination instead.
+    pub fn min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    /// Sample notifications sharing a fingerprint (or message, without one)
+    /// with a given policy, the next one sent notes how many were suppressed
+    /// in its context
+    pub fn sampling(mut self, policy: SamplingPolicy) -> Self {
+        self.sampling = Some(policy);
+        self
+    }
+
+    /// Group notifications sharing a fingerprint (or message, without one),
+    /// sending at most one per `window`
+    ///
+    /// The first sent after the window notes when the group was first and
+    /// last seen, and how many times, in its context.
+    pub fn grouping(mut self, window: Duration) -> Self {
+        self.grouping = Some(window);
+        self
+    }
+
+    /// Render timestamps generated by this crate in a given format and timezone,
+    /// e.g. so on-call readers see their local time
+    pub fn timestamp_format(mut self, format: TimestampFormat) -> Self {
+        self.timestamp_format = Some(format);
+        self
+    }
+
+    /// Mask secrets in every `Notification` with a given `Redactor`, after
+    /// every hook has run and just before it's sent (or spooled)
+    #[cfg(feature = "redaction")]
+    pub fn redact(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Scrub PII out of every `Notification` with a given `Scrubber`, after
+    /// every hook has run and just before it's sent (or spooled)
+    #[cfg(feature = "scrubbing")]
+    pub fn scrub(mut self, scrubber: Scrubber) -> Self {
+        self.scrubber = Some(scrubber);
+        self
+    }
+
+    /// Render messages with given built-in labels, e.g. "Alert" and
+    /// "Occurred at", unless a `Notification` has its own
+    pub fn labels(mut self, labels: Labels) -> Self {
+        self.labels = Some(labels);
+        self
+    }
+
+    /// Render built-in labels and timestamp month and weekday names in a
+    /// given `Locale`, after any hooks, scrubbing and redaction
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Truncate context values longer than a `ContextLimit`, after any
+    /// hooks, scrubbing and redaction
+    pub fn context_limit(mut self, limit: ContextLimit) -> Self {
+        self.context_limit = Some(limit);
+        self
+    }
+
+    /// Show context entries in a given `ContextOrder`, e.g. with
+    /// `Customer ID` always at the top
+    pub fn context_order(mut self, order: ContextOrder) -> Self {
+        self.context_order = Some(order);
+        self
+    }
+
+    /// Stop sending to a destination for a `cooldown` period after it fails
+    /// `failure_threshold` times in a row
+    ///
+    /// Sends to a destination with an open circuit fail fast with
+    /// `Error::CircuitOpen`, which `after_send` hooks also observe.
+    pub fn circuit_breaker(mut self, failure_threshold: u32, cooldown: Duration) -> Self {
+        self.circuit_breaker = Some((failure_threshold, cooldown));
+        self
+    }
+
+    /// Append notifications that fail to deliver to a file at a given path,
+    /// so they can be resent with `Notifier::replay` once the destination recovers
+    pub fn spool(mut self, path: impl Into<PathBuf>) -> Self {
+        self.spool = Some(path.into());
+        self
+    }
+
+    /// Record every delivery attempt, with its outcome, to a file at a given
+    /// path (JSON lines), for post-incident review and `Notifier::replay_from`
+    pub fn audit_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_log = Some(path.into());
+        self
+    }
+
+    /// Track the state of delivered notifications in a given store, so they
+    /// can be acknowledged and resolved with `Notifier::acknowledge` and
+    /// `Notifier::resolve`, or from Slack buttons with `Notifier::respond`
+    pub fn alert_store(mut self, store: impl AlertStore + 'static) -> Self {
+        self.alert_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Escalate notifications that aren't acknowledged in time, when
+    /// `Notifier::escalate` is called (e.g. by `Notifier::spawn_escalations`)
+    ///
+    /// Building fails with `Error::Config` without an `alert_store`.
+    #[cfg(feature = "escalation")]
+    pub fn escalation(mut self, escalation: Escalation) -> Self {
+        self.escalation = Some(escalation);
+        self
+    }
+
+    /// Hold notifications below `Severity::Critical` during given `QuietHours`,
+    /// to be sent as a digest by `Notifier::release_held` once they're over
+    pub fn quiet_hours(mut self, quiet_hours: QuietHours) -> Self {
+        self.quiet_hours = Some(quiet_hours);
+        self
+    }
+
+    /// Add a `MaintenanceWindow` during which matching notifications are
+    /// suppressed or tagged
+    pub fn maintenance_window(mut self, window: MaintenanceWindow) -> Self {
+        self.maintenance_windows.push(window);
+        self
+    }
+
+    /// Add a named destination for `Notifier::notify`
+    pub fn destination(mut self, name: impl Into<String>, destination: Endpoint) -> Self {
+        self.destinations.push((name.into(), destination));
+        self
+    }
+
+    /// Add a `Route` deciding which named destinations `Notifier::notify`
+    /// sends to, a `Notification` goes to the destinations of every matching route
+    ///
+    /// Without any routes, notifications go to every destination.
+    pub fn route(mut self, route: Route) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    /// Add a hook run on every `Notification` before it's sent, which can
+    /// modify it (e.g. enrichment) or drop it by returning `None` (e.g. filtering)
+    ///
+    /// Hooks run in the order they were added.
+    pub fn before_send<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(Notification) -> Option<Notification> + Send + Sync + 'static,
+    {
+        self.before_send.push(Box::new(hook));
+        self
+    }
+
+    /// Append metadata about the host and process (e.g. from `host_metadata!`)
+    /// to the context of every `Notification`
+    ///
+    /// This runs as a `before_send` hook, in the order hooks were added.
+    pub fn host_metadata(self, metadata: HostMetadata) -> Self {
+        self.before_send(move |notification| Some(metadata.enrich(notification)))
+    }
+
+    /// Add a hook run after every delivery attempt with its destination
+    /// and result (e.g. for metrics), dropped notifications are not observed
+    pub fn after_send<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Endpoint, &Result<DeliveryReceipt, Error>) + Send + Sync + 'static,
+    {
+        self.after_send.push(Box::new(hook));
+        self
+    }
+
+    /// Consume the `NotifierBuilder` and build a `Notifier`
+    pub fn build(self) -> Result<Notifier, Error> {
+        let http_client = match &self.http_client {
+            Some(_) if self.configures_client() => {
+                return Err(Error::Config(String::from(
+                    "proxy and TLS settings can't be applied to a provided HTTP client",
+                )));
+            }
+            Some(http_client) => http_client.clone(),
+            None => self.configure_client(reqwest::Client::builder())?.build()?,
+        };
+        #[cfg(feature = "escalation")]
+        if self.escalation.is_some() && self.alert_store.is_none() {
+            return Err(Error::Config(String::from(
+                "escalation needs an alert store to know what was acknowledged",
+            )));
+        }
+
+        Ok(Notifier {
+            http_client,
+            min_severity: self.min_severity,
+            sampler: self.sampling.map(Sampler::new),
+            grouper: self.grouping.map(Grouper::new),
+            timestamp_format: self.timestamp_format,
+            labels: self.labels,
+            locale: self.locale,
+            #[cfg(feature = "redaction")]
+            redactor: self.redactor,
+            #[cfg(feature = "scrubbing")]
+            scrubber: self.scrubber,
+            context_limit: self.context_limit,
+            context_order: self.context_order,
+            circuit_breaker: self.circuit_breaker.map(|(failure_threshold, cooldown)| {
+                CircuitBreaker::new(failure_threshold, cooldown)
+            }),
+            spool: self.spool.map(Spool::new),
+            audit_log: self.audit_log.map(AuditLog::new),
+            alert_store: self.alert_store,
+            #[cfg(feature = "escalation")]
+            escalator: self.escalation.map(Escalator::new),
+            quiet_hours: self.quiet_hours,
+            rate_limiter: RateLimiter::default(),
+            maintenance_windows: self.maintenance_windows,
+            metrics: Metrics::default(),
+            destinations: self.destinations,
+            routes: self.routes,
+            before_send: self.before_send,
+            after_send: self.after_send,
+            #[cfg(any(test, feature = "testing"))]
+            recorder: None,
+        })
+    }
+
+    /// Whether any proxy or TLS settings were configured
+    fn configures_client(&self) -> bool {
+        #[cfg(feature = "__tls")]
+        let tls_configured = !self.root_certificates.is_empty() || self.accept_invalid_certs;
+        #[cfg(not(feature = "__tls"))]
+        let tls_configured = false;
+
+        self.proxy.is_some() || self.no_proxy || tls_configured
+    }
+
+    /// Apply proxy and TLS configuration to the HTTP client
+    #[cfg(not(target_arch = "wasm32"))]
+    fn configure_client(
+        &self,
+        mut client_builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder, Error> {
+        // An explicit proxy takes precedence over the environment
+        if self.no_proxy {
+            client_builder = client_builder.no_proxy();
+        }
+        if let Some(config) = &self.proxy {
+            let mut proxy = Proxy::all(&config.url)?;
+            if let Some((username, password)) = &config.credentials {
+                proxy = proxy.basic_auth(username, password);
+            }
+            client_builder = client_builder.proxy(proxy);
+        }
+
+        // Apply TLS configuration
+        #[cfg(feature = "__tls")]
+        {
+            for root_certificate in &self.root_certificates {
+                client_builder = client_builder.add_root_certificate(root_certificate.parse()?);
+            }
+            client_builder = client_builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        }
+
+        Ok(client_builder)
+    }
+
+    /// Reject proxy and TLS configuration, which the host environment
+    /// (browser or edge runtime) controls on `wasm32`
+    #[cfg(target_arch = "wasm32")]
+    fn configure_client(
+        &self,
+        client_builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder, Error> {
+        if self.configures_client() {
+            return Err(Error::Config(String::from(
+                "proxy and TLS settings aren't supported on wasm32",
+            )));
+        }
+
+        Ok(client_builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{MockDestination, MockServer};
+    use crate::{
+        Context, ContextKind, Error, Labels, Notification, Notifier, Route, SamplingPolicy,
+        Severity, TimestampFormat,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// A notification for tests to send
+    fn notification() -> Notification {
+        Notification {
+            message: String::from("External API Error: Could not find API Keys"),
+            timestamp: String::from("2024-01-19 19:26:20.022233"),
+            context: vec![Context {
+                label: String::from("Customer ID"),
+                value: String::from("0"),
+                kind: ContextKind::Text,
+            }],
+            severity: Severity::Error,
+            idempotency_key: None,
+            fingerprint: None,
+            sections: Vec::new(),
+            attachments: Vec::new(),
+            labels: None,
+            channel: None,
+            id: None,
+        }
+    }
+
+    /// A test to make sure `before_send` hooks run in order and can modify a notification
+    #[test]
+    fn runs_before_send_hooks_in_order() {
+        let notifier = Notifier::builder()
+            .before_send(|mut notification| {
+                notification.message.push_str(" (first)");
+                Some(notification)
+            })
+            .before_send(|mut notification| {
+                notification.message.push_str(" (second)");
+                Some(notification)
+            })
+            .build()
+            .unwrap();
+
+        let notification = notifier.run_before_send(notification()).unwrap();
+        assert_eq!(
+            notification.message,
+            "External API Error: Could not find API Keys (first) (second)"
+        )
+    }
+
+    /// A test to make sure a `before_send` hook can drop a notification
+    #[tokio::test]
+    async fn before_send_hook_can_drop() {
+        let observed = Arc::new(AtomicUsize::new(0));
+        let after_send_observed = observed.clone();
+        let notifier = Notifier::builder()
+            .before_send(|_| None)
+            .after_send(move |_, _| {
+                after_send_observed.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .unwrap();
+
+        let result = notifier
+            .send(notification(), &"http://localhost:0/unreachable".into())
+            .await;
+        assert!(matches!(result, Ok(None)));
+        assert_eq!(observed.load(Ordering::SeqCst), 0)
+    }
+
+    /// A test to make sure notifications below the minimum severity aren't sent
+    #[tokio::test]
+    async fn skips_below_min_severity() {
+        let observed = Arc::new(AtomicUsize::new(0));
+        let after_send_observed = observed.clone();
+        let notifier = Notifier::builder()
+            .min_severity(Severity::Critical)
+            .after_send(move |_, _| {
+                after_send_observed.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .unwrap();
+
+        let result = notifier
+            .send(notification(), &"http://localhost:0/unreachable".into())
+            .await;
+        assert!(matches!(result, Ok(None)));
+        assert_eq!(observed.load(Ordering::SeqCst), 0)
+    }
+
+    /// A test to make sure sampled notifications note how many were suppressed
+    #[test]
+    fn notes_suppressed_notifications() {
+        let notifier = Notifier::builder()
+            .sampling(SamplingPolicy::OneIn(2))
+            .build()
+            .unwrap();
+
+        assert!(notifier.sample(notification()).is_some());
+        assert!(notifier.sample(notification()).is_none());
+        let notification = notifier.sample(notification()).unwrap();
+        let suppressed = notification.context.last().unwrap();
+        assert_eq!(suppressed.label, "Suppressed");
+        assert_eq!(suppressed.value, "1 since last sent");
+
+        // Messages varying in their details are sampled together by fingerprint
+        let sampled: Vec<_> = (0..4)
+            .map(|id| {
+                let mut notification = Notification::new(format!("Order {id} failed"));
+                notification.fingerprint = Some(String::from("order-failed"));
+                notifier.sample(notification).is_some()
+            })
+            .collect();
+        assert_eq!(sampled, [true, false, true, false]);
+    }
+
+    /// A test to make sure grouped notifications note their occurrences
+    #[test]
+    fn notes_grouped_occurrences() {
+        let notifier = Notifier::builder()
+            .grouping(Duration::ZERO)
+            .build()
+            .unwrap();
+
+        let mut first = notification();
+        first.fingerprint = Some(String::from("api-keys"));
+        first.timestamp = String::from("2024-01-19 19:00:00.000000");
+        assert_eq!(notifier.group(first).unwrap().context.len(), 1);
+
+        let mut second = notification();
+        second.fingerprint = Some(String::from("api-keys"));
+        let grouped = notifier.group(second).unwrap();
+        let labels: Vec<_> = grouped.context.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, ["Customer ID", "First Seen", "Last Seen", "Count"]);
+        assert_eq!(grouped.context[1].value, "2024-01-19 19:00:00.000000");
+        assert_eq!(grouped.context[3].value, "2");
+    }
+
+    /// A test to make sure generated timestamps are rendered in the configured format
+    #[test]
+    fn formats_generated_timestamps() {
+        let notifier = Notifier::builder()
+            .timestamp_format(TimestampFormat::Pattern {
+                pattern: String::from("%H:%M %z"),
+                utc_offset_minutes: 60,
+            })
+            .build()
+            .unwrap();
+
+        let formatted = notifier.prepare(notification()).unwrap();
+        assert_eq!(formatted.timestamp, "20:26 +01:00");
+
+        let mut custom = notification();
+        custom.timestamp = String::from("Friday evening");
+        let custom = notifier.prepare(custom).unwrap();
+        assert_eq!(custom.timestamp, "Friday evening");
+    }
+
+    /// A test to make sure an open circuit fails fast and is reported
+    #[tokio::test]
+    async fn fails_fast_with_open_circuit() {
+        let notifier = Notifier::builder()
+            .circuit_breaker(1, Duration::from_secs(3600))
+            .build()
+            .unwrap();
+        let destination = "http://localhost:0/unreachable".into();
+
+        let result = notifier.send(notification(), &destination).await;
+        assert!(matches!(result, Err(Error::Http(_))));
+        let result = notifier.send(notification(), &destination).await;
+        assert!(matches!(result, Err(Error::CircuitOpen { .. })));
+        assert_eq!(notifier.metrics().failed, 2);
+    }
+
+    /// A test to make sure error responses count as failures, opening the circuit and spooled
+    #[tokio::test]
+    async fn fails_on_error_status() {
+        let path = std::env::temp_dir().join(format!("dev-notify-status-{}", std::process::id()));
+        let server = MockServer::new(vec![(503, String::from("upstream unavailable"))]);
+        let notifier = Notifier::builder()
+            .circuit_breaker(1, Duration::from_secs(3600))
+            .spool(&path)
+            .build()
+            .unwrap();
+        let destination = format!("{}/notify", server.url()).into();
+
+        let result = notifier.send(notification(), &destination).await;
+        assert!(matches!(
+            result,
+            Err(Error::Status { status, ref body })
+                if status.as_u16() == 503 && body == "upstream unavailable"
+        ));
+        let result = notifier.send(notification(), &destination).await;
+        assert!(matches!(result, Err(Error::CircuitOpen { .. })));
+        assert_eq!(server.requests().len(), 1);
+
+        // Spooled once for the error status, and once more for the open circuit
+        let spooled = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(spooled.lines().count(), 2);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// A test to make sure a rejected payload doesn't count as a failure of the destination
+    #[cfg(feature = "slack")]
+    #[tokio::test]
+    async fn rejected_payload_keeps_circuit_closed() {
+        use crate::{Endpoint, OversizePolicy};
+
+        let server = MockServer::new(vec![(200, String::from("ok"))]);
+        let notifier = Notifier::builder()
+            .circuit_breaker(1, Duration::from_secs(3600))
+            .build()
+            .unwrap();
+        let destination =
+            Endpoint::new(format!("{}/notify", server.url())).oversize(OversizePolicy::Reject);
+
+        let mut oversized = notification();
+        oversized.message = "x".repeat(10_000);
+        let result = notifier.send(oversized, &destination).await;
+        assert!(matches!(result, Err(Error::PayloadTooLarge { .. })));
+        assert!(server.requests().is_empty());
+
+        notifier.send(notification(), &destination).await.unwrap();
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    /// A test to make sure canaries skip thresholds and hooks, but aren't spooled
+    #[tokio::test]
+    async fn sends_canary_straight_to_destination() {
+        let path = std::env::temp_dir().join(format!("dev-notify-canary-{}", std::process::id()));
+        let mock = MockDestination::new();
+        let notifier = Notifier::builder()
+            .min_severity(Severity::Critical)
+            .before_send(|_| None)
+            .spool(&path)
+            .destination("slack", mock.endpoint())
+            .build()
+            .unwrap();
+
+        let (name, slack) = notifier.destinations().next().unwrap();
+        assert_eq!(name, "slack");
+        assert!(notifier.send_canary(slack).await.is_ok());
+        assert_eq!(mock.len(), 1);
+
+        let unreachable = "http://localhost:0/unreachable".into();
+        assert!(notifier.send_canary(&unreachable).await.is_err());
+        assert!(!path.exists());
+    }
+
+    /// A test to make sure every delivered notification has an ID, on its receipt too
+    #[tokio::test]
+    async fn identifies_notifications() {
+        let mock = MockDestination::new();
+        let notifier = Notifier::new();
+
+        let notification = Notification::new("Disk full");
+        let id = notification.id.clone().unwrap();
+        let receipt = notifier
+            .send(notification, &mock.endpoint())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(receipt.notification_id.as_deref(), Some(id.as_str()));
+        assert_eq!(mock.delivered()[0].id.as_deref(), Some(id.as_str()));
+
+        // Notifications built without one get one when sent
+        let mut notification = Notification::new("CPU high");
+        notification.id = None;
+        let receipt = notifier
+            .send(notification, &mock.endpoint())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(receipt.notification_id, mock.delivered()[1].id);
+        assert!(receipt.notification_id.is_some());
+    }
+
+    /// A test to make sure failed notifications are spooled and can be replayed
+    #[tokio::test]
+    async fn spools_and_replays_failures() {
+        let path = std::env::temp_dir().join(format!("dev-notify-replay-{}", std::process::id()));
+        let notifier = Notifier::builder().spool(&path).build().unwrap();
+        let unreachable = "http://localhost:0/unreachable".into();
+        assert!(notifier.send(notification(), &unreachable).await.is_err());
+
+        // The destination has "recovered" once it's swapped for a mock
+        let mock = MockDestination::new();
+        assert_eq!(notifier.replay(&[]).await.unwrap(), 0);
+        let recovered = mock.endpoint_at("http://localhost:0/unreachable");
+        assert_eq!(notifier.replay(&[recovered]).await.unwrap(), 1);
+        mock.assert_delivered("External API Error: Could not find API Keys");
+        let metrics = notifier.metrics();
+        assert_eq!((metrics.sent, metrics.failed, metrics.retried), (1, 1, 1));
+        assert_eq!(notifier.replay(&[]).await.unwrap(), 0);
+    }
+
+    /// A test to make sure notifications only go to the destinations they're routed to
+    #[tokio::test]
+    async fn notifies_routed_destinations() {
+        let (slack, pagerduty) = (MockDestination::new(), MockDestination::new());
+        let notifier = Notifier::builder()
+            .destination("slack", slack.endpoint())
+            .destination("pagerduty", pagerduty.endpoint())
+            .route(Route::new(["slack"]))
+            .route(Route::new(["pagerduty"]).min_severity(Severity::Critical))
+            .build()
+            .unwrap();
+
+        let results = notifier.notify(notification()).await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "slack");
+        let mut critical = notification();
+        critical.severity = Severity::Critical;
+        assert_eq!(notifier.notify(critical).await.len(), 2);
+        assert_eq!((slack.len(), pagerduty.len()), (2, 1));
+    }
+
+    /// A test to make sure secrets added by hooks are still redacted before sending
+    #[cfg(feature = "redaction")]
+    #[tokio::test]
+    async fn redacts_after_hooks() {
+        let mock = MockDestination::new();
+        let notifier = Notifier::builder()
+            .redact(crate::Redactor::new())
+            .before_send(|n| Some(n.with_context("Authorization", "Bearer abc.def.ghi")))
+            .build()
+            .unwrap();
+
+        notifier
+            .send(notification(), &mock.endpoint())
+            .await
+            .unwrap();
+        assert_eq!(mock.delivered()[0].context[1].value, "[REDACTED]");
+    }
+
+    /// A test to make sure notifier labels apply unless a notification has its own
+    #[tokio::test]
+    async fn applies_labels() {
+        let mock = MockDestination::new();
+        let notifier = Notifier::builder()
+            .labels(Labels::new("Event", "Occurred at"))
+            .build()
+            .unwrap();
+
+        notifier
+            .send(notification(), &mock.endpoint())
+            .await
+            .unwrap();
+        let own = notification().with_labels(Labels::new("Alert", "At"));
+        notifier.send(own, &mock.endpoint()).await.unwrap();
+        let delivered = mock.delivered();
+        assert_eq!(
+            delivered[0].labels,
+            Some(Labels::new("Event", "Occurred at"))
+        );
+        assert_eq!(delivered[1].labels, Some(Labels::new("Alert", "At")));
+    }
+
+    /// A test to make sure labels generated by the notifier are localized too
+    #[tokio::test]
+    async fn localizes_labels() {
+        let mock = MockDestination::new();
+        let notifier = Notifier::builder()
+            .grouping(Duration::ZERO)
+            .locale(crate::Locale::builtin("es").unwrap())
+            .build()
+            .unwrap();
+
+        notifier
+            .send(notification(), &mock.endpoint())
+            .await
+            .unwrap();
+        notifier
+            .send(notification(), &mock.endpoint())
+            .await
+            .unwrap();
+        let delivered = mock.delivered();
+        assert_eq!(delivered[0].labels, Some(Labels::new("Problema", "Fecha")));
+        let labels: Vec<_> = delivered[1]
+            .context
+            .iter()
+            .map(|c| c.label.as_str())
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                "Customer ID",
+                "Visto por primera vez",
+                "Visto por última vez",
+                "Recuento"
+            ]
+        );
+    }
+
+    /// A test to make sure a valid proxy configuration builds
+    #[test]
+    fn can_build_with_proxy() {
+        let notifier = Notifier::builder()
+            .proxy("http://proxy.internal:3128")
+            .proxy_auth("user", "pass")
+            .build();
+        assert!(notifier.is_ok())
+    }
+
+    /// A test to make sure TLS verification can be toggled off
+    #[cfg(feature = "__tls")]
+    #[test]
+    fn can_build_accepting_invalid_certs() {
+        let notifier = Notifier::builder()
+            .danger_accept_invalid_certs(true)
+            .build();
+        assert!(notifier.is_ok())
+    }
+
+    /// A test to make sure an invalid root certificate is rejected
+    #[cfg(feature = "__tls")]
+    #[test]
+    fn rejects_invalid_root_certificate() {
+        let notifier = Notifier::builder()
+            .add_root_certificate_der(b"not a certificate".to_vec())
+            .build();
+        assert!(notifier.is_err())
+    }
+
+    /// A test to make sure an invalid proxy URL is rejected
+    #[test]
+    fn rejects_invalid_proxy() {
+        let notifier = Notifier::builder().proxy("not a url").build();
+        assert!(notifier.is_err())
+    }
+
+    /// A test to make sure a provided HTTP client is used as it is
+    #[test]
+    fn can_build_with_client() {
+        let notifier = Notifier::builder()
+            .http_client(reqwest::Client::new())
+            .build();
+        assert!(notifier.is_ok());
+
+        let notifier = Notifier::builder()
+            .http_client(reqwest::Client::new())
+            .proxy("http://proxy.internal:3128")
+            .build();
+        assert!(matches!(notifier, Err(Error::Config(_))))
+    }
+}
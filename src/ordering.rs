@@ -0,0 +1,96 @@
+use crate::{Context, Notification};
+
+/// The order context entries are shown in: pinned labels first, in the
+/// order they were pinned, then the rest as added or alphabetically
+///
+/// Applies to the context of each section too.
+#[derive(Clone, Debug, Default)]
+pub struct ContextOrder {
+    pinned: Vec<String>,
+    alphabetical: bool,
+}
+impl ContextOrder {
+    /// Create a `ContextOrder` keeping context entries as added
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Always show context with a given label (ignoring case) first, after
+    /// any labels pinned before it
+    pub fn pin(mut self, label: impl Into<String>) -> Self {
+        self.pinned.push(label.into());
+        self
+    }
+
+    /// Sort context entries that aren't pinned by label, ignoring case
+    pub fn alphabetical(mut self) -> Self {
+        self.alphabetical = true;
+        self
+    }
+
+    /// Reorder the context of a `Notification`, sections included
+    pub(crate) fn apply(&self, mut notification: Notification) -> Notification {
+        self.sort(&mut notification.context);
+        for section in &mut notification.sections {
+            self.sort(&mut section.context);
+        }
+
+        notification
+    }
+
+    /// Sort context entries in place, stable so equal entries keep their order
+    fn sort(&self, context: &mut [Context]) {
+        context.sort_by_cached_key(|ctx| {
+            let pin = self
+                .pinned
+                .iter()
+                .position(|label| label.eq_ignore_ascii_case(&ctx.label))
+                .unwrap_or(self.pinned.len());
+            let label = self.alphabetical.then(|| ctx.label.to_lowercase());
+            (pin, label)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContextOrder;
+    use crate::{Notification, Section};
+
+    /// A test to make sure pinned labels come first, then the rest as added or alphabetically
+    #[test]
+    fn orders_context() {
+        let notification = Notification::new("Payment failed")
+            .with_context("Region", "eu-west-1")
+            .with_context("customer id", 42)
+            .with_context("Amount", "9.99")
+            .with_context("Order ID", 1042)
+            .with_section(
+                Section::new("Request")
+                    .with_context("Path", "/checkout")
+                    .with_context("Method", "POST"),
+            );
+        let labels = |notification: &Notification| -> Vec<String> {
+            notification
+                .context
+                .iter()
+                .map(|ctx| ctx.label.clone())
+                .collect()
+        };
+
+        let order = ContextOrder::new().pin("Order ID").pin("Customer ID");
+        let ordered = order.clone().apply(notification.clone());
+        assert_eq!(
+            labels(&ordered),
+            ["Order ID", "customer id", "Region", "Amount"]
+        );
+        assert_eq!(ordered.sections[0].context[0].label, "Path");
+
+        let ordered = order.alphabetical().apply(notification);
+        assert_eq!(
+            labels(&ordered),
+            ["Order ID", "customer id", "Amount", "Region"]
+        );
+        assert_eq!(ordered.sections[0].context[0].label, "Method");
+    }
+}
@@ -0,0 +1,62 @@
+use crate::Notification;
+use opentelemetry::trace::{SpanContext, TraceContextExt};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+impl Notification {
+    /// Add the IDs of the active OpenTelemetry trace and span as context, so
+    /// the notification links to the distributed trace
+    ///
+    /// The current `tracing` span is used when it's exported through
+    /// `tracing-opentelemetry`, otherwise the current OpenTelemetry context.
+    pub(crate) fn with_trace_context(self) -> Self {
+        match active_span() {
+            Some(span) => self
+                .with_context("trace_id", span.trace_id())
+                .with_context("span_id", span.span_id()),
+            None => self,
+        }
+    }
+}
+
+/// The context of the active span, if any
+fn active_span() -> Option<SpanContext> {
+    let from_tracing = tracing::Span::current().context();
+    [from_tracing, opentelemetry::Context::current()]
+        .into_iter()
+        .map(|cx| cx.span().span_context().clone())
+        .find(SpanContext::is_valid)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Notification;
+    use opentelemetry::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+    };
+
+    /// A test to make sure the active span's IDs are attached as context
+    #[test]
+    fn attaches_active_span() {
+        assert!(Notification::new("No trace").context.is_empty());
+
+        let span = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let _guard = opentelemetry::Context::current()
+            .with_remote_span_context(span)
+            .attach();
+
+        let notification = Notification::new("Checkout failed");
+        assert_eq!(notification.context[0].label, "trace_id");
+        assert_eq!(
+            notification.context[0].value,
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(notification.context[1].label, "span_id");
+        assert_eq!(notification.context[1].value, "00f067aa0ba902b7");
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{Context, Endpoint, Notification, Notifier, Severity};
+use std::backtrace::Backtrace;
+use std::panic::{self, PanicHookInfo};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+/// How long a panicking thread waits for its notification to be delivered
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Install a panic hook that sends a `Notification` with the panic message,
+/// location and backtrace to a given destination, before running the
+/// previously installed hook
+///
+/// Delivery blocks the panicking thread until it's done, or for at most 5
+/// seconds, so the notification isn't lost when the panic takes the process
+/// down with it, and a hung destination doesn't hang the process instead.
+pub fn install_panic_hook(notifier: Arc<Notifier>, destination: Endpoint) {
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let notification = panic_notification(info);
+
+        // Deliver from a fresh thread and runtime, since the panicking
+        // thread may already be driving a tokio runtime
+        let notifier = notifier.clone();
+        let destination = destination.clone();
+        let (done, delivered) = mpsc::channel();
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build();
+            if let Ok(runtime) = runtime {
+                let _ = runtime.block_on(async {
+                    let delivery = notifier.send(notification, &destination);
+                    tokio::time::timeout(DELIVERY_TIMEOUT, delivery).await
+                });
+            }
+            let _ = done.send(());
+        });
+        // The runtime may fail to time out a delivery blocking its thread
+        let _ = delivered.recv_timeout(DELIVERY_TIMEOUT + Duration::from_secs(1));
+
+        previous_hook(info);
+    }));
+}
+
+/// Capture a panic as a `Notification`
+fn panic_notification(info: &PanicHookInfo<'_>) -> Notification {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("Box<dyn Any>"));
+
+    let mut notification = Notification::new(format!("Panic: {message}"))
+        .with_context("Thread", thread::current().name().unwrap_or("<unnamed>"));
+    if let Some(location) = info.location() {
+        notification = notification.with_context("Location", location);
+    }
+    notification.context.push(Context::code(
+        "Backtrace",
+        Backtrace::force_capture().to_string(),
+    ));
+    notification.severity = Severity::Critical;
+
+    notification
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::install_panic_hook;
+    use crate::testing::MockDestination;
+    use crate::{ContextKind, Notifier};
+    use std::panic;
+    use std::sync::Arc;
+
+    /// A test to make sure a panic is delivered before unwinding continues
+    #[test]
+    fn notifies_on_panic() {
+        let mock = MockDestination::new();
+        let previous_hook = panic::take_hook();
+        install_panic_hook(Arc::new(Notifier::new()), mock.endpoint());
+
+        let result = panic::catch_unwind(|| panic!("Failed to capture transaction"));
+        let _ = panic::take_hook();
+        panic::set_hook(previous_hook);
+        assert!(result.is_err());
+
+        let delivered = mock.delivered();
+        let notification = delivered
+            .iter()
+            .find(|n| n.message == "Panic: Failed to capture transaction")
+            .unwrap();
+        assert!(notification
+            .context
+            .iter()
+            .any(|ctx| ctx.label == "Location" && ctx.value.contains("panic.rs")));
+        let backtrace = notification
+            .context
+            .iter()
+            .find(|ctx| ctx.label == "Backtrace")
+            .unwrap();
+        assert_eq!(backtrace.kind, ContextKind::Code);
+        assert!(!backtrace.value.starts_with("```"));
+    }
+}
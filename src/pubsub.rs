@@ -0,0 +1,144 @@
+use crate::time::Instant;
+use crate::{Error, Formatter, Notification, RenderedPayload};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Where workloads on Google Cloud get access tokens for their service account
+const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+/// How long before it expires a cached access token is refreshed
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// How notifications are published to a Google Cloud Pub/Sub topic
+///
+/// The destination is the publish URL of the topic, like
+/// `https://pubsub.googleapis.com/v1/projects/my-project/topics/alerts:publish`,
+/// authenticated with an OAuth access token through `Endpoint::bearer_auth`,
+/// or from the metadata server with `metadata_server_auth`. Each message is a
+/// serialized `Notification`, with its severity and ID as attributes.
+#[derive(Clone, Debug, Default)]
+pub struct PubSub {
+    metadata_server_auth: bool,
+    token: Arc<Mutex<Option<(String, Instant)>>>,
+}
+impl PubSub {
+    /// Create `PubSub` options, authenticated through the `Endpoint`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Authenticate with access tokens of the service account of the
+    /// workload (e.g. on GKE, Cloud Run or Compute Engine), from the
+    /// metadata server, cached until they expire
+    pub fn metadata_server_auth(mut self) -> Self {
+        self.metadata_server_auth = true;
+        self
+    }
+
+    /// The access token to publish with, if authenticating through the
+    /// metadata server
+    pub(crate) async fn access_token(&self, http_client: &Client) -> Result<Option<String>, Error> {
+        if !self.metadata_server_auth {
+            return Ok(None);
+        }
+        if let Some((token, expires)) = self.token.lock().unwrap().as_ref() {
+            if Instant::now() + REFRESH_MARGIN < *expires {
+                return Ok(Some(token.clone()));
+            }
+        }
+
+        let request = http_client
+            .get(METADATA_TOKEN_URL)
+            .header("Metadata-Flavor", "Google");
+        let body = crate::runtime::send_checked(request).await?;
+        let response: TokenResponse = serde_json::from_str(&body)
+            .map_err(|e| Error::Config(format!("unexpected metadata server token: {e}")))?;
+        let expires = Instant::now() + Duration::from_secs(response.expires_in);
+        *self.token.lock().unwrap() = Some((response.access_token.clone(), expires));
+
+        Ok(Some(response.access_token))
+    }
+}
+
+/// The parts of a metadata server token response we read
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+/// Renders notifications as Pub/Sub publish requests, as `MessageFormat::PubSub`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PubSubFormatter;
+impl Formatter for PubSubFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let payload = notification.clone().into_pubsub_payload();
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into a Pub/Sub publish request
+    /// of a single message: the serialized `Notification`
+    pub(crate) fn into_pubsub_payload(self) -> Value {
+        let mut attributes = json!({ "severity": self.severity });
+        if let Some(id) = &self.id {
+            attributes["id"] = Value::String(id.clone());
+        }
+        let data = STANDARD.encode(serde_json::to_vec(&self).unwrap_or_default());
+
+        json!({ "messages": [{ "data": data, "attributes": attributes }] })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PubSub;
+    use crate::{Endpoint, Notification, Severity};
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde_json::Value;
+
+    /// A test to make sure notifications are published serialized, with attributes
+    #[test]
+    fn can_parse_into_publish_request() {
+        let mut notification = Notification::new("Disk full")
+            .with_context("Host", "web-1")
+            .with_channel("ops");
+        notification.severity = Severity::Critical;
+        let id = notification.id.clone().unwrap();
+
+        let destination = Endpoint::new(
+            "https://pubsub.googleapis.com/v1/projects/my-project/topics/alerts:publish",
+        )
+        .pubsub(PubSub::new());
+        let payload: Value =
+            serde_json::from_str(&notification.render_for(&destination).unwrap().body).unwrap();
+        let message = &payload["messages"][0];
+        assert_eq!(message["attributes"]["severity"], "critical");
+        assert_eq!(message["attributes"]["id"], id.as_str());
+        assert!(payload.get("channel").is_none());
+
+        let data = STANDARD.decode(message["data"].as_str().unwrap()).unwrap();
+        let published: Notification = serde_json::from_slice(&data).unwrap();
+        assert_eq!(published.message, "Disk full");
+        assert_eq!(published.channel.as_deref(), Some("ops"));
+        assert_eq!(published.id, Some(id));
+    }
+
+    /// A test to make sure no token is fetched unless authenticating through the metadata server
+    #[tokio::test]
+    async fn skips_metadata_server() {
+        let token = PubSub::new()
+            .access_token(&reqwest::Client::new())
+            .await
+            .unwrap();
+        assert!(token.is_none());
+    }
+}
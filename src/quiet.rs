@@ -0,0 +1,290 @@
+use crate::time::{SystemTime, UNIX_EPOCH};
+use crate::{Endpoint, Error, Notification, Notifier, Overflow, Severity};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// How many of the most frequent held messages are listed in a digest
+const DIGEST_TOP: usize = 3;
+
+/// Hours during which notifications below `Severity::Critical` are held
+/// instead of sent, and delivered as a digest per destination once they're
+/// over by `Notifier::release_held`
+///
+/// Hours are local to a fixed offset from UTC, like `TimestampFormat::Pattern`,
+/// and may span midnight (e.g. 22:00 to 07:00).
+pub struct QuietHours {
+    start: u32,
+    end: u32,
+    utc_offset_minutes: i32,
+    below: Severity,
+    held: Mutex<Vec<(Endpoint, Notification)>>,
+}
+impl QuietHours {
+    /// Hold notifications from `start` to `end`, both like `22:00`, at a
+    /// given offset from UTC
+    ///
+    /// Returns `Error::Config` if either time is invalid.
+    pub fn new(start: &str, end: &str, utc_offset_minutes: i32) -> Result<Self, Error> {
+        Ok(Self {
+            start: minute_of_day(start)?,
+            end: minute_of_day(end)?,
+            utc_offset_minutes,
+            below: Severity::Critical,
+            held: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Hold notifications below a given severity (defaults to `Severity::Critical`)
+    pub fn below(mut self, severity: Severity) -> Self {
+        self.below = severity;
+        self
+    }
+
+    /// Hold a notification for a destination, handing it back if it's severe
+    /// enough or it isn't quiet hours
+    pub(crate) fn hold(
+        &self,
+        notification: Notification,
+        destination: &Endpoint,
+    ) -> Option<Notification> {
+        self.hold_at(notification, destination, unix_now())
+    }
+
+    /// A digest of the notifications held for each destination, once quiet
+    /// hours are over
+    pub(crate) fn release(&self) -> Vec<(Endpoint, Notification)> {
+        self.release_at(unix_now())
+    }
+
+    /// Hold a notification at a given time (seconds since the epoch)
+    fn hold_at(
+        &self,
+        notification: Notification,
+        destination: &Endpoint,
+        unix_secs: u64,
+    ) -> Option<Notification> {
+        if notification.severity >= self.below || !self.is_quiet_at(unix_secs) {
+            return Some(notification);
+        }
+        self.lock().push((destination.clone(), notification));
+        None
+    }
+
+    /// Release held notifications at a given time (seconds since the epoch)
+    fn release_at(&self, unix_secs: u64) -> Vec<(Endpoint, Notification)> {
+        if self.is_quiet_at(unix_secs) {
+            return Vec::new();
+        }
+        let held = std::mem::take(&mut *self.lock());
+
+        let mut by_destination: Vec<(Endpoint, Vec<Notification>)> = Vec::new();
+        for (destination, notification) in held {
+            match by_destination
+                .iter_mut()
+                .find(|(d, _)| d.url() == destination.url())
+            {
+                Some((_, notifications)) => notifications.push(notification),
+                None => by_destination.push((destination, vec![notification])),
+            }
+        }
+
+        by_destination
+            .into_iter()
+            .map(|(destination, notifications)| {
+                (destination, digest(&notifications, "during quiet hours"))
+            })
+            .collect()
+    }
+
+    /// Whether a given time (seconds since the epoch) falls in quiet hours
+    fn is_quiet_at(&self, unix_secs: u64) -> bool {
+        let local = unix_secs.saturating_add_signed(i64::from(self.utc_offset_minutes) * 60);
+        let minute = (local / 60 % (24 * 60)) as u32;
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+
+    /// Lock the held notifications, even if a panicking thread poisoned them
+    fn lock(&self) -> MutexGuard<'_, Vec<(Endpoint, Notification)>> {
+        self.held.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Notifier {
+    /// Send what was held back: once quiet hours are over, a digest of the
+    /// notifications held during them to each destination, and whatever the
+    /// rate limit of each destination now allows of those held over it
+    ///
+    /// Call it periodically (e.g. every minute). Returns how many
+    /// notifications (or digests) were delivered.
+    pub async fn release_held(&self) -> usize {
+        let mut released: Vec<(Endpoint, Notification)> = self
+            .quiet_hours()
+            .map(QuietHours::release)
+            .unwrap_or_default();
+        for (destination, overflow, held) in self.rate_limiter().release() {
+            match overflow {
+                Overflow::Digest => {
+                    released.push((destination, digest(&held, "over the rate limit")));
+                }
+                _ => released.extend(held.into_iter().map(|n| (destination.clone(), n))),
+            }
+        }
+
+        let mut delivered = 0;
+        for (destination, notification) in released {
+            if self.dispatch(notification, &destination).await.is_ok() {
+                delivered += 1;
+            }
+        }
+
+        delivered
+    }
+}
+
+/// Parse a time of day like `07:30` into minutes since midnight
+fn minute_of_day(time: &str) -> Result<u32, Error> {
+    let invalid = || {
+        Error::Config(format!(
+            "invalid time of day `{time}`, expected like `07:30`"
+        ))
+    };
+    let (hours, minutes) = time.split_once(':').ok_or_else(invalid)?;
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+
+    Ok(hours * 60 + minutes)
+}
+
+/// Seconds since the epoch
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Summarize notifications held for a reason (e.g. "during quiet hours"),
+/// listing the most frequent messages
+fn digest(held: &[Notification], why: &str) -> Notification {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for notification in held {
+        *counts.entry(&notification.message).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts.truncate(DIGEST_TOP);
+
+    let top: Vec<_> = counts
+        .iter()
+        .map(|(message, count)| format!("`{message}` ({count})"))
+        .collect();
+    let mut notification = Notification::new(format!(
+        "{} notifications held {why}, top {}: {}",
+        held.len(),
+        counts.len(),
+        top.join(", ")
+    ));
+    notification.severity = held
+        .iter()
+        .map(|n| n.severity)
+        .max()
+        .unwrap_or(Severity::Info);
+
+    notification
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unix_now, QuietHours};
+    use crate::testing::MockDestination;
+    use crate::{Notification, Notifier, Severity};
+
+    /// A test to make sure quiet hours can span midnight in a given timezone
+    #[test]
+    fn checks_local_time() {
+        // 2024-01-19 23:30 UTC
+        let late = 1_705_707_000;
+        let overnight = QuietHours::new("22:00", "07:00", 0).unwrap();
+        assert!(overnight.is_quiet_at(late));
+        assert!(!overnight.is_quiet_at(late - 2 * 3600));
+
+        // 18:30 in New York
+        let new_york = QuietHours::new("22:00", "07:00", -300).unwrap();
+        assert!(!new_york.is_quiet_at(late));
+        let lunch = QuietHours::new("12:00", "13:00", -300).unwrap();
+        assert!(lunch.is_quiet_at(late - 6 * 3600));
+
+        assert!(QuietHours::new("24:00", "07:00", 0).is_err());
+        assert!(QuietHours::new("7", "07:00", 0).is_err());
+    }
+
+    /// A test to make sure notifications held by a `Notifier` are counted in its metrics
+    #[tokio::test]
+    async fn counts_held() {
+        // Quiet from 11:00 to 13:00 at an offset where it's now noon
+        let minute = (unix_now() / 60 % (24 * 60)) as i32;
+        let quiet_hours = QuietHours::new("11:00", "13:00", 12 * 60 - minute).unwrap();
+        let mock = MockDestination::new();
+        let notifier = Notifier::builder()
+            .quiet_hours(quiet_hours)
+            .build()
+            .unwrap();
+
+        let result = notifier
+            .send(Notification::new("Disk full"), &mock.endpoint())
+            .await;
+        assert!(matches!(result, Ok(None)));
+        let mut critical = Notification::new("Database down");
+        critical.severity = Severity::Critical;
+        notifier.send(critical, &mock.endpoint()).await.unwrap();
+
+        let metrics = notifier.metrics();
+        assert_eq!((metrics.sent, metrics.suppressed), (1, 1));
+        assert_eq!(mock.len(), 1);
+    }
+
+    /// A test to make sure only sub-critical notifications are held, and
+    /// summarized per destination in the morning
+    #[test]
+    fn holds_and_summarizes() {
+        // 2024-01-19 23:30 and 2024-01-20 07:30 UTC
+        let (night, morning) = (1_705_707_000, 1_705_735_800);
+        let (slack, email) = (MockDestination::new(), MockDestination::new());
+        let (slack, email) = (
+            slack.endpoint_at("mock://slack"),
+            email.endpoint_at("mock://email"),
+        );
+        let quiet_hours = QuietHours::new("22:00", "07:00", 0).unwrap();
+
+        let mut critical = Notification::new("Database down");
+        critical.severity = Severity::Critical;
+        assert!(quiet_hours.hold_at(critical, &slack, night).is_some());
+        for message in ["Disk full", "Slow query", "Disk full"] {
+            let notification = Notification::new(message);
+            assert!(quiet_hours
+                .hold_at(notification.clone(), &slack, night)
+                .is_none());
+            assert!(quiet_hours.hold_at(notification, &slack, morning).is_some());
+        }
+        assert!(quiet_hours
+            .hold_at(Notification::new("Backup done"), &email, night)
+            .is_none());
+
+        assert!(quiet_hours.release_at(night).is_empty());
+        let digests = quiet_hours.release_at(morning);
+        assert_eq!(digests.len(), 2);
+        assert_eq!(
+            digests[0].1.message,
+            "3 notifications held during quiet hours, top 2: `Disk full` (2), `Slow query` (1)"
+        );
+        assert_eq!(digests[1].0.url(), "mock://email");
+        assert!(quiet_hours.release_at(morning).is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+use crate::metrics::Metrics;
+use crate::time::Instant;
+use crate::{Endpoint, Notification};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// The most notifications held per destination, the oldest are dropped past it
+const MAX_HELD: usize = 1000;
+
+/// What happens to notifications over the `RateLimit` of a destination
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Overflow {
+    /// Drop them, nothing is sent
+    #[default]
+    Drop,
+    /// Hold them, in order, and send them as the limit allows
+    Queue,
+    /// Hold them and send a single summary of them as the limit allows
+    Digest,
+}
+
+/// How many notifications a destination takes in a period (e.g. Slack 1 per
+/// second, SMS 5 per hour), set with `Endpoint::rate_limit`, for when
+/// downstream quotas differ
+///
+/// Up to `max` are sent in a burst, then one more for every `per / max`
+/// that passes. Held notifications are sent by `Notifier::release_held`.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimit {
+    max: u32,
+    per: Duration,
+    overflow: Overflow,
+}
+impl RateLimit {
+    /// Allow `max` notifications every `per`, dropping any more
+    pub fn new(max: u32, per: Duration) -> Self {
+        Self {
+            max: max.max(1),
+            per,
+            overflow: Overflow::default(),
+        }
+    }
+
+    /// What to do with notifications over the limit (defaults to `Overflow::Drop`)
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// How long it takes to earn back a single notification
+    fn refill_interval(&self) -> Duration {
+        self.per / self.max
+    }
+}
+
+/// Notifications a destination took and holds, by the state of its limit
+struct Bucket {
+    destination: Endpoint,
+    tokens: u32,
+    last_refill: Instant,
+    held: VecDeque<Notification>,
+}
+impl Bucket {
+    /// Earn back a token for every refill interval that passed
+    fn refill(&mut self, limit: &RateLimit) {
+        let interval = limit.refill_interval();
+        if interval.is_zero() {
+            self.tokens = limit.max;
+            return;
+        }
+        let refills = self.last_refill.elapsed().as_nanos() / interval.as_nanos();
+        if refills > 0 {
+            let refills = u32::try_from(refills).unwrap_or(u32::MAX);
+            self.tokens = self.tokens.saturating_add(refills).min(limit.max);
+            self.last_refill = Instant::now();
+        }
+    }
+
+    /// Take a token, if there's one left
+    fn take(&mut self) -> bool {
+        let available = self.tokens > 0;
+        if available {
+            self.tokens -= 1;
+        }
+        available
+    }
+}
+
+/// Applies the `RateLimit` of each destination, keeping state per URL
+#[derive(Default)]
+pub(crate) struct RateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+impl RateLimiter {
+    /// Decide whether to send a notification to a destination now, handing
+    /// it back if so, otherwise dropping (counted in `metrics`) or holding it
+    pub(crate) fn admit(
+        &self,
+        notification: Notification,
+        destination: &Endpoint,
+        metrics: &Metrics,
+    ) -> Option<Notification> {
+        let Some(limit) = destination.rate_limit_config() else {
+            return Some(notification);
+        };
+
+        let mut buckets = self.lock();
+        let bucket = buckets
+            .entry(destination.url().to_string())
+            .or_insert_with(|| Bucket {
+                destination: destination.clone(),
+                tokens: limit.max,
+                last_refill: Instant::now(),
+                held: VecDeque::new(),
+            });
+        bucket.refill(limit);
+        if bucket.take() {
+            return Some(notification);
+        }
+
+        if limit.overflow == Overflow::Drop {
+            metrics.record_drop();
+            return None;
+        }
+        if bucket.held.len() == MAX_HELD {
+            bucket.held.pop_front();
+            metrics.record_drop();
+        }
+        bucket.held.push_back(notification);
+        None
+    }
+
+    /// Take the held notifications each destination's limit now allows
+    /// sending, each queued one on its own or all digested ones together
+    pub(crate) fn release(&self) -> Vec<(Endpoint, Overflow, Vec<Notification>)> {
+        let mut released = Vec::new();
+        for bucket in self.lock().values_mut() {
+            let Some(limit) = bucket.destination.rate_limit_config().copied() else {
+                continue;
+            };
+            bucket.refill(&limit);
+            match limit.overflow {
+                Overflow::Drop => {}
+                Overflow::Queue => {
+                    while !bucket.held.is_empty() && bucket.take() {
+                        let notification = bucket.held.pop_front().into_iter().collect();
+                        released.push((bucket.destination.clone(), Overflow::Queue, notification));
+                    }
+                }
+                Overflow::Digest => {
+                    if !bucket.held.is_empty() && bucket.take() {
+                        let held = std::mem::take(&mut bucket.held).into();
+                        released.push((bucket.destination.clone(), Overflow::Digest, held));
+                    }
+                }
+            }
+        }
+
+        released
+    }
+
+    /// Lock the buckets, even if a panicking thread poisoned them
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Bucket>> {
+        self.buckets.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Overflow, RateLimit, RateLimiter};
+    use crate::metrics::Metrics;
+    use crate::{Endpoint, Notification};
+    use std::time::Duration;
+
+    /// A test to make sure each destination gets its own limit
+    #[test]
+    fn limits_per_destination() {
+        let limiter = RateLimiter::default();
+        let metrics = Metrics::default();
+        let slack = Endpoint::new("https://hooks.slack.com/services/T0/B0/X")
+            .rate_limit(RateLimit::new(2, Duration::from_secs(3600)));
+        let sms = Endpoint::new("https://sms.internal/send")
+            .rate_limit(RateLimit::new(1, Duration::from_secs(3600)));
+
+        let admitted = |destination: &Endpoint| {
+            limiter
+                .admit(Notification::new("Disk full"), destination, &metrics)
+                .is_some()
+        };
+        assert!(admitted(&slack));
+        assert!(admitted(&sms));
+        assert!(admitted(&slack));
+        assert!(!admitted(&slack));
+        assert!(!admitted(&sms));
+        assert!(admitted(&Endpoint::new("https://unlimited.internal")));
+
+        // Dropped notifications aren't held
+        assert!(limiter.release().is_empty());
+        assert_eq!(metrics.snapshot().dropped, 2);
+    }
+
+    /// A test to make sure queued and digested notifications are released as the limit allows
+    #[test]
+    fn releases_held() {
+        let limiter = RateLimiter::default();
+        let metrics = Metrics::default();
+        let limit = RateLimit::new(1, Duration::from_millis(50));
+        let queue =
+            Endpoint::new("https://queue.internal").rate_limit(limit.overflow(Overflow::Queue));
+        let digest =
+            Endpoint::new("https://digest.internal").rate_limit(limit.overflow(Overflow::Digest));
+        for message in ["First", "Second", "Third"] {
+            limiter.admit(Notification::new(message), &queue, &metrics);
+            limiter.admit(Notification::new(message), &digest, &metrics);
+        }
+        assert!(limiter.release().is_empty());
+        assert_eq!(metrics.snapshot().dropped, 0);
+
+        std::thread::sleep(Duration::from_millis(60));
+        let mut released = limiter.release();
+        released.sort_by_key(|(destination, _, _)| destination.url().to_string());
+        let messages = |held: &[Notification]| -> Vec<String> {
+            held.iter().map(|n| n.message.clone()).collect()
+        };
+        assert_eq!(released.len(), 2);
+        assert_eq!(released[0].1, Overflow::Digest);
+        assert_eq!(messages(&released[0].2), ["Second", "Third"]);
+        assert_eq!(released[1].1, Overflow::Queue);
+        assert_eq!(messages(&released[1].2), ["Second"]);
+    }
+}
@@ -0,0 +1,29 @@
+use reqwest::StatusCode;
+
+/// Details of a delivered `Notification`, for logging, threading or auditing
+#[derive(Clone, Debug)]
+pub struct DeliveryReceipt {
+    /// The HTTP status the destination responded with
+    pub status: StatusCode,
+    /// The channel the message was posted to (Slack Web API only)
+    pub channel: Option<String>,
+    /// The timestamp identifying the posted message, used to reply
+    /// in a thread (Slack Web API only)
+    pub ts: Option<String>,
+    /// A link to the posted message (Slack Web API only) or opened issue (GitLab)
+    pub permalink: Option<String>,
+    /// The ID of the delivered `Notification`
+    pub notification_id: Option<String>,
+}
+impl DeliveryReceipt {
+    /// Create a `DeliveryReceipt` with only an HTTP status
+    pub(crate) fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            channel: None,
+            ts: None,
+            permalink: None,
+            notification_id: None,
+        }
+    }
+}
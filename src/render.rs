@@ -0,0 +1,145 @@
+#[cfg(any(feature = "home-assistant", feature = "bark", feature = "victorops"))]
+use crate::{Context, ContextKind, Notification};
+use crate::{Error, OversizePolicy};
+#[cfg(any(feature = "slack", feature = "discord", feature = "gitlab"))]
+use serde_json::Value;
+
+/// Render the details of a `Notification` as plain text without markup, for
+/// destinations titling it with the message: its timestamp, then its context,
+/// sections and attachments line by line, fit within a given number of characters
+#[cfg(any(feature = "home-assistant", feature = "bark", feature = "victorops"))]
+pub(crate) fn plain_text(
+    notification: &Notification,
+    limit: usize,
+    policy: OversizePolicy,
+) -> Result<String, Error> {
+    let labels = notification.labels.clone().unwrap_or_default();
+    let mut lines = vec![format!("{}: {}", labels.timestamp, notification.timestamp)];
+    lines.extend(notification.context.iter().map(context_line));
+    for section in &notification.sections {
+        lines.push(format!("\n{}", section.title));
+        lines.extend(section.context.iter().map(context_line));
+    }
+    for attachment in &notification.attachments {
+        lines.push(format!("\n{}\n{}", attachment.filename, attachment.content));
+    }
+
+    let mut text = lines.join("\n");
+    fit_text(&mut text, limit, policy)?;
+
+    Ok(text)
+}
+
+/// Render a `Context` as a line of plain text
+#[cfg(any(feature = "home-assistant", feature = "bark", feature = "victorops"))]
+fn context_line(ctx: &Context) -> String {
+    match &ctx.kind {
+        ContextKind::Text | ContextKind::Code => format!("{}: {}", ctx.label, ctx.value),
+        ContextKind::Link { text } => format!("{}: {text} ({})", ctx.label, ctx.value),
+    }
+}
+
+/// Fit a text value within a limit of characters, truncating it
+/// or rejecting it depending on a given policy
+#[cfg(any(feature = "slack", feature = "discord", feature = "gitlab"))]
+pub(crate) fn fit(text: &mut Value, limit: usize, policy: OversizePolicy) -> Result<(), Error> {
+    match text {
+        Value::String(text) => fit_text(text, limit, policy),
+        _ => Ok(()),
+    }
+}
+
+/// Fit text within a limit of characters, like `fit`
+pub(crate) fn fit_text(
+    text: &mut String,
+    limit: usize,
+    policy: OversizePolicy,
+) -> Result<(), Error> {
+    let size = text.chars().count();
+    if size <= limit {
+        return Ok(());
+    }
+
+    match policy {
+        OversizePolicy::Reject => Err(Error::PayloadTooLarge { limit, size }),
+        OversizePolicy::Truncate => {
+            // Leave room for the ellipsis
+            if let Some((idx, _)) = text.char_indices().nth(limit - 1) {
+                text.truncate(idx);
+            }
+            text.push('…');
+            Ok(())
+        }
+    }
+}
+
+/// Escape the characters HTML reserves
+#[cfg(any(feature = "email", feature = "gitlab", feature = "telegram"))]
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The name of a variable (or attribute) for a context label, in snake case
+#[cfg(any(feature = "slack", feature = "markers"))]
+pub(crate) fn variable_name(label: &str) -> String {
+    label
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(any(feature = "home-assistant", feature = "bark", feature = "victorops"))]
+    use super::plain_text;
+    #[cfg(any(feature = "slack", feature = "markers"))]
+    use super::variable_name;
+    #[cfg(any(feature = "home-assistant", feature = "bark", feature = "victorops"))]
+    use crate::{Context, Error, Notification, OversizePolicy, Section};
+
+    /// A test to make sure details are rendered line by line, fit within the limit
+    #[cfg(any(feature = "home-assistant", feature = "bark", feature = "victorops"))]
+    #[test]
+    fn can_render_plain_text() {
+        let mut notification = Notification::new("Deploy failed")
+            .with_context("Service", "api")
+            .with_section(Section::new("Build").with_context("Commit", "4f2a9c1"))
+            .with_attachment("build.log", "x".repeat(100));
+        notification.timestamp = String::from("2024-01-19 19:26:20.022233");
+        notification
+            .context
+            .push(Context::link("Run", "https://ci.example.com/runs/7", "#7"));
+
+        let text = plain_text(&notification, 1000, OversizePolicy::Reject).unwrap();
+        assert!(text.starts_with(
+            "Timestamp: 2024-01-19 19:26:20.022233\n\
+             Service: api\n\
+             Run: #7 (https://ci.example.com/runs/7)\n\
+             \nBuild\nCommit: 4f2a9c1\n\
+             \nbuild.log\nxxx"
+        ));
+
+        let text = plain_text(&notification, 100, OversizePolicy::Truncate).unwrap();
+        assert_eq!(text.chars().count(), 100);
+        assert!(text.ends_with('…'));
+        assert!(matches!(
+            plain_text(&notification, 100, OversizePolicy::Reject),
+            Err(Error::PayloadTooLarge { limit: 100, .. })
+        ));
+    }
+
+    /// A test to make sure labels are named in snake case
+    #[cfg(any(feature = "slack", feature = "markers"))]
+    #[test]
+    fn names_variables() {
+        assert_eq!(variable_name("Order ID"), "order_id");
+        assert_eq!(variable_name("  p99 latency (ms) "), "p99_latency_ms");
+        assert_eq!(variable_name("Caused-By"), "caused_by");
+        assert_eq!(variable_name("--"), "");
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{Notification, Severity};
+
+/// Sends notifications matching every one of its conditions to a set of
+/// named destinations, see `NotifierBuilder::route`
+#[derive(Clone, Debug)]
+pub struct Route {
+    destinations: Vec<String>,
+    min_severity: Option<Severity>,
+    message_contains: Option<String>,
+    message_pattern: Option<String>,
+    context: Vec<(String, Option<String>)>,
+}
+impl Route {
+    /// Create a `Route` to the destinations with the given names,
+    /// matching every notification until conditions are added
+    pub fn new<I, S>(destinations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            destinations: destinations.into_iter().map(Into::into).collect(),
+            min_severity: None,
+            message_contains: None,
+            message_pattern: None,
+            context: Vec::new(),
+        }
+    }
+
+    /// Only match notifications at or above a given severity
+    pub fn min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    /// Only match notifications whose message contains the given text
+    pub fn message_contains(mut self, text: impl Into<String>) -> Self {
+        self.message_contains = Some(text.into());
+        self
+    }
+
+    /// Only match notifications whose whole message matches a glob pattern,
+    /// where `*` matches any text and `?` any single character
+    /// (e.g. `"Payment*failed"`)
+    pub fn message_matches(mut self, pattern: impl Into<String>) -> Self {
+        self.message_pattern = Some(pattern.into());
+        self
+    }
+
+    /// Only match notifications with a context entry labeled `label`
+    /// (ignoring case), in their context or any section
+    pub fn has_context(mut self, label: impl Into<String>) -> Self {
+        self.context.push((label.into(), None));
+        self
+    }
+
+    /// Only match notifications with a context entry labeled `label`
+    /// (ignoring case) holding exactly `value`, e.g. `("Team", "payments")`
+    pub fn context_equals(mut self, label: impl Into<String>, value: impl Into<String>) -> Self {
+        self.context.push((label.into(), Some(value.into())));
+        self
+    }
+
+    /// The names of the destinations the `Route` sends to
+    pub fn destinations(&self) -> &[String] {
+        &self.destinations
+    }
+
+    /// Whether a `Notification` matches every condition of the `Route`
+    pub(crate) fn matches(&self, notification: &Notification) -> bool {
+        self.min_severity
+            .is_none_or(|min| notification.severity >= min)
+            && self
+                .message_contains
+                .as_ref()
+                .is_none_or(|text| notification.message.contains(text.as_str()))
+            && self
+                .message_pattern
+                .as_ref()
+                .is_none_or(|pattern| glob_matches(pattern, &notification.message))
+            && self.context.iter().all(|(label, value)| {
+                let mut contexts = notification
+                    .context
+                    .iter()
+                    .chain(notification.sections.iter().flat_map(|s| &s.context));
+                contexts.any(|ctx| {
+                    ctx.label.eq_ignore_ascii_case(label)
+                        && value.as_ref().is_none_or(|value| ctx.value == *value)
+                })
+            })
+    }
+}
+
+/// Whether the whole of some text matches a glob pattern, where `*`
+/// matches any text and `?` any single character
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was, and the text it's matched up to, to backtrack to
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_matches;
+    use crate::{Context, ContextKind, Notification, Route, Section, Severity};
+
+    /// A test to make sure a route only matches when every condition does
+    #[test]
+    fn matches_every_condition() {
+        let route = Route::new(["pagerduty"])
+            .min_severity(Severity::Critical)
+            .message_contains("Payment");
+        let mut notification = Notification {
+            message: String::from("Payment Proccessing Error: Failed to capture transaction"),
+            timestamp: String::from("2024-01-18 21:06:05.778504"),
+            context: vec![Context {
+                label: String::from("Customer ID"),
+                value: String::from("0"),
+                kind: ContextKind::Text,
+            }],
+            severity: Severity::Error,
+            idempotency_key: None,
+            fingerprint: None,
+            sections: Vec::new(),
+            attachments: Vec::new(),
+            labels: None,
+            channel: None,
+            id: None,
+        };
+
+        assert!(!route.matches(&notification));
+        notification.severity = Severity::Critical;
+        assert!(route.matches(&notification));
+        notification.message = String::from("External API Error: Could not find API Keys");
+        assert!(!route.matches(&notification));
+    }
+    /// A test to make sure routes match on message patterns and context
+    #[test]
+    fn matches_pattern_and_context() {
+        let route = Route::new(["payments-alerts"])
+            .message_matches("Payment*failed")
+            .context_equals("team", "payments")
+            .has_context("Order ID");
+        let notification = Notification::new("Payment capture failed")
+            .with_context("Team", "payments")
+            .with_section(Section::new("Order").with_context("Order ID", "1042"));
+        assert!(route.matches(&notification));
+
+        let other_team = Notification::new("Payment capture failed")
+            .with_context("Team", "infra")
+            .with_context("Order ID", "1042");
+        assert!(!route.matches(&other_team));
+        let other_message = Notification::new("Payment captured")
+            .with_context("Team", "payments")
+            .with_context("Order ID", "1042");
+        assert!(!route.matches(&other_message));
+    }
+
+    /// A test to make sure glob patterns match the whole text
+    #[test]
+    fn matches_globs() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("db-?", "db-1"));
+        assert!(glob_matches("*timeout*", "Upstream timeout after 30s"));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(!glob_matches("timeout", "Upstream timeout"));
+        assert!(!glob_matches("a*b", "aXbYc"));
+    }
+}
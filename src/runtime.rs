@@ -0,0 +1,111 @@
+// `reqwest` needs a tokio runtime to drive its connections and timers. With
+// the `runtime-agnostic` feature, requests made outside of one (e.g. from
+// async-std, smol or a plain `block_on`) run on a small background runtime
+// instead, so callers can await them from any executor.
+
+use reqwest::{RequestBuilder, StatusCode};
+use std::future::Future;
+
+/// Send a request and read its body, returning the status and body
+pub(crate) async fn send(request: RequestBuilder) -> Result<(StatusCode, String), reqwest::Error> {
+    on_tokio(async move {
+        let response = request.send().await?;
+        let status = response.status();
+        Ok((status, response.text().await?))
+    })
+    .await
+}
+
+/// Send a request and read its body, failing on error statuses
+pub(crate) async fn send_checked(request: RequestBuilder) -> Result<String, reqwest::Error> {
+    on_tokio(async move { request.send().await?.error_for_status()?.text().await }).await
+}
+
+/// Run a future on the tokio runtime the caller is on, or the background
+/// runtime if there isn't one
+#[cfg(feature = "runtime-agnostic")]
+async fn on_tokio<F>(future: F) -> F::Output
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return future.await;
+    }
+    // The task only fails by panicking, which carries on to the caller
+    match background().spawn(future).await {
+        Ok(output) => output,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+/// Run a future on the tokio runtime the caller is on
+#[cfg(not(feature = "runtime-agnostic"))]
+async fn on_tokio<F: Future>(future: F) -> F::Output {
+    future.await
+}
+
+/// The background runtime, started on first use on a thread of its own
+#[cfg(feature = "runtime-agnostic")]
+fn background() -> &'static tokio::runtime::Handle {
+    static HANDLE: std::sync::OnceLock<tokio::runtime::Handle> = std::sync::OnceLock::new();
+    HANDLE.get_or_init(|| {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("the background runtime should start");
+        let handle = runtime.handle().clone();
+        std::thread::Builder::new()
+            .name(String::from("dev-notify-runtime"))
+            .spawn(move || runtime.block_on(std::future::pending::<()>()))
+            .expect("the background runtime thread should start");
+        handle
+    })
+}
+
+#[cfg(all(test, feature = "runtime-agnostic"))]
+mod tests {
+    use crate::testing::MockDestination;
+    use crate::{Notification, Notifier};
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    /// Wakes an executor by unparking its thread
+    struct ThreadWaker(Thread);
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// A minimal executor without tokio, like those of other runtimes
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    /// A test to make sure requests can be awaited outside of a tokio runtime
+    #[test]
+    fn sends_without_tokio() {
+        let notifier = Notifier::new();
+        let result = block_on(notifier.send(
+            Notification::new("Disk full"),
+            &crate::Endpoint::new("http://127.0.0.1:1/notify"),
+        ));
+        assert!(matches!(result, Err(crate::Error::Http(e)) if e.is_connect()));
+
+        let mock = MockDestination::new();
+        let result = block_on(notifier.send(Notification::new("Disk full"), &mock.endpoint()));
+        assert!(result.is_ok());
+    }
+}
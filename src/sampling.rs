@@ -0,0 +1,137 @@
+use crate::idle::{IdleMap, IDLE_TIMEOUT};
+use crate::time::Instant;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How to sample notifications sharing a fingerprint (or message, without
+/// one), so a runaway error loop doesn't flood a destination
+#[derive(Clone, Copy, Debug)]
+pub enum SamplingPolicy {
+    /// Send 1 in every `n` notifications, starting with the first
+    OneIn(u32),
+    /// Send bursts of up to `capacity` notifications, then one
+    /// more for every `refill_interval` that passes
+    TokenBucket {
+        capacity: u32,
+        refill_interval: Duration,
+    },
+}
+
+/// Sampling state for notifications sharing a fingerprint
+struct SampleState {
+    seen: u64,
+    tokens: u32,
+    last_refill: Instant,
+    suppressed: u64,
+}
+
+/// Applies a `SamplingPolicy`, keeping state per fingerprint
+pub(crate) struct Sampler {
+    policy: SamplingPolicy,
+    states: Mutex<IdleMap<SampleState>>,
+}
+impl Sampler {
+    /// Create a `Sampler` for a given policy
+    ///
+    /// State of fingerprints unseen for an hour (or as long as a token
+    /// bucket takes to refill, if longer) is forgotten.
+    pub(crate) fn new(policy: SamplingPolicy) -> Self {
+        let idle_timeout = match policy {
+            SamplingPolicy::OneIn(_) => IDLE_TIMEOUT,
+            SamplingPolicy::TokenBucket {
+                capacity,
+                refill_interval,
+            } => IDLE_TIMEOUT.max(refill_interval.saturating_mul(capacity)),
+        };
+        Self {
+            policy,
+            states: Mutex::new(IdleMap::new(idle_timeout)),
+        }
+    }
+
+    /// Decide whether to send a notification with a given fingerprint
+    ///
+    /// Returns the number of notifications suppressed since the last one
+    /// sent, or `None` if this notification should be suppressed too.
+    pub(crate) fn admit(&self, fingerprint: &str) -> Option<u64> {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        let state = states.entry(fingerprint, || SampleState {
+            seen: 0,
+            tokens: match self.policy {
+                SamplingPolicy::OneIn(_) => 0,
+                SamplingPolicy::TokenBucket { capacity, .. } => capacity,
+            },
+            last_refill: Instant::now(),
+            suppressed: 0,
+        });
+
+        let admitted = match self.policy {
+            SamplingPolicy::OneIn(n) => {
+                let admitted = state.seen.is_multiple_of(u64::from(n.max(1)));
+                state.seen += 1;
+                admitted
+            }
+            SamplingPolicy::TokenBucket {
+                capacity,
+                refill_interval,
+            } => {
+                // Refill a token for every full interval that has passed
+                if refill_interval.is_zero() {
+                    state.tokens = capacity;
+                } else {
+                    let refills =
+                        state.last_refill.elapsed().as_nanos() / refill_interval.as_nanos();
+                    if refills > 0 {
+                        let refills = u32::try_from(refills).unwrap_or(u32::MAX);
+                        state.tokens = state.tokens.saturating_add(refills).min(capacity);
+                        state.last_refill = Instant::now();
+                    }
+                }
+
+                if state.tokens > 0 {
+                    state.tokens -= 1;
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+
+        if admitted {
+            Some(std::mem::take(&mut state.suppressed))
+        } else {
+            state.suppressed += 1;
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sampler, SamplingPolicy};
+    use std::time::Duration;
+
+    /// A test to make sure 1 in N sampling sends the first of every N
+    #[test]
+    fn samples_one_in_n() {
+        let sampler = Sampler::new(SamplingPolicy::OneIn(3));
+        let admitted: Vec<Option<u64>> = (0..7).map(|_| sampler.admit("error")).collect();
+        assert_eq!(
+            admitted,
+            vec![Some(0), None, None, Some(2), None, None, Some(2)]
+        )
+    }
+
+    /// A test to make sure a token bucket allows a burst then suppresses
+    #[test]
+    fn samples_with_token_bucket() {
+        let sampler = Sampler::new(SamplingPolicy::TokenBucket {
+            capacity: 2,
+            refill_interval: Duration::from_secs(3600),
+        });
+        assert_eq!(sampler.admit("error"), Some(0));
+        assert_eq!(sampler.admit("error"), Some(0));
+        assert_eq!(sampler.admit("error"), None);
+        assert_eq!(sampler.admit("other error"), Some(0));
+    }
+}
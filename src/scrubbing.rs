@@ -0,0 +1,146 @@
+use crate::{Context, Notification};
+use sha2::{Digest, Sha256};
+
+/// How many characters of a hash are kept, enough to tell values apart
+const HASH_LENGTH: usize = 16;
+
+/// How many trailing characters `Scrub::Mask` keeps of values that aren't emails
+const MASK_KEEP: usize = 4;
+
+/// How the value of a context entry holding PII is scrubbed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scrub {
+    /// Replace the value with a (salted) SHA-256 hash, like `sha256:<hex>`,
+    /// so occurrences of the same value can still be correlated
+    Hash,
+    /// Partially mask the value, keeping the first character and domain of
+    /// emails (`j***@example.com`) or the last 4 characters of anything else
+    Mask,
+    /// Remove the context entry altogether
+    Remove,
+}
+
+/// Scrubs customer PII out of notifications by context label, e.g. hashing
+/// anything labeled "Email" and masking anything labeled "Phone", to keep it
+/// out of chat tools
+///
+/// Labels are matched ignoring case, in context and in sections alike.
+#[derive(Clone, Debug, Default)]
+pub struct Scrubber {
+    rules: Vec<(String, Scrub)>,
+    salt: String,
+}
+impl Scrubber {
+    /// Create a `Scrubber` without any rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scrub the value of every context entry with a given label
+    pub fn label(mut self, label: impl Into<String>, scrub: Scrub) -> Self {
+        self.rules.push((label.into(), scrub));
+        self
+    }
+
+    /// Salt hashed values, so they can't be matched against hashes of known
+    /// values (e.g. a list of customer emails)
+    pub fn salt(mut self, salt: impl Into<String>) -> Self {
+        self.salt = salt.into();
+        self
+    }
+
+    /// Scrub the context of a `Notification`
+    pub(crate) fn scrub(&self, mut notification: Notification) -> Notification {
+        self.scrub_context(&mut notification.context);
+        for section in &mut notification.sections {
+            self.scrub_context(&mut section.context);
+        }
+
+        notification
+    }
+
+    /// Scrub context entries in place, removing those that should be removed
+    fn scrub_context(&self, context: &mut Vec<Context>) {
+        context.retain_mut(|ctx| {
+            let rule = self
+                .rules
+                .iter()
+                .find(|(label, _)| label.eq_ignore_ascii_case(&ctx.label));
+            match rule {
+                Some((_, Scrub::Hash)) => ctx.value = self.hash(&ctx.value),
+                Some((_, Scrub::Mask)) => ctx.value = mask(&ctx.value),
+                Some((_, Scrub::Remove)) => return false,
+                None => {}
+            }
+            true
+        });
+    }
+
+    /// A salted hash of a value
+    fn hash(&self, value: &str) -> String {
+        let hash: String = Sha256::new()
+            .chain_update(self.salt.as_bytes())
+            .chain_update(value.as_bytes())
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        format!("sha256:{}", &hash[..HASH_LENGTH])
+    }
+}
+
+/// Partially mask a value
+fn mask(value: &str) -> String {
+    if let Some((local, domain)) = value.split_once('@') {
+        let first: String = local.chars().take(1).collect();
+        return format!("{first}***@{domain}");
+    }
+
+    let length = value.chars().count();
+    if length <= MASK_KEEP {
+        return "*".repeat(length);
+    }
+    let kept: String = value.chars().skip(length - MASK_KEEP).collect();
+    format!("{}{kept}", "*".repeat(length - MASK_KEEP))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scrub, Scrubber};
+    use crate::{Notification, Section};
+
+    /// A test to make sure each rule scrubs the context entries with its label
+    #[test]
+    fn scrubs_by_label() {
+        let scrubber = Scrubber::new()
+            .label("Email", Scrub::Mask)
+            .label("phone", Scrub::Mask)
+            .label("Customer", Scrub::Hash)
+            .label("Address", Scrub::Remove);
+        let notification = Notification::new("Checkout failed")
+            .with_context("email", "jane.doe@example.com")
+            .with_context("Customer", "Jane Doe")
+            .with_context("Address", "1 Main St")
+            .with_context("Order", "1042")
+            .with_section(Section::new("Contact").with_context("Phone", "+1 555 010 4567"));
+
+        let scrubbed = scrubber.scrub(notification);
+        let values: Vec<_> = scrubbed.context.iter().map(|c| c.value.as_str()).collect();
+        assert_eq!(values[0], "j***@example.com");
+        assert!(values[1].starts_with("sha256:"));
+        assert_eq!(values[1].len(), "sha256:".len() + 16);
+        assert_eq!(values[2], "1042");
+        assert_eq!(scrubbed.sections[0].context[0].value, "***********4567");
+    }
+
+    /// A test to make sure hashes are stable and depend on the salt
+    #[test]
+    fn hashes_with_salt() {
+        let unsalted = Scrubber::new();
+        let salted = Scrubber::new().salt("pepper");
+        assert_eq!(unsalted.hash("Jane Doe"), unsalted.hash("Jane Doe"));
+        assert_ne!(unsalted.hash("Jane Doe"), salted.hash("Jane Doe"));
+        // echo -n 'Jane Doe' | sha256sum
+        assert_eq!(unsalted.hash("Jane Doe"), "sha256:01332c876518a793");
+    }
+}
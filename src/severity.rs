@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How severe a `Notification` is, ordered from least to most severe
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Debug,
+    Info,
+    Warning,
+    #[default]
+    Error,
+    Critical,
+}
+
+impl FromStr for Severity {
+    type Err = ParseSeverityError;
+
+    /// Parse a severity from its name, ignoring case
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "debug" => Ok(Self::Debug),
+            "info" => Ok(Self::Info),
+            "warning" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            "critical" => Ok(Self::Critical),
+            _ => Err(ParseSeverityError(name.to_string())),
+        }
+    }
+}
+
+impl Severity {
+    /// The color of the severity (RGB), e.g. for an embed bar or a badge
+    #[cfg(any(feature = "discord", feature = "email"))]
+    pub(crate) fn color(self) -> u32 {
+        match self {
+            Self::Debug => 0x95a5a6,
+            Self::Info => 0x3498db,
+            Self::Warning => 0xf1c40f,
+            Self::Error => 0xe74c3c,
+            Self::Critical => 0x992d22,
+        }
+    }
+}
+
+/// An unknown severity name
+#[derive(Debug)]
+pub struct ParseSeverityError(String);
+impl fmt::Display for ParseSeverityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown severity `{}`, expected one of debug, info, warning, error or critical",
+            self.0
+        )
+    }
+}
+impl std::error::Error for ParseSeverityError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::Severity;
+
+    /// A test to make sure severities are ordered from least to most severe
+    #[test]
+    fn orders_by_severity() {
+        assert!(Severity::Debug < Severity::Info);
+        assert!(Severity::Info < Severity::Warning);
+        assert!(Severity::Warning < Severity::Error);
+        assert!(Severity::Error < Severity::Critical);
+    }
+
+    /// A test to make sure severities parse from lowercase names
+    #[test]
+    fn can_parse_severity() {
+        let severity: Severity = serde_json::from_str("\"warning\"").unwrap();
+        assert_eq!(severity, Severity::Warning);
+        assert_eq!("Critical".parse::<Severity>().unwrap(), Severity::Critical);
+        assert!("loud".parse::<Severity>().is_err())
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{Context, ContextKind, Endpoint, Error, Formatter, Notification, RenderedPayload};
+use serde_json::{json, Value};
+
+/// How Signal messages are sent to a destination through the
+/// signal-cli-rest-api: from a given registered number
+///
+/// The destination is the `/v2/send` endpoint of the API, like
+/// `http://signal-cli:8080/v2/send`, and messages go to the group ID (like
+/// `group.abc...`) or phone numbers set as the channel, comma separated.
+#[derive(Clone, Debug)]
+pub struct Signal {
+    number: String,
+}
+impl Signal {
+    /// Create `Signal` options sending from a given number, registered
+    /// with signal-cli
+    pub fn new(number: impl Into<String>) -> Self {
+        Self {
+            number: number.into(),
+        }
+    }
+}
+
+/// Renders notifications as Signal messages, as `MessageFormat::Signal`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SignalFormatter<'a> {
+    signal: Option<&'a Signal>,
+}
+impl<'a> SignalFormatter<'a> {
+    /// Create a `SignalFormatter` with the options of a destination
+    pub(crate) fn for_destination(destination: &'a Endpoint) -> Self {
+        Self {
+            signal: destination.signal_config(),
+        }
+    }
+}
+impl Formatter for SignalFormatter<'_> {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let payload = notification.clone().into_signal_payload(self.signal);
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into a signal-cli-rest-api
+    /// send payload, styled with bold labels and monospace code
+    pub(crate) fn into_signal_payload(self, signal: Option<&Signal>) -> Value {
+        let labels = self.labels.unwrap_or_default();
+        let mut lines = vec![
+            format!("**{}**: {}", labels.issue, self.message),
+            format!("*{}*: {}", labels.timestamp, self.timestamp),
+        ];
+        lines.extend(self.context.iter().map(context_line));
+        for section in &self.sections {
+            lines.push(format!("\n**{}**", section.title));
+            lines.extend(section.context.iter().map(context_line));
+        }
+        for attachment in &self.attachments {
+            lines.push(format!(
+                "\n**{}**\n`{}`",
+                attachment.filename, attachment.content
+            ));
+        }
+
+        let mut payload = json!({
+            "message": lines.join("\n"),
+            "text_mode": "styled",
+        });
+        if let Some(signal) = signal {
+            payload["number"] = Value::String(signal.number.clone());
+        }
+        if let Some(channel) = self.channel {
+            payload["recipients"] = recipients(&channel);
+        }
+
+        payload
+    }
+}
+
+/// Render a `Context` as a line of styled text
+fn context_line(ctx: &Context) -> String {
+    match &ctx.kind {
+        ContextKind::Text => format!("**{}**: {}", ctx.label, ctx.value),
+        ContextKind::Code => format!("**{}**:\n`{}`", ctx.label, ctx.value),
+        ContextKind::Link { text } => format!("**{}**: {text} ({})", ctx.label, ctx.value),
+    }
+}
+
+/// The recipients of a Signal message, from a comma separated channel
+fn recipients(channel: &str) -> Value {
+    channel
+        .split(',')
+        .map(str::trim)
+        .filter(|recipient| !recipient.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Signal;
+    use crate::{Context, Endpoint, Notification};
+    use serde_json::Value;
+
+    /// A test to make sure messages are styled and sent from the number to the recipients
+    #[test]
+    fn can_parse_into_signal_message() {
+        let mut notification = Notification::new("Disk full")
+            .with_context("Host", "web-1")
+            .with_channel("group.b3BzLWdyb3Vw, +15555550100");
+        notification.timestamp = String::from("2024-01-19 19:26:20.022233");
+        notification.context.push(Context::link(
+            "Runbook",
+            "https://wiki.example.com/disk",
+            "Runbook",
+        ));
+
+        let destination =
+            Endpoint::new("http://signal-cli:8080/v2/send").signal(Signal::new("+15555550199"));
+        let payload: Value =
+            serde_json::from_str(&notification.render_for(&destination).unwrap().body).unwrap();
+        assert_eq!(payload["number"], "+15555550199");
+        assert_eq!(
+            payload["recipients"],
+            serde_json::json!(["group.b3BzLWdyb3Vw", "+15555550100"])
+        );
+        assert_eq!(payload["text_mode"], "styled");
+        assert_eq!(
+            payload["message"],
+            "**Issue**: Disk full\n\
+             *Timestamp*: 2024-01-19 19:26:20.022233\n\
+             **Host**: web-1\n\
+             **Runbook**: Runbook (https://wiki.example.com/disk)"
+        );
+    }
+}
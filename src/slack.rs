@@ -0,0 +1,565 @@
+use crate::formatter::{address_message, inline_attachment};
+use crate::interaction::{actions_block, Action};
+use crate::render::fit;
+use crate::{
+    Attachment, Context, ContextKind, DeliveryReceipt, Endpoint, Error, Formatter, Notification,
+    OversizePolicy, RenderedPayload, Section,
+};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// The most fields Slack renders in a single section block
+const MAX_FIELDS: usize = 10;
+
+/// The most blocks Slack renders in a single message
+const MAX_BLOCKS: usize = 50;
+
+/// The most characters Slack renders in the text of a section block
+const MAX_BLOCK_TEXT: usize = 3000;
+
+/// The most characters Slack renders in a single field
+const MAX_FIELD_TEXT: usize = 2000;
+
+/// The parts of a Slack Web API response we read
+#[derive(Deserialize)]
+struct SlackResponse {
+    channel: Option<String>,
+    ts: Option<String>,
+    permalink: Option<String>,
+}
+
+/// The parts of a Slack `files.getUploadURLExternal` response we read
+#[derive(Deserialize)]
+struct UploadUrlResponse {
+    upload_url: String,
+    file_id: String,
+}
+
+/// The parts of a Slack `files.completeUploadExternal` response we read
+#[derive(Deserialize)]
+struct CompleteUploadResponse {
+    files: Vec<SlackResponse>,
+}
+
+/// The default `Formatter`: Slack Block Kit blocks, as `MessageFormat::Blocks`
+/// renders them, fitting them within the limits of Slack
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SlackFormatter {
+    policy: OversizePolicy,
+    action_buttons: bool,
+    unfurl: (Option<bool>, Option<bool>),
+}
+impl SlackFormatter {
+    /// Create a `SlackFormatter` truncating oversized text, without action buttons
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a `SlackFormatter` with the options of a destination
+    pub(crate) fn for_destination(destination: &Endpoint) -> Self {
+        Self {
+            policy: destination.oversize_policy(),
+            action_buttons: destination.has_action_buttons(),
+            unfurl: destination.unfurl_config(),
+        }
+    }
+}
+impl Formatter for SlackFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let mut notification = notification.clone();
+        let channel = notification.channel.take();
+        let id = notification.id.take();
+        let actions = self.action_buttons.then(|| {
+            actions_block(
+                notification.alert_id(),
+                &[Action::Acknowledge, Action::Resolve],
+            )
+        });
+
+        let max_blocks = MAX_BLOCKS - usize::from(actions.is_some());
+        let mut blocks = notification.into_slack_blocks();
+        if blocks.len() > max_blocks {
+            if self.policy == OversizePolicy::Reject {
+                return Err(Error::PayloadTooLarge {
+                    limit: max_blocks,
+                    size: blocks.len(),
+                });
+            }
+            blocks.truncate(max_blocks);
+        }
+        for block in &mut blocks {
+            if let Some(text) = block.pointer_mut("/text/text") {
+                fit(text, MAX_BLOCK_TEXT, self.policy)?;
+            }
+            let fields = block.get_mut("fields").and_then(Value::as_array_mut);
+            for field in fields.into_iter().flatten() {
+                if let Some(text) = field.get_mut("text") {
+                    fit(text, MAX_FIELD_TEXT, self.policy)?;
+                }
+            }
+        }
+        blocks.extend(actions);
+        let mut payload = json!({ "blocks": blocks });
+        address_message(&mut payload, channel, id, self.unfurl);
+
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into a slack message (JSON String)
+    #[cfg(test)]
+    pub(crate) fn into_slack_message(self) -> String {
+        json!({ "blocks": self.into_slack_blocks() }).to_string()
+    }
+
+    /// Consume the `Notification` and parse it into Block Kit blocks
+    fn into_slack_blocks(mut self) -> Vec<Value> {
+        let sections = std::mem::take(&mut self.sections);
+        let attachments = std::mem::take(&mut self.attachments);
+        let message = self.into_message();
+
+        // Build the JSON payload required for a slack message
+        let mut blocks = vec![json!({
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": message,
+            }
+        })];
+        for section in sections {
+            blocks.extend(section_blocks(section));
+        }
+        for attachment in attachments {
+            blocks.push(attachment_block(attachment));
+        }
+
+        blocks
+    }
+}
+
+/// Render a `Section` as Block Kit section blocks, with its context as fields
+fn section_blocks(section: Section) -> Vec<Value> {
+    let fields: Vec<Value> = section
+        .context
+        .into_iter()
+        .map(|ctx| {
+            json!({
+                "type": "mrkdwn",
+                "text": field_text(&ctx),
+            })
+        })
+        .collect();
+
+    let mut blocks = vec![json!({
+        "type": "section",
+        "text": {
+            "type": "mrkdwn",
+            "text": format!("*{}*", section.title),
+        }
+    })];
+    // Slack renders at most 10 fields per block, so overflow into more blocks
+    for (idx, fields) in fields.chunks(MAX_FIELDS).enumerate() {
+        if idx == 0 {
+            blocks[0]["fields"] = json!(fields);
+        } else {
+            blocks.push(json!({ "type": "section", "fields": fields }));
+        }
+    }
+
+    blocks
+}
+
+/// Render a `Context` as the mrkdwn text of a field, its label in bold
+fn field_text(ctx: &Context) -> String {
+    let mut text = String::with_capacity(ctx.formatted_len());
+    text.push('*');
+    text.push_str(&ctx.label);
+    text.push_str("*\n");
+    ctx.write_value(&mut text);
+    text
+}
+
+/// Render an `Attachment` inline as a Block Kit section block
+fn attachment_block(attachment: Attachment) -> Value {
+    json!({
+        "type": "section",
+        "text": {
+            "type": "mrkdwn",
+            "text": inline_attachment(attachment),
+        }
+    })
+}
+
+impl DeliveryReceipt {
+    /// Create a `DeliveryReceipt` from a Slack response body
+    ///
+    /// Incoming webhooks respond with plain text, so only Slack Web API
+    /// responses (JSON) identify the posted message.
+    pub(crate) fn from_slack_response(status: StatusCode, body: &str) -> Self {
+        let mut receipt = Self::new(status);
+        if let Ok(response) = serde_json::from_str::<SlackResponse>(body) {
+            receipt.channel = response.channel;
+            receipt.ts = response.ts;
+        }
+
+        receipt
+    }
+}
+
+/// Look up the permalink of a message posted through the Slack Web API,
+/// adding it to its receipt
+///
+/// This is best effort, any failure just leaves the receipt without a permalink.
+pub(crate) async fn with_permalink(
+    http_client: &Client,
+    destination: &Endpoint,
+    mut receipt: DeliveryReceipt,
+) -> DeliveryReceipt {
+    if let (Some(channel), Some(ts)) = (&receipt.channel, &receipt.ts) {
+        receipt.permalink = fetch_permalink(http_client, destination, channel, ts).await;
+    }
+
+    receipt
+}
+
+/// Look up the permalink of a message posted through the Slack Web API
+async fn fetch_permalink(
+    http_client: &Client,
+    destination: &Endpoint,
+    channel: &str,
+    ts: &str,
+) -> Option<String> {
+    let base_url = destination.url().strip_suffix("chat.postMessage")?;
+    let request = destination
+        .get(http_client, &format!("{base_url}chat.getPermalink"))
+        .query(&[("channel", channel), ("message_ts", ts)]);
+
+    let (_, body) = crate::runtime::send(request).await.ok()?;
+    serde_json::from_str::<SlackResponse>(&body).ok()?.permalink
+}
+
+/// Upload the attachments of a `Notification` through the Slack Web API,
+/// replacing each with a link to the uploaded file in its context
+///
+/// Only destinations on the Slack Web API can upload files, attachments that
+/// can't be uploaded stay on the `Notification` to be included inline.
+pub(crate) async fn upload_attachments(
+    http_client: &Client,
+    destination: &Endpoint,
+    mut notification: Notification,
+) -> Notification {
+    let Some(base_url) = destination.url().strip_suffix("chat.postMessage") else {
+        return notification;
+    };
+
+    let mut inline = Vec::new();
+    for attachment in std::mem::take(&mut notification.attachments) {
+        match upload_file(http_client, destination, base_url, &attachment).await {
+            Some(permalink) => notification.context.push(Context {
+                label: attachment.filename,
+                value: format!("<{permalink}|View file>"),
+                kind: ContextKind::Text,
+            }),
+            None => inline.push(attachment),
+        }
+    }
+    notification.attachments = inline;
+
+    notification
+}
+
+/// Upload a single file through the Slack Web API, returning its permalink
+///
+/// This is best effort, any failure returns `None`.
+async fn upload_file(
+    http_client: &Client,
+    destination: &Endpoint,
+    base_url: &str,
+    attachment: &Attachment,
+) -> Option<String> {
+    let length = attachment.content.len().to_string();
+    let request = destination
+        .get(
+            http_client,
+            &format!("{base_url}files.getUploadURLExternal"),
+        )
+        .query(&[
+            ("filename", attachment.filename.as_str()),
+            ("length", length.as_str()),
+        ]);
+    let (_, body) = crate::runtime::send(request).await.ok()?;
+    let upload: UploadUrlResponse = serde_json::from_str(&body).ok()?;
+
+    let request = http_client
+        .post(&upload.upload_url)
+        .body(attachment.content.clone());
+    crate::runtime::send_checked(request).await.ok()?;
+
+    let body = json!({
+        "files": [{ "id": upload.file_id, "title": attachment.filename }],
+    });
+    let request = destination
+        .post_to(
+            http_client,
+            &format!("{base_url}files.completeUploadExternal"),
+        )
+        .header("Content-type", "application/json")
+        .body(body.to_string());
+    let (_, body) = crate::runtime::send(request).await.ok()?;
+    let completed: CompleteUploadResponse = serde_json::from_str(&body).ok()?;
+
+    completed.files.into_iter().next()?.permalink
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        DeliveryReceipt, Endpoint, Error, MessageFormat, Notification, OversizePolicy, Section,
+        Severity, SeverityEmoji,
+    };
+    use reqwest::StatusCode;
+    use serde_json::Value;
+
+    /// A test to make sure Slack Web API responses identify the posted message
+    #[test]
+    fn can_parse_web_api_response() {
+        let receipt = DeliveryReceipt::from_slack_response(
+            StatusCode::OK,
+            "{\"ok\":true,\"channel\":\"C024BE91L\",\"ts\":\"1503435956.000247\"}",
+        );
+        assert_eq!(receipt.channel.as_deref(), Some("C024BE91L"));
+        assert_eq!(receipt.ts.as_deref(), Some("1503435956.000247"));
+    }
+
+    /// A test to make sure incoming webhook responses only carry a status
+    #[test]
+    fn can_parse_webhook_response() {
+        let receipt = DeliveryReceipt::from_slack_response(StatusCode::OK, "ok");
+        assert_eq!(receipt.status, StatusCode::OK);
+        assert!(receipt.ts.is_none());
+    }
+
+    /// A test to make sure sections are rendered as Block Kit fields, 10 per block
+    #[test]
+    fn can_parse_sections_into_fields() {
+        let mut section = Section::new("Request");
+        for idx in 0..12 {
+            section = section.with_context(format!("Header {idx}"), idx);
+        }
+        let message = Notification::new("Payment failed")
+            .with_section(section)
+            .into_slack_message();
+
+        let message: Value = serde_json::from_str(&message).unwrap();
+        let blocks = message["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[1]["text"]["text"], "*Request*");
+        assert_eq!(blocks[1]["fields"].as_array().unwrap().len(), 10);
+        assert_eq!(blocks[1]["fields"][0]["text"], "*Header 0*\n0");
+        assert_eq!(blocks[2]["fields"].as_array().unwrap().len(), 2);
+        assert!(!blocks[0]["text"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Request"));
+    }
+
+    /// A test to make sure attachments that weren't uploaded are included inline, truncated
+    #[test]
+    fn can_parse_attachments_inline() {
+        let message = Notification::new("Payment failed")
+            .with_attachment("response.json", "{\"error\":\"card_declined\"}")
+            .with_attachment("build.log", "x".repeat(5000))
+            .into_slack_message();
+
+        let message: Value = serde_json::from_str(&message).unwrap();
+        let blocks = message["blocks"].as_array().unwrap();
+        assert_eq!(
+            blocks[1]["text"]["text"],
+            "*response.json*\n```{\"error\":\"card_declined\"}```"
+        );
+        let truncated = blocks[2]["text"]["text"].as_str().unwrap();
+        assert!(truncated.ends_with("x…```"));
+        assert!(truncated.chars().count() < 3000);
+    }
+
+    /// A test to make sure each message format renders the same notification differently
+    #[test]
+    fn can_parse_into_each_format() {
+        let notification = Notification::new("Payment failed")
+            .with_context("Order ID", 1042)
+            .with_attachment("response.json", "{}");
+
+        let terse: Value = serde_json::from_str(
+            &notification
+                .clone()
+                .into_payload(MessageFormat::Terse, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(terse, serde_json::json!({ "text": "Payment failed" }));
+
+        let text: Value = serde_json::from_str(
+            &notification
+                .clone()
+                .into_payload(MessageFormat::Text, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        let text = text["text"].as_str().unwrap();
+        assert!(text.contains(">`Order ID`: 1042"));
+        assert!(text.ends_with("*response.json*\n```{}```"));
+
+        let blocks: Value = serde_json::from_str(
+            &notification
+                .into_payload(MessageFormat::Blocks, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(blocks["blocks"].as_array().unwrap().len(), 2);
+    }
+
+    /// A test to make sure messages are prefixed with the emoji of their severity
+    #[test]
+    fn can_prefix_severity_emoji() {
+        let destination = Endpoint::new("https://hooks.slack.com/services/T0/B0/X")
+            .format(MessageFormat::Terse)
+            .severity_emoji(SeverityEmoji::new().set(Severity::Critical, ":rotating_light:"));
+        let mut critical = Notification::new("Database down");
+        critical.severity = Severity::Critical;
+        let mut warning = Notification::new("Slow query");
+        warning.severity = Severity::Warning;
+
+        let payload: Value =
+            serde_json::from_str(&critical.render_for(&destination).unwrap().body).unwrap();
+        assert_eq!(payload["text"], ":rotating_light: Database down");
+        let payload: Value =
+            serde_json::from_str(&warning.render_for(&destination).unwrap().body).unwrap();
+        assert_eq!(payload["text"], "🟠 Slow query");
+    }
+
+    /// A test to make sure link previews are only set for Slack formats when configured
+    #[test]
+    fn can_control_unfurling() {
+        let notification =
+            Notification::new("Deploy failed").with_context("Run", "https://ci.example.com/1");
+
+        let destination = Endpoint::new("https://hooks.slack.com/services/T000/B000/XXXX")
+            .unfurl_links(false)
+            .unfurl_media(false);
+        let payload: Value =
+            serde_json::from_str(&notification.clone().render_for(&destination).unwrap().body)
+                .unwrap();
+        assert_eq!(payload["unfurl_links"], false);
+        assert_eq!(payload["unfurl_media"], false);
+
+        let payload: Value = serde_json::from_str(
+            &notification
+                .clone()
+                .render_for(&destination.clone().format(MessageFormat::Workflow))
+                .unwrap()
+                .body,
+        )
+        .unwrap();
+        assert!(payload.get("unfurl_links").is_none());
+
+        let payload: Value = serde_json::from_str(
+            &notification
+                .render_for(&Endpoint::new("https://hooks.slack.com").unfurl_media(true))
+                .unwrap()
+                .body,
+        )
+        .unwrap();
+        assert!(payload.get("unfurl_links").is_none());
+        assert_eq!(payload["unfurl_media"], true);
+    }
+
+    /// A test to make sure the channel of a notification is included in every format
+    #[test]
+    fn can_target_channel() {
+        let notification = Notification::new("Deploy finished").with_channel("#deploys");
+        for format in [
+            MessageFormat::Blocks,
+            MessageFormat::Text,
+            MessageFormat::Terse,
+        ] {
+            let payload: Value = serde_json::from_str(
+                &notification
+                    .clone()
+                    .into_payload(format, OversizePolicy::Reject)
+                    .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(payload["channel"], "#deploys");
+        }
+
+        let payload = Notification::new("Deploy finished")
+            .into_payload(MessageFormat::Terse, OversizePolicy::Reject)
+            .unwrap();
+        assert!(!payload.contains("channel"));
+    }
+
+    /// A test to make sure Slack messages carry the ID of the notification as metadata
+    #[test]
+    fn includes_id_metadata() {
+        let notification = Notification::new("Deploy finished");
+        let id = notification.id.clone().unwrap();
+
+        let payload: Value = serde_json::from_str(
+            &notification
+                .into_payload(MessageFormat::Blocks, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(payload["metadata"]["event_payload"]["id"], id.as_str());
+    }
+
+    /// A test to make sure action buttons end the message, identifying the notification
+    #[test]
+    fn can_add_action_buttons() {
+        let mut notification = Notification::new("Disk full");
+        notification.fingerprint = Some(String::from("disk-full"));
+
+        let destination = Endpoint::new("https://hooks.slack.com").action_buttons();
+        let payload: Value =
+            serde_json::from_str(&notification.render_for(&destination).unwrap().body).unwrap();
+        let actions = &payload["blocks"][1];
+        assert_eq!(actions["type"], "actions");
+        assert_eq!(actions["elements"][0]["text"]["text"], "Acknowledge");
+        assert_eq!(actions["elements"][1]["text"]["text"], "Resolve");
+        assert_eq!(actions["elements"][1]["value"], "disk-full");
+    }
+
+    /// A test to make sure oversized payloads are truncated or rejected per policy
+    #[test]
+    fn fits_payload_within_limits() {
+        let notification = Notification::new("x".repeat(5000));
+
+        let truncated: Value = serde_json::from_str(
+            &notification
+                .clone()
+                .into_payload(MessageFormat::Blocks, OversizePolicy::Truncate)
+                .unwrap(),
+        )
+        .unwrap();
+        let text = truncated["blocks"][0]["text"]["text"].as_str().unwrap();
+        assert_eq!(text.chars().count(), 3000);
+        assert!(text.ends_with("x…"));
+
+        let truncated: Value = serde_json::from_str(
+            &notification
+                .clone()
+                .into_payload(MessageFormat::Terse, OversizePolicy::Truncate)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(truncated["text"].as_str().unwrap().chars().count(), 1600);
+
+        let rejected = notification.into_payload(MessageFormat::Blocks, OversizePolicy::Reject);
+        assert!(matches!(
+            rejected,
+            Err(Error::PayloadTooLarge { limit: 3000, .. })
+        ));
+    }
+}
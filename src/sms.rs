@@ -0,0 +1,273 @@
+use crate::{
+    Context, ContextKind, Endpoint, Error, Formatter, Notification, OversizePolicy, RenderedPayload,
+};
+use serde_json::{json, Value};
+
+/// The characters of the GSM 03.38 basic set, one unit each
+const GSM_BASIC: &str = "@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ !\"#¤%&'()*+,-./0123456789:;<=>?\
+                          ¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüà";
+
+/// The characters of the GSM 03.38 extension set, two units each
+const GSM_EXTENSION: &str = "^{}\\[~]|€";
+
+/// How SMS to a destination are rendered: the message and its first few
+/// context entries, without markdown, in at most a given number of segments
+///
+/// A segment is 160 GSM characters, or 70 if any character is outside the
+/// GSM set (e.g. an emoji), and less when split into several.
+#[derive(Clone, Copy, Debug)]
+pub struct Sms {
+    max_segments: usize,
+    top_context: usize,
+}
+impl Sms {
+    /// Create `Sms` options for at most 3 segments with the first 3 context entries
+    pub fn new() -> Self {
+        Self {
+            max_segments: 3,
+            top_context: 3,
+        }
+    }
+
+    /// The most segments an SMS is split into (defaults to 3), past which
+    /// it's truncated or rejected per the `OversizePolicy` of the destination
+    pub fn max_segments(mut self, max_segments: usize) -> Self {
+        self.max_segments = max_segments.max(1);
+        self
+    }
+
+    /// How many context entries are included, from the first (defaults to 3)
+    pub fn top_context(mut self, top_context: usize) -> Self {
+        self.top_context = top_context;
+        self
+    }
+}
+impl Default for Sms {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Renders notifications as compact SMS, as `MessageFormat::Sms`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SmsFormatter {
+    sms: Sms,
+    policy: OversizePolicy,
+}
+impl SmsFormatter {
+    /// Create an `SmsFormatter` with the options of a destination
+    pub(crate) fn for_destination(destination: &Endpoint) -> Self {
+        Self {
+            sms: destination.sms_config().copied().unwrap_or_default(),
+            policy: destination.oversize_policy(),
+        }
+    }
+}
+impl Formatter for SmsFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let payload = notification
+            .clone()
+            .into_sms_payload(&self.sms, self.policy)?;
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into a compact SMS payload,
+    /// to the phone number set as the channel
+    ///
+    /// Over the most segments, context entries are dropped from the last,
+    /// then the message is truncated, unless the policy rejects it.
+    pub(crate) fn into_sms_payload(
+        self,
+        sms: &Sms,
+        policy: OversizePolicy,
+    ) -> Result<Value, Error> {
+        let mut lines = vec![strip_markdown(&self.message)];
+        lines.extend(self.context.iter().take(sms.top_context).map(context_line));
+
+        let mut text = lines.join("\n");
+        let size = segments(&text);
+        if size > sms.max_segments {
+            if policy == OversizePolicy::Reject {
+                return Err(Error::PayloadTooLarge {
+                    limit: sms.max_segments,
+                    size,
+                });
+            }
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                segments = size,
+                max_segments = sms.max_segments,
+                "SMS truncated to fit its segments"
+            );
+            while lines.len() > 1 && segments(&text) > sms.max_segments {
+                lines.pop();
+                text = lines.join("\n");
+            }
+            if segments(&text) > sms.max_segments {
+                text = truncate(&text, sms.max_segments);
+            }
+        }
+
+        let mut payload = json!({ "text": text });
+        if let Some(to) = self.channel {
+            payload["to"] = Value::String(to);
+        }
+
+        Ok(payload)
+    }
+}
+
+/// Render a `Context` as a line of plain text, code as its first line and
+/// links as their URL
+fn context_line(ctx: &Context) -> String {
+    let value = match &ctx.kind {
+        ContextKind::Text => strip_markdown(&ctx.value),
+        ContextKind::Code => ctx.value.lines().next().unwrap_or_default().to_string(),
+        ContextKind::Link { .. } => ctx.value.clone(),
+    };
+
+    format!("{}: {value}", ctx.label)
+}
+
+/// Strip Slack markdown from text: emphasis and code markers, and links
+/// like `<https://example.com|text>` down to their text
+fn strip_markdown(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        stripped.push_str(&rest[..start]);
+        let link = &rest[start + 1..];
+        match link.find('>') {
+            Some(end) if is_url(link[..end].split('|').next().unwrap_or_default()) => {
+                let link = &link[..end];
+                stripped.push_str(link.split_once('|').map_or(link, |(_, text)| text));
+                rest = &rest[start + end + 2..];
+            }
+            _ => {
+                stripped.push('<');
+                rest = link;
+            }
+        }
+    }
+    stripped.push_str(rest);
+    stripped.retain(|c| !matches!(c, '*' | '`' | '~'));
+
+    stripped
+}
+
+/// Whether text looks like the URL of a Slack link
+fn is_url(text: &str) -> bool {
+    text.contains(':') && !text.contains(char::is_whitespace)
+}
+
+/// Truncate text to fit in a number of segments, marked with an ellipsis
+fn truncate(text: &str, max_segments: usize) -> String {
+    let mut truncated: String = text.to_string();
+    while !truncated.is_empty() && segments(&format!("{truncated}...")) > max_segments {
+        truncated.pop();
+    }
+    truncated.push_str("...");
+
+    truncated
+}
+
+/// How many segments text is split into when sent as an SMS
+fn segments(text: &str) -> usize {
+    let gsm_units: Option<usize> = text
+        .chars()
+        .map(|c| {
+            if GSM_BASIC.contains(c) {
+                Some(1)
+            } else if GSM_EXTENSION.contains(c) {
+                Some(2)
+            } else {
+                None
+            }
+        })
+        .sum();
+    let (units, single, multipart) = match gsm_units {
+        Some(units) => (units, 160, 153),
+        None => (text.encode_utf16().count(), 70, 67),
+    };
+
+    if units <= single {
+        1
+    } else {
+        units.div_ceil(multipart)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{segments, strip_markdown, Sms};
+    use crate::{Context, Error, MessageFormat, Notification, OversizePolicy};
+    use serde_json::Value;
+
+    /// A test to make sure segments are counted per the encoding of the text
+    #[test]
+    fn counts_segments() {
+        assert_eq!(segments(&"a".repeat(160)), 1);
+        assert_eq!(segments(&"a".repeat(161)), 2);
+        assert_eq!(segments(&"{".repeat(80)), 1);
+        assert_eq!(segments(&"a".repeat(306)), 2);
+        assert_eq!(segments(&"🔥".repeat(35)), 1);
+        assert_eq!(segments(&format!("🔥{}", "a".repeat(69))), 2);
+    }
+
+    /// A test to make sure markdown is stripped, keeping link text
+    #[test]
+    fn strips_markdown() {
+        assert_eq!(
+            strip_markdown("*Deploy* of `api` failed, see <https://ci.example.com|the run>"),
+            "Deploy of api failed, see the run"
+        );
+        assert_eq!(strip_markdown("p99 < 200ms"), "p99 < 200ms");
+    }
+
+    /// A test to make sure only the top context is included, dropped or rejected past the segments
+    #[test]
+    fn renders_compactly() {
+        let mut notification = Notification::new("Disk full")
+            .with_context("Host", "web-1")
+            .with_context("Usage", "99%")
+            .with_channel("+15555550100");
+        notification
+            .context
+            .push(Context::code("Output", "df: /var\nmore"));
+
+        let payload: Value = serde_json::from_str(
+            &notification
+                .clone()
+                .into_payload(MessageFormat::Sms, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            payload["text"],
+            "Disk full\nHost: web-1\nUsage: 99%\nOutput: df: /var"
+        );
+        assert_eq!(payload["to"], "+15555550100");
+
+        let sms = Sms::new().max_segments(1).top_context(1);
+        let mut long = notification;
+        long.context[0].value = "w".repeat(160);
+        let payload = long
+            .clone()
+            .into_sms_payload(&sms, OversizePolicy::Truncate)
+            .unwrap();
+        assert_eq!(payload["text"], "Disk full");
+        assert!(matches!(
+            long.into_sms_payload(&sms, OversizePolicy::Reject),
+            Err(Error::PayloadTooLarge { limit: 1, size: 2 })
+        ));
+
+        let payload = Notification::new("x".repeat(500))
+            .into_sms_payload(&sms, OversizePolicy::Truncate)
+            .unwrap();
+        let text = payload["text"].as_str().unwrap();
+        assert_eq!(text.len(), 160);
+        assert!(text.ends_with("x..."));
+    }
+}
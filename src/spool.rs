@@ -0,0 +1,112 @@
+use crate::Notification;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A `Notification` that failed to deliver, along with its destination
+#[derive(Deserialize, Serialize)]
+pub(crate) struct SpooledNotification {
+    pub(crate) url: String,
+    pub(crate) notification: Notification,
+}
+
+/// An append-only file of notifications that failed to deliver (JSON lines)
+///
+/// Only destination URLs are written, never headers, so credentials
+/// don't end up on disk.
+pub(crate) struct Spool {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+impl Spool {
+    /// Create a `Spool` writing to a given file, created on first use
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Append a failed `Notification` for a given destination
+    pub(crate) fn append(&self, url: &str, notification: &Notification) -> io::Result<()> {
+        let mut line = serde_json::to_string(&SpooledNotification {
+            url: url.to_string(),
+            notification: notification.clone(),
+        })?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())
+    }
+
+    /// Remove and return every spooled `Notification`, oldest first
+    ///
+    /// Lines that can't be parsed are skipped.
+    pub(crate) fn take(&self) -> io::Result<Vec<SpooledNotification>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut spooled = Vec::new();
+        for line in BufReader::new(file).lines() {
+            if let Ok(notification) = serde_json::from_str(&line?) {
+                spooled.push(notification);
+            }
+        }
+        fs::remove_file(&self.path)?;
+
+        Ok(spooled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Spool;
+    use crate::{Context, ContextKind, Notification, Severity};
+
+    /// A test to make sure spooled notifications can be taken back out
+    #[test]
+    fn can_append_and_take() {
+        let path = std::env::temp_dir().join(format!("dev-notify-spool-{}", std::process::id()));
+        let spool = Spool::new(path.clone());
+        let notification = Notification {
+            message: String::from("External API Error: Could not find API Keys"),
+            timestamp: String::from("2024-01-19 19:26:20.022233"),
+            context: vec![Context {
+                label: String::from("Customer ID"),
+                value: String::from("0"),
+                kind: ContextKind::Text,
+            }],
+            severity: Severity::Critical,
+            idempotency_key: None,
+            fingerprint: None,
+            sections: Vec::new(),
+            attachments: Vec::new(),
+            labels: None,
+            channel: None,
+            id: None,
+        };
+
+        spool
+            .append("https://hooks.slack.com/a", &notification)
+            .unwrap();
+        spool
+            .append("https://hooks.slack.com/b", &notification)
+            .unwrap();
+        let spooled = spool.take().unwrap();
+        assert_eq!(spooled.len(), 2);
+        assert_eq!(spooled[1].url, "https://hooks.slack.com/b");
+        assert_eq!(spooled[1].notification.severity, Severity::Critical);
+        assert!(spool.take().unwrap().is_empty());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{Context, Error, Notification, Notifier, Severity};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+
+/// How often units are checked by default
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How many of the last journal lines of a failed unit are included by default
+const DEFAULT_JOURNAL_LINES: usize = 20;
+
+/// Watches systemd units, notifying when one enters the failed state with
+/// the last lines of its journal as context, routed like any other
+/// notification
+///
+/// Units are checked through `systemctl` (which asks systemd over D-Bus)
+/// on an interval rather than by subscribing to D-Bus signals, to avoid
+/// depending on a D-Bus client. Failures between checks are still caught:
+/// a unit that restarts (e.g. with `Restart=on-failure`) counts up its
+/// `NRestarts`, and one that stops with an error changes its `Result`.
+///
+/// Only changes are notified: a unit already failed when watching starts
+/// isn't, until it recovers and fails again.
+#[derive(Clone, Debug)]
+pub struct SystemdWatcher {
+    units: Vec<String>,
+    interval: Duration,
+    journal_lines: usize,
+    user: bool,
+}
+impl SystemdWatcher {
+    /// Create a `SystemdWatcher` without any units, checking every 10 seconds
+    pub fn new() -> Self {
+        Self {
+            units: Vec::new(),
+            interval: DEFAULT_INTERVAL,
+            journal_lines: DEFAULT_JOURNAL_LINES,
+            user: false,
+        }
+    }
+
+    /// Watch a unit, e.g. `nginx.service` or `backup.timer`
+    pub fn unit(mut self, unit: impl Into<String>) -> Self {
+        self.units.push(unit.into());
+        self
+    }
+
+    /// How often to check the units
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// How many of the last journal lines of a failed unit to include
+    pub fn journal_lines(mut self, lines: usize) -> Self {
+        self.journal_lines = lines;
+        self
+    }
+
+    /// Watch units of the user's service manager instead of the system's
+    pub fn user(mut self) -> Self {
+        self.user = true;
+        self
+    }
+
+    /// Spawn a task on the current tokio runtime checking the units on the
+    /// interval, sending a notification through a given `Notifier` (routed
+    /// by it) when one fails
+    ///
+    /// Checks that fail (e.g. `systemctl` isn't installed) are skipped, and
+    /// logged as warnings with the `tracing` feature.
+    ///
+    /// Aborting the returned task stops watching.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn(self, notifier: Arc<Notifier>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut states: HashMap<String, UnitState> = HashMap::new();
+            let mut ticks = tokio::time::interval(self.interval);
+            loop {
+                ticks.tick().await;
+                let output = match self.systemctl_show().await {
+                    Ok(output) => output,
+                    Err(_e) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(error = %_e, "checking systemd units failed");
+                        continue;
+                    }
+                };
+                for unit in parse_show(&output) {
+                    let failed = states
+                        .get(&unit.id)
+                        .is_some_and(|previous| unit.failed_since(previous));
+                    if failed {
+                        let journal = self.journal(&unit.id).await;
+                        // Failures are already handled by the `Notifier`
+                        let _ = notifier.notify(unit.notification(journal)).await;
+                    }
+                    states.insert(unit.id.clone(), unit);
+                }
+            }
+        })
+    }
+
+    /// The state of every unit, as `systemctl show` lists it
+    async fn systemctl_show(&self) -> Result<String, Error> {
+        let mut command = Command::new("systemctl");
+        if self.user {
+            command.arg("--user");
+        }
+        let output = command
+            .args(["show", "--property=Id,ActiveState,Result,NRestarts", "--"])
+            .args(&self.units)
+            .output()
+            .await
+            .map_err(|e| Error::Config(format!("couldn't run systemctl: {e}")))?;
+        if !output.status.success() {
+            return Err(Error::Config(format!(
+                "systemctl show failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// The last lines of the journal of a unit, if there are any
+    async fn journal(&self, unit: &str) -> Option<String> {
+        if self.journal_lines == 0 {
+            return None;
+        }
+        let mut command = Command::new("journalctl");
+        command.arg(if self.user { "--user-unit" } else { "--unit" });
+        let output = command
+            .arg(unit)
+            .args(["--no-pager", "--output=short-iso", "--lines"])
+            .arg(self.journal_lines.to_string())
+            .output()
+            .await
+            .ok()?;
+        let journal = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        (!journal.is_empty()).then_some(journal)
+    }
+}
+impl Default for SystemdWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The state of a unit
+#[derive(Debug, PartialEq, Eq)]
+struct UnitState {
+    id: String,
+    active_state: String,
+    result: String,
+    restarts: u32,
+}
+impl UnitState {
+    fn is_failed(&self) -> bool {
+        self.active_state == "failed"
+    }
+
+    /// Whether the unit failed since its `previous` check: it entered the
+    /// failed state, its result turned into an error, or it was restarted
+    fn failed_since(&self, previous: &UnitState) -> bool {
+        (self.is_failed() && !previous.is_failed())
+            || (self.result != "success" && previous.result == "success")
+            || self.restarts > previous.restarts
+    }
+
+    /// Describe the failure of the unit as a `Notification`
+    fn notification(&self, journal: Option<String>) -> Notification {
+        let message = if self.result == "success" {
+            format!("{} was restarted", self.id)
+        } else {
+            format!("{} failed ({})", self.id, self.result)
+        };
+        let mut notification = Notification::new(message)
+            .with_context("Unit", &self.id)
+            .with_context("Result", &self.result);
+        if self.restarts > 0 {
+            notification = notification.with_context("Restarts", self.restarts);
+        }
+        if let Some(journal) = journal {
+            notification.context.push(Context::code("Journal", journal));
+        }
+        notification.severity = Severity::Error;
+        notification.fingerprint = Some(format!("systemd:{}", self.id));
+
+        notification
+    }
+}
+
+/// Parse the `Key=Value` blocks `systemctl show` prints, one per unit
+fn parse_show(output: &str) -> Vec<UnitState> {
+    output
+        .split("\n\n")
+        .filter_map(|block| {
+            let properties: HashMap<&str, &str> = block
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .collect();
+            Some(UnitState {
+                id: properties.get("Id")?.to_string(),
+                active_state: properties.get("ActiveState")?.to_string(),
+                result: properties.get("Result").unwrap_or(&"").to_string(),
+                restarts: properties
+                    .get("NRestarts")
+                    .and_then(|restarts| restarts.parse().ok())
+                    .unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_show;
+
+    /// A test to make sure every unit's state is read, and failures described with the journal
+    #[test]
+    fn can_parse_unit_states() {
+        let output = "Id=nginx.service\nActiveState=failed\nResult=exit-code\n\n\
+                      Id=backup.timer\nActiveState=active\nResult=success\n";
+        let units = parse_show(output);
+        assert_eq!(units.len(), 2);
+        assert!(units[0].is_failed());
+        assert!(!units[1].is_failed());
+        assert_eq!(units[1].id, "backup.timer");
+
+        let notification = units[0].notification(Some(String::from(
+            "2024-01-19T19:26:20+0000 web-1 nginx[812]: bind() to 0.0.0.0:80 failed",
+        )));
+        assert_eq!(notification.message, "nginx.service failed (exit-code)");
+        assert_eq!(notification.context[1].value, "exit-code");
+        assert_eq!(notification.context[2].label, "Journal");
+        assert_eq!(
+            notification.fingerprint.as_deref(),
+            Some("systemd:nginx.service")
+        );
+    }
+
+    /// A test to make sure failures between checks are caught through restarts and results
+    #[test]
+    fn detects_failures_between_checks() {
+        let state = |output: &str| parse_show(output).remove(0);
+        let running = state("Id=api.service\nActiveState=active\nResult=success\nNRestarts=0\n");
+        let restarted = state("Id=api.service\nActiveState=active\nResult=success\nNRestarts=1\n");
+        let restarting =
+            state("Id=api.service\nActiveState=activating\nResult=exit-code\nNRestarts=0\n");
+        let failed = state("Id=api.service\nActiveState=failed\nResult=signal\nNRestarts=1\n");
+
+        assert!(!running.failed_since(&running));
+        assert!(restarted.failed_since(&running));
+        assert!(restarting.failed_since(&running));
+        assert!(failed.failed_since(&restarted));
+        assert!(!failed.failed_since(&failed));
+
+        let notification = restarted.notification(None);
+        assert_eq!(notification.message, "api.service was restarted");
+        assert_eq!(notification.context[2].value, "1");
+    }
+}
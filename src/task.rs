@@ -0,0 +1,111 @@
+use crate::{Notification, Worker};
+use std::any::Any;
+use std::fmt::{Debug, Display};
+use std::future::{poll_fn, Future};
+use std::panic::{self, AssertUnwindSafe};
+use std::task::Poll;
+use tokio::task::JoinHandle;
+
+impl Worker {
+    /// Spawn a named task on the current tokio runtime, queueing a
+    /// `Notification` if it returns an error or panics, so background tasks
+    /// don't die unnoticed
+    ///
+    /// The message is `"Task {name} failed: {error}"` (or `panicked`), with
+    /// the task name and `Debug` representation of the error as context.
+    /// The handle resolves like one from `tokio::spawn`, panics included.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn_monitored<F, T, E>(&self, name: &str, future: F) -> JoinHandle<Result<T, E>>
+    where
+        F: Future<Output = Result<T, E>> + Send + 'static,
+        T: Send + 'static,
+        E: Display + Debug + Send + 'static,
+    {
+        let worker = self.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            let mut future = Box::pin(future);
+            let outcome = poll_fn(|cx| {
+                match panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+                    Ok(poll) => poll.map(Ok),
+                    Err(payload) => Poll::Ready(Err(payload)),
+                }
+            })
+            .await;
+
+            match outcome {
+                Ok(Err(error)) => {
+                    worker.notify(
+                        Notification::new(format!("Task {name} failed: {error}"))
+                            .with_context("Task", &name)
+                            .with_context("Error", format!("{error:?}")),
+                    );
+                    Err(error)
+                }
+                Ok(result) => result,
+                Err(payload) => {
+                    worker.notify(
+                        Notification::new(format!(
+                            "Task {name} panicked: {}",
+                            panic_message(&*payload)
+                        ))
+                        .with_context("Task", &name),
+                    );
+                    panic::resume_unwind(payload)
+                }
+            }
+        })
+    }
+}
+
+/// The message a panic was raised with
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("Box<dyn Any>"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockDestination;
+    use crate::{Notifier, Worker};
+    use std::sync::Arc;
+
+    /// A test to make sure failing and panicking tasks are notified, and successful ones aren't
+    #[tokio::test]
+    async fn notifies_on_task_failure() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+
+        let ok = worker.spawn_monitored("sync", async { Ok::<_, String>(1) });
+        assert_eq!(ok.await.unwrap(), Ok(1));
+        let failed =
+            worker.spawn_monitored("cleanup", async { Err::<(), _>(String::from("disk full")) });
+        assert!(failed.await.unwrap().is_err());
+        let panicked = worker.spawn_monitored("poller", async {
+            if true {
+                panic!("lost connection");
+            }
+            Ok::<(), String>(())
+        });
+        assert!(panicked.await.unwrap_err().is_panic());
+        while mock.delivered().len() < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        let delivered = mock.delivered();
+        assert_eq!(delivered.len(), 2);
+        assert_eq!(delivered[0].message, "Task cleanup failed: disk full");
+        assert_eq!(delivered[0].context[0].value, "cleanup");
+        assert_eq!(delivered[0].context[1].value, "\"disk full\"");
+        assert_eq!(
+            delivered[1].message,
+            "Task poller panicked: lost connection"
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use crate::render::escape_html;
+use crate::{
+    Attachment, Context, ContextKind, Endpoint, Error, Formatter, MessageFormat, Notification,
+    OversizePolicy, RenderedPayload,
+};
+use serde_json::{json, Value};
+
+/// The most characters Telegram takes in the text of a message
+const MAX_MESSAGE_TEXT: usize = 4096;
+
+/// How many times longer escaping can make text, at most (`"` is `&quot;`)
+const MAX_ESCAPED_GROWTH: usize = 6;
+
+/// The characters MarkdownV2 reserves, which must be escaped in plain text
+const RESERVED: &str = "_*[]()~`>#+-=|{}.!\\";
+
+/// How a Telegram message is marked up, set as its `parse_mode`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ParseMode {
+    MarkdownV2,
+    Html,
+}
+impl ParseMode {
+    /// The name Telegram knows the parse mode by
+    fn name(self) -> &'static str {
+        match self {
+            Self::MarkdownV2 => "MarkdownV2",
+            Self::Html => "HTML",
+        }
+    }
+
+    /// Escape plain text
+    fn escape(self, text: &str) -> String {
+        match self {
+            Self::MarkdownV2 => escape_markdown(text, RESERVED),
+            Self::Html => escape_html(text),
+        }
+    }
+
+    /// Render plain text in bold
+    fn bold(self, text: &str) -> String {
+        match self {
+            Self::MarkdownV2 => format!("*{}*", self.escape(text)),
+            Self::Html => format!("<b>{}</b>", self.escape(text)),
+        }
+    }
+
+    /// Render plain text in italics
+    fn italic(self, text: &str) -> String {
+        match self {
+            Self::MarkdownV2 => format!("_{}_", self.escape(text)),
+            Self::Html => format!("<i>{}</i>", self.escape(text)),
+        }
+    }
+
+    /// Render text as a preformatted block
+    fn pre(self, text: &str) -> String {
+        match self {
+            Self::MarkdownV2 => format!("```\n{}\n```", escape_markdown(text, "`\\")),
+            Self::Html => format!("<pre>{}</pre>", escape_html(text)),
+        }
+    }
+
+    /// Render a link to a URL with a given text
+    fn link(self, text: &str, url: &str) -> String {
+        match self {
+            Self::MarkdownV2 => format!("[{}]({})", self.escape(text), escape_markdown(url, ")\\")),
+            Self::Html => format!("<a href=\"{}\">{}</a>", escape_html(url), escape_html(text)),
+        }
+    }
+}
+
+/// Renders notifications as Telegram messages, as `MessageFormat::TelegramMarkdown`
+/// or `MessageFormat::TelegramHtml`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TelegramFormatter {
+    mode: ParseMode,
+    policy: OversizePolicy,
+}
+impl TelegramFormatter {
+    /// Create a `TelegramFormatter` with the format and options of a destination
+    pub(crate) fn for_destination(destination: &Endpoint) -> Self {
+        let mode = match destination.message_format() {
+            MessageFormat::TelegramHtml => ParseMode::Html,
+            _ => ParseMode::MarkdownV2,
+        };
+        Self {
+            mode,
+            policy: destination.oversize_policy(),
+        }
+    }
+}
+impl Formatter for TelegramFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let payload = notification
+            .clone()
+            .into_telegram_payload(self.mode, self.policy)?;
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into a Telegram `sendMessage`
+    /// payload marked up in a given parse mode, to the chat set as the
+    /// channel, fitting it within the limits of Telegram
+    ///
+    /// Text that doesn't fit is truncated at the last whole line that does,
+    /// so markup is never cut in half.
+    pub(crate) fn into_telegram_payload(
+        self,
+        mode: ParseMode,
+        policy: OversizePolicy,
+    ) -> Result<Value, Error> {
+        let labels = self.labels.unwrap_or_default();
+
+        let mut lines = vec![
+            format!(
+                "{}: {}",
+                mode.bold(&labels.issue),
+                mode.escape(&self.message)
+            ),
+            format!(
+                "{}: {}",
+                mode.italic(&labels.timestamp),
+                mode.escape(&self.timestamp)
+            ),
+        ];
+        lines.extend(self.context.iter().map(|ctx| context_line(mode, ctx)));
+        for section in &self.sections {
+            lines.push(format!("\n{}", mode.bold(&section.title)));
+            lines.extend(section.context.iter().map(|ctx| context_line(mode, ctx)));
+        }
+        lines.extend(
+            self.attachments
+                .iter()
+                .map(|attachment| attachment_line(mode, attachment)),
+        );
+
+        let size: usize = lines.iter().map(|line| line.chars().count() + 1).sum();
+        let text = if size - 1 <= MAX_MESSAGE_TEXT {
+            lines.join("\n")
+        } else if policy == OversizePolicy::Reject {
+            return Err(Error::PayloadTooLarge {
+                limit: MAX_MESSAGE_TEXT,
+                size: size - 1,
+            });
+        } else {
+            truncate(lines, mode, &self.message)
+        };
+
+        let mut payload = json!({ "text": text, "parse_mode": mode.name() });
+        if let Some(chat_id) = self.channel {
+            payload["chat_id"] = Value::String(chat_id);
+        }
+
+        Ok(payload)
+    }
+}
+
+/// Join as many whole lines as fit, marked with an ellipsis, or only the
+/// start of the message if not even the first line fits
+fn truncate(lines: Vec<String>, mode: ParseMode, message: &str) -> String {
+    // Leave room for the ellipsis
+    let limit = MAX_MESSAGE_TEXT - 2;
+    let mut text = String::new();
+    let mut size = 0;
+    for line in lines {
+        let line_size = line.chars().count() + usize::from(!text.is_empty());
+        if size + line_size > limit {
+            if text.is_empty() {
+                let start: String = message.chars().take(limit / MAX_ESCAPED_GROWTH).collect();
+                text = mode.escape(&start);
+            }
+            break;
+        }
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&line);
+        size += line_size;
+    }
+    text.push_str("\n…");
+
+    text
+}
+
+/// Render a `Context` as a line of text
+fn context_line(mode: ParseMode, ctx: &Context) -> String {
+    let label = mode.bold(&ctx.label);
+    match &ctx.kind {
+        ContextKind::Text => format!("{label}: {}", mode.escape(&ctx.value)),
+        ContextKind::Code => format!("{label}:\n{}", mode.pre(&ctx.value)),
+        ContextKind::Link { text } => format!("{label}: {}", mode.link(text, &ctx.value)),
+    }
+}
+
+/// Render an `Attachment` inline as a preformatted block
+fn attachment_line(mode: ParseMode, attachment: &Attachment) -> String {
+    format!(
+        "{}\n{}",
+        mode.bold(&attachment.filename),
+        mode.pre(&attachment.content)
+    )
+}
+
+/// Escape the given reserved characters of MarkdownV2 with a backslash
+fn escape_markdown(text: &str, reserved: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if reserved.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseMode;
+    use crate::{Context, Error, MessageFormat, Notification, OversizePolicy};
+    use serde_json::Value;
+
+    /// A test to make sure reserved characters are escaped in each parse mode
+    #[test]
+    fn escapes_reserved_characters() {
+        let mut notification = Notification::new("Deploy of v1.2.0 failed (exit 1)!")
+            .with_context("Branch", "fix/<login>_page")
+            .with_channel("-1001234567890");
+        notification.timestamp = String::from("2024-01-19 19:26:20.022233");
+
+        let markdown: Value = serde_json::from_str(
+            &notification
+                .clone()
+                .into_payload(MessageFormat::TelegramMarkdown, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(markdown["parse_mode"], "MarkdownV2");
+        assert_eq!(markdown["chat_id"], "-1001234567890");
+        assert_eq!(
+            markdown["text"],
+            "*Issue*: Deploy of v1\\.2\\.0 failed \\(exit 1\\)\\!\n\
+             _Timestamp_: 2024\\-01\\-19 19:26:20\\.022233\n\
+             *Branch*: fix/<login\\>\\_page"
+        );
+
+        let html: Value = serde_json::from_str(
+            &notification
+                .into_payload(MessageFormat::TelegramHtml, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(html["parse_mode"], "HTML");
+        assert!(html["text"]
+            .as_str()
+            .unwrap()
+            .ends_with("<b>Branch</b>: fix/&lt;login&gt;_page"));
+    }
+
+    /// A test to make sure code and links are marked up with their own escaping
+    #[test]
+    fn can_markup_code_and_links() {
+        let mut notification = Notification::new("Build failed");
+        notification.context = vec![
+            Context::code("Log", "let x = `y`;"),
+            Context::link("Run", "https://ci.example.com/runs/(1)", "View run"),
+        ];
+
+        let payload = notification
+            .into_telegram_payload(ParseMode::MarkdownV2, OversizePolicy::Reject)
+            .unwrap();
+        let text = payload["text"].as_str().unwrap();
+        assert!(text.contains("*Log*:\n```\nlet x = \\`y\\`;\n```"));
+        assert!(text.ends_with("*Run*: [View run](https://ci.example.com/runs/(1\\))"));
+    }
+
+    /// A test to make sure long messages are truncated at whole lines or rejected per policy
+    #[test]
+    fn fits_message_within_limits() {
+        let mut notification = Notification::new("Many failures");
+        for idx in 0..500 {
+            notification = notification.with_context(format!("Entry {idx}"), "failed");
+        }
+
+        let payload = notification
+            .clone()
+            .into_telegram_payload(ParseMode::Html, OversizePolicy::Truncate)
+            .unwrap();
+        let text = payload["text"].as_str().unwrap();
+        assert!(text.chars().count() <= 4096);
+        assert!(text.ends_with("failed\n…"));
+
+        assert!(matches!(
+            notification.into_telegram_payload(ParseMode::Html, OversizePolicy::Reject),
+            Err(Error::PayloadTooLarge { limit: 4096, .. })
+        ));
+
+        // Escaping never pushes the start of a huge message over the limit
+        let payload = Notification::new("\"".repeat(5000))
+            .into_telegram_payload(ParseMode::Html, OversizePolicy::Truncate)
+            .unwrap();
+        let text = payload["text"].as_str().unwrap();
+        assert!(text.starts_with("&quot;") && text.ends_with("&quot;\n…"));
+        assert!(text.chars().count() <= 4096);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{Context, Notification, Section, Severity};
+use serde::Deserialize;
+
+/// How many failed tests get a section of their own
+const MAX_FAILURES: usize = 10;
+
+/// How many of the last lines of a failed test's output are included
+const MAX_OUTPUT_LINES: usize = 15;
+
+/// A summary of `cargo test` runs from libtest's JSON output
+/// (`cargo test -- -Z unstable-options --format json`), to notify about
+/// failed tests nobody reads the logs of, e.g. in nightly CI
+///
+/// Lines that aren't libtest events (build output, cargo messages) are
+/// skipped, so the whole output of `cargo test` can be fed in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TestReport {
+    pub passed: usize,
+    pub failed: Vec<FailedTest>,
+    pub ignored: usize,
+}
+
+/// A test that failed, with what it printed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FailedTest {
+    pub name: String,
+    pub output: String,
+}
+
+/// The parts of a libtest event we read
+#[derive(Deserialize)]
+struct TestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: Option<String>,
+    stdout: Option<String>,
+}
+
+impl TestReport {
+    /// Create an empty `TestReport`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Summarize the complete output of a test run
+    pub fn parse(output: &str) -> Self {
+        let mut report = Self::new();
+        for line in output.lines() {
+            report.push_line(line);
+        }
+
+        report
+    }
+
+    /// Add a line of output to the summary, as it's read
+    pub fn push_line(&mut self, line: &str) {
+        let Ok(event) = serde_json::from_str::<TestEvent>(line.trim()) else {
+            return;
+        };
+        if event.kind != "test" {
+            return;
+        }
+        match event.event.as_str() {
+            "ok" => self.passed += 1,
+            "ignored" => self.ignored += 1,
+            "failed" | "timeout" => self.failed.push(FailedTest {
+                name: event.name.unwrap_or_default(),
+                output: event.stdout.unwrap_or_default(),
+            }),
+            _ => {}
+        }
+    }
+
+    /// Whether every test that ran passed
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Describe the run as a `Notification`, with the counts as context and
+    /// a section with the tail of the output of each failed test
+    pub fn to_notification(&self) -> Notification {
+        let total = self.passed + self.failed.len();
+        let message = match self.failed.len() {
+            0 => format!("All {total} tests passed"),
+            1 => format!("1 of {total} tests failed: {}", self.failed[0].name),
+            failed => format!("{failed} of {total} tests failed"),
+        };
+        let mut notification = Notification::new(message)
+            .with_context("Passed", self.passed)
+            .with_context("Failed", self.failed.len())
+            .with_context("Ignored", self.ignored);
+        notification.severity = if self.is_success() {
+            Severity::Info
+        } else {
+            Severity::Error
+        };
+
+        for test in self.failed.iter().take(MAX_FAILURES) {
+            let lines: Vec<&str> = test.output.lines().collect();
+            let tail = lines[lines.len().saturating_sub(MAX_OUTPUT_LINES)..].join("\n");
+            let mut section = Section::new(&test.name);
+            if !tail.is_empty() {
+                section.context.push(Context::code("Output", tail));
+            }
+            notification = notification.with_section(section);
+        }
+        if self.failed.len() > MAX_FAILURES {
+            let more = self.failed.len() - MAX_FAILURES;
+            notification = notification.with_context("Not shown", format!("{more} more failures"));
+        }
+
+        notification
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TestReport;
+    use crate::Severity;
+
+    const OUTPUT: &str = r#"   Compiling api v0.1.0 (/src/api)
+{ "type": "suite", "event": "started", "test_count": 4 }
+{ "type": "test", "event": "started", "name": "orders::creates_order" }
+{ "type": "test", "name": "orders::creates_order", "event": "ok" }
+{ "type": "test", "name": "orders::cancels_order", "event": "failed", "stdout": "thread 'orders::cancels_order' panicked at src/orders.rs:42:9:\nassertion `left == right` failed\n  left: Pending\n right: Cancelled\n" }
+{ "type": "test", "name": "orders::refunds_order", "event": "ignored" }
+{ "type": "test", "name": "users::signs_up", "event": "ok" }
+{ "type": "suite", "event": "failed", "passed": 2, "failed": 1, "ignored": 1, "measured": 0, "filtered_out": 0 }
+"#;
+
+    /// A test to make sure libtest events are counted and other output skipped
+    #[test]
+    fn can_parse_test_output() {
+        let report = TestReport::parse(OUTPUT);
+        assert_eq!(report.passed, 2);
+        assert_eq!(report.ignored, 1);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].name, "orders::cancels_order");
+        assert!(report.failed[0].output.contains("right: Cancelled"));
+        assert!(!report.is_success());
+    }
+
+    /// A test to make sure failures are summarized with their output
+    #[test]
+    fn summarizes_failures() {
+        let notification = TestReport::parse(OUTPUT).to_notification();
+        assert_eq!(
+            notification.message,
+            "1 of 3 tests failed: orders::cancels_order"
+        );
+        assert_eq!(notification.severity, Severity::Error);
+        assert_eq!(notification.context[1].value, "1");
+        assert_eq!(notification.sections[0].title, "orders::cancels_order");
+        assert!(notification.sections[0].context[0]
+            .value
+            .starts_with("thread 'orders::cancels_order' panicked"));
+
+        let notification = TestReport::parse("").to_notification();
+        assert_eq!(notification.message, "All 0 tests passed");
+        assert_eq!(notification.severity, Severity::Info);
+    }
+}
@@ -0,0 +1,288 @@
+//! Helpers for testing code that sends notifications, without a real destination
+
+use crate::{DeliveryReceipt, Endpoint, Notification};
+use crate::{Error, Notifier, NotifierBuilder};
+use reqwest::StatusCode;
+#[cfg(test)]
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A destination that records delivered notifications in memory
+///
+/// Send to the `Endpoint` returned by `endpoint` as usual, every
+/// `Notifier` setting (hooks, thresholds, sampling) still applies.
+#[derive(Clone, Debug, Default)]
+pub struct MockDestination {
+    delivered: Arc<Mutex<Vec<Notification>>>,
+}
+impl MockDestination {
+    /// Create an empty `MockDestination`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An `Endpoint` delivering to the `MockDestination`
+    pub fn endpoint(&self) -> Endpoint {
+        self.endpoint_at("mock://dev-notify")
+    }
+
+    /// An `Endpoint` with a given URL delivering to the `MockDestination`,
+    /// to stand in for a real destination
+    pub fn endpoint_at(&self, url: impl Into<String>) -> Endpoint {
+        Endpoint::new(url).mock(self.clone())
+    }
+
+    /// The notifications delivered so far, oldest first
+    pub fn delivered(&self) -> Vec<Notification> {
+        self.lock().clone()
+    }
+
+    /// The number of notifications delivered so far
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no notifications have been delivered yet
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Forget every notification delivered so far
+    pub fn clear(&self) {
+        self.lock().clear()
+    }
+
+    /// Assert that a notification with a given message was delivered
+    #[track_caller]
+    pub fn assert_delivered(&self, message: &str) {
+        let delivered = self.lock();
+        assert!(
+            delivered.iter().any(|n| n.message == message),
+            "no notification with message {message:?} was delivered, got {:?}",
+            delivered.iter().map(|n| &n.message).collect::<Vec<_>>()
+        )
+    }
+
+    /// Record a delivered notification
+    pub(crate) fn record(&self, notification: Notification) -> DeliveryReceipt {
+        self.lock().push(notification);
+        DeliveryReceipt::new(StatusCode::OK)
+    }
+
+    /// Lock the delivered notifications, even if a panicking test poisoned them
+    fn lock(&self) -> MutexGuard<'_, Vec<Notification>> {
+        self.delivered.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// An HTTP server on a local port, answering each request with the next of
+/// given responses (repeating the last one) and recording the requests
+#[cfg(test)]
+pub(crate) struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+#[cfg(test)]
+impl MockServer {
+    /// Start a `MockServer` answering with given statuses and bodies
+    pub(crate) fn new(responses: Vec<(u16, String)>) -> Self {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        std::thread::spawn(move || {
+            for (idx, stream) in listener.incoming().enumerate() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let request = read_request(&mut stream);
+                recorded.lock().unwrap().push(request);
+                let (status, body) = &responses[idx.min(responses.len() - 1)];
+                let response = format!(
+                    "HTTP/1.1 {status} Mock\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// The URL of the `MockServer`, without a trailing slash
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The request lines (e.g. `GET /events HTTP/1.1`) received so far
+    pub(crate) fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Read a request up to the end of its body, returning its request line
+#[cfg(test)]
+fn read_request(stream: &mut std::net::TcpStream) -> String {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    let _ = reader.read_line(&mut request_line);
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).unwrap_or(0) == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let _ = reader.read_exact(&mut vec![0; content_length]);
+
+    request_line.trim().to_string()
+}
+
+/// A `Notifier` recording every notification it delivers instead of sending
+/// it, to assert which alerts fired during a scenario test
+///
+/// Hand `notifier` to the application code under test, whichever destination
+/// it sends to, nothing leaves the process. Clones share the same recording.
+#[derive(Clone)]
+pub struct Recording {
+    notifier: Arc<Notifier>,
+    sent: MockDestination,
+}
+impl Recording {
+    /// Record what a given `Notifier` delivers
+    fn new(notifier: Notifier) -> Self {
+        let sent = MockDestination::new();
+        Self {
+            notifier: Arc::new(notifier.record_to(sent.clone())),
+            sent,
+        }
+    }
+
+    /// The recording `Notifier`, for the application code under test
+    pub fn notifier(&self) -> Arc<Notifier> {
+        Arc::clone(&self.notifier)
+    }
+
+    /// The notifications delivered so far, oldest first, once per destination
+    pub fn sent(&self) -> Vec<Notification> {
+        self.sent.delivered()
+    }
+
+    /// The notifications delivered so far with a context entry labeled
+    /// `label` (ignoring case), in or out of sections
+    pub fn find_by_label(&self, label: &str) -> Vec<Notification> {
+        self.sent()
+            .into_iter()
+            .filter(|notification| {
+                notification
+                    .context
+                    .iter()
+                    .chain(notification.sections.iter().flat_map(|s| &s.context))
+                    .any(|ctx| ctx.label.eq_ignore_ascii_case(label))
+            })
+            .collect()
+    }
+
+    /// Forget every notification delivered so far
+    pub fn clear(&self) {
+        self.sent.clear()
+    }
+}
+impl Notifier {
+    /// Create a `Notifier` recording what it delivers instead of sending it,
+    /// see `Recording`
+    pub fn recording() -> Recording {
+        Recording::new(Notifier::new())
+    }
+}
+impl NotifierBuilder {
+    /// Build a `Notifier` with this configuration (hooks, thresholds, routes)
+    /// recording what it delivers instead of sending it, see `Recording`
+    pub fn build_recording(self) -> Result<Recording, Error> {
+        self.build().map(Recording::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockDestination;
+    use crate::{Context, ContextKind, Endpoint, Notification, Notifier, Route, Severity};
+
+    /// A test to make sure the mock records what's delivered to it
+    #[tokio::test]
+    async fn records_delivered_notifications() {
+        let mock = MockDestination::new();
+        let notifier = Notifier::builder()
+            .min_severity(Severity::Error)
+            .build()
+            .unwrap();
+
+        for (message, severity) in [("Debugging", Severity::Debug), ("Failed", Severity::Error)] {
+            let notification = Notification {
+                message: String::from(message),
+                timestamp: String::from("2024-01-19 19:26:20.022233"),
+                context: vec![Context {
+                    label: String::from("Customer ID"),
+                    value: String::from("0"),
+                    kind: ContextKind::Text,
+                }],
+                severity,
+                idempotency_key: None,
+                fingerprint: None,
+                sections: Vec::new(),
+                attachments: Vec::new(),
+                labels: None,
+                channel: None,
+                id: None,
+            };
+            notifier.send(notification, &mock.endpoint()).await.unwrap();
+        }
+
+        assert_eq!(mock.len(), 1);
+        mock.assert_delivered("Failed");
+    }
+
+    /// A test to make sure a recording notifier captures what application code sends
+    #[tokio::test]
+    async fn records_scenario() {
+        let recording = Notifier::recording();
+        let notifier = recording.notifier();
+        notifier
+            .send(
+                Notification::new("Payment failed").with_context("Order ID", 1042),
+                &Endpoint::new("https://hooks.slack.com/services/T0/B0/X"),
+            )
+            .await
+            .unwrap();
+        notifier.notify(Notification::new("Cache cold")).await;
+
+        assert_eq!(recording.sent().len(), 2);
+        let orders = recording.find_by_label("order id");
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].message, "Payment failed");
+        recording.clear();
+        assert!(recording.clone().sent().is_empty());
+
+        // Routes and thresholds of the builder still apply
+        let recording = Notifier::builder()
+            .min_severity(Severity::Warning)
+            .destination("oncall", Endpoint::new("https://oncall.internal"))
+            .route(Route::new(["oncall"]).min_severity(Severity::Critical))
+            .build_recording()
+            .unwrap();
+        recording
+            .notifier()
+            .notify(Notification::new("Disk full"))
+            .await;
+        let mut critical = Notification::new("Database down");
+        critical.severity = Severity::Critical;
+        recording.notifier().notify(critical).await;
+        assert_eq!(recording.sent().len(), 1);
+        assert_eq!(recording.sent()[0].message, "Database down");
+    }
+}
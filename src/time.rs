@@ -0,0 +1,6 @@
+// `std::time::Instant` and `SystemTime` panic on `wasm32-unknown-unknown`,
+// so use the equivalents backed by the JavaScript clock there
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::{Instant, SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::{Instant, SystemTime, UNIX_EPOCH};
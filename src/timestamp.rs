@@ -0,0 +1,257 @@
+use crate::time::{SystemTime, UNIX_EPOCH};
+use crate::Locale;
+
+/// How the `Notifier` renders timestamps generated by this crate, instead of
+/// the default UTC format like `2024-01-19 19:26:20.022233`
+///
+/// Timestamps set by hand are sent as they are.
+#[derive(Clone, Debug)]
+pub enum TimestampFormat {
+    /// A `strftime`-like pattern at a fixed offset from UTC, supporting
+    /// `%Y`, `%m`, `%d`, `%H`, `%M`, `%S`, `%f` (microseconds), `%z`, `%%`,
+    /// and `%B` and `%A` for month and weekday names in the `Locale` of the `Notifier`
+    Pattern {
+        pattern: String,
+        utc_offset_minutes: i32,
+    },
+    /// Slack's `<!date^...>` syntax, rendered in each reader's own timezone
+    /// using Slack date tokens like `{date_short_pretty} {time_secs}`
+    Slack { tokens: String },
+}
+impl TimestampFormat {
+    /// Render a timestamp generated by this crate, or `None` for any other timestamp
+    pub(crate) fn reformat(&self, timestamp: &str, locale: &Locale) -> Option<String> {
+        let (secs, micros) = parse(timestamp)?;
+        Some(match self {
+            Self::Pattern {
+                pattern,
+                utc_offset_minutes,
+            } => {
+                let local = secs.saturating_add_signed(i64::from(*utc_offset_minutes) * 60);
+                format_pattern(pattern, local, micros, *utc_offset_minutes, locale)
+            }
+            Self::Slack { tokens } => format!("<!date^{secs}^{tokens}|{timestamp} UTC>"),
+        })
+    }
+}
+
+/// The current UTC time, formatted like `2024-01-19 19:26:20.022233`
+pub(crate) fn now() -> String {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format_unix(since_epoch.as_secs(), since_epoch.subsec_micros())
+}
+
+/// Format seconds (and microseconds) since the Unix epoch as a UTC timestamp
+fn format_unix(secs: u64, micros: u32) -> String {
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}.{micros:06}",
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60,
+    )
+}
+
+/// Format seconds (and microseconds) since the Unix epoch with a `strftime`-like pattern
+fn format_pattern(
+    pattern: &str,
+    secs: u64,
+    micros: u32,
+    utc_offset_minutes: i32,
+    locale: &Locale,
+) -> String {
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days);
+
+    let mut formatted = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            formatted.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => formatted.push_str(&format!("{year:04}")),
+            Some('m') => formatted.push_str(&format!("{month:02}")),
+            Some('d') => formatted.push_str(&format!("{day:02}")),
+            Some('B') => formatted.push_str(locale.month(month as usize)),
+            // The Unix epoch was a Thursday
+            Some('A') => formatted.push_str(locale.weekday(((days + 3) % 7) as usize)),
+            Some('H') => formatted.push_str(&format!("{:02}", secs_of_day / 3_600)),
+            Some('M') => formatted.push_str(&format!("{:02}", secs_of_day % 3_600 / 60)),
+            Some('S') => formatted.push_str(&format!("{:02}", secs_of_day % 60)),
+            Some('f') => formatted.push_str(&format!("{micros:06}")),
+            Some('z') => {
+                let sign = if utc_offset_minutes < 0 { '-' } else { '+' };
+                let offset = utc_offset_minutes.unsigned_abs();
+                formatted.push_str(&format!("{sign}{:02}:{:02}", offset / 60, offset % 60));
+            }
+            Some('%') => formatted.push('%'),
+            // Leave unsupported specifiers as they are
+            Some(other) => {
+                formatted.push('%');
+                formatted.push(other);
+            }
+            None => formatted.push('%'),
+        }
+    }
+
+    formatted
+}
+
+/// Parse a timestamp formatted like `2024-01-19 19:26:20.022233` into
+/// seconds (and microseconds) since the Unix epoch
+pub(crate) fn parse(timestamp: &str) -> Option<(u64, u32)> {
+    let bytes = timestamp.as_bytes();
+    let separators = [
+        (4, b'-'),
+        (7, b'-'),
+        (10, b' '),
+        (13, b':'),
+        (16, b':'),
+        (19, b'.'),
+    ];
+    if bytes.len() != 26 || separators.iter().any(|&(idx, sep)| bytes[idx] != sep) {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| -> Option<u64> {
+        let digits = timestamp.get(range)?;
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse().ok())?
+    };
+
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    let micros = u32::try_from(number(20..26)?).ok()?;
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day)?;
+    Some((days * 86_400 + hour * 3_600 + minute * 60 + second, micros))
+}
+
+/// Convert a (year, month, day) date into days since the Unix epoch
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+fn days_from_civil(year: u64, month: u64, day: u64) -> Option<u64> {
+    let year = if month <= 2 {
+        year.checked_sub(1)?
+    } else {
+        year
+    };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let month_from_march = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    (era * 146_097 + day_of_era).checked_sub(719_468)
+}
+
+/// Convert days since the Unix epoch into a (year, month, day) date
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_unix, parse, TimestampFormat};
+    use crate::Locale;
+
+    /// A test to make sure timestamps are formatted like the examples
+    #[test]
+    fn can_format_timestamp() {
+        assert_eq!(format_unix(0, 0), "1970-01-01 00:00:00.000000");
+        assert_eq!(
+            format_unix(1_705_692_380, 22_233),
+            "2024-01-19 19:26:20.022233"
+        );
+        assert_eq!(format_unix(951_782_400, 0), "2000-02-29 00:00:00.000000");
+    }
+
+    /// A test to make sure generated timestamps parse back to the same time
+    #[test]
+    fn can_parse_timestamp() {
+        assert_eq!(
+            parse("2024-01-19 19:26:20.022233"),
+            Some((1_705_692_380, 22_233))
+        );
+        assert_eq!(parse("2000-02-29 00:00:00.000000"), Some((951_782_400, 0)));
+        assert_eq!(parse("1970-01-01 00:00:00.000000"), Some((0, 0)));
+        assert_eq!(parse("19 Jan 2024 19:26"), None);
+        assert_eq!(parse("2024-13-19 19:26:20.022233"), None);
+    }
+
+    /// A test to make sure timestamps are rendered in a given format and timezone
+    #[test]
+    fn can_reformat_timestamp() {
+        let pattern = TimestampFormat::Pattern {
+            pattern: String::from("%d/%m/%Y %H:%M %z"),
+            utc_offset_minutes: -300,
+        };
+        assert_eq!(
+            pattern
+                .reformat("2024-01-19 01:26:20.022233", &Locale::new())
+                .as_deref(),
+            Some("18/01/2024 20:26 -05:00")
+        );
+        assert_eq!(pattern.reformat("yesterday", &Locale::new()), None);
+
+        let slack = TimestampFormat::Slack {
+            tokens: String::from("{date_short_pretty} {time}"),
+        };
+        assert_eq!(
+            slack
+                .reformat("2024-01-19 19:26:20.022233", &Locale::new())
+                .as_deref(),
+            Some("<!date^1705692380^{date_short_pretty} {time}|2024-01-19 19:26:20.022233 UTC>")
+        );
+    }
+
+    /// A test to make sure month and weekday names are rendered in a given locale
+    #[test]
+    fn can_reformat_in_locale() {
+        let pattern = TimestampFormat::Pattern {
+            pattern: String::from("%A, %d. %B %Y"),
+            utc_offset_minutes: 60,
+        };
+        assert_eq!(
+            pattern
+                .reformat("2024-01-19 19:26:20.022233", &Locale::new())
+                .as_deref(),
+            Some("Friday, 19. January 2024")
+        );
+        assert_eq!(
+            pattern
+                .reformat(
+                    "2024-03-31 23:26:20.022233",
+                    &Locale::builtin("de").unwrap()
+                )
+                .as_deref(),
+            Some("Montag, 01. April 2024")
+        );
+    }
+}
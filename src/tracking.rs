@@ -0,0 +1,231 @@
+use crate::{DeliveryReceipt, Endpoint, Error, Notification, Notifier};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Where a tracked notification is at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertState {
+    /// Delivered, nobody picked it up yet
+    Open,
+    /// Someone picked it up
+    Acknowledged,
+    /// Someone resolved it
+    Resolved,
+}
+
+/// The state of a notification tracked by an `AlertStore`, and the message
+/// it was first delivered as
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlertRecord {
+    /// Identifies the notification: its fingerprint, idempotency key or message
+    pub id: String,
+    /// Where the notification is at
+    pub state: AlertState,
+    /// Who acknowledged or resolved it, if known
+    pub by: Option<String>,
+    /// When the state last changed, like `2024-01-19 19:26:20.022233`
+    pub updated_at: String,
+    /// The URL of the destination it was delivered to
+    pub url: String,
+    /// The channel it was posted to (Slack Web API only)
+    pub channel: Option<String>,
+    /// The timestamp identifying the posted message (Slack Web API only)
+    pub ts: Option<String>,
+}
+
+/// Keeps the state of notifications tracked by `NotifierBuilder::alert_store`
+///
+/// Implement it to persist alert state somewhere shared (e.g. a database),
+/// `MemoryAlertStore` keeps it for the life of the process.
+pub trait AlertStore: Send + Sync {
+    /// The record of a notification, if it's tracked
+    fn load(&self, id: &str) -> Option<AlertRecord>;
+
+    /// Insert or replace the record of a notification
+    fn save(&self, record: AlertRecord);
+}
+
+/// An `AlertStore` keeping records in memory
+#[derive(Debug, Default)]
+pub struct MemoryAlertStore {
+    records: Mutex<HashMap<String, AlertRecord>>,
+}
+impl MemoryAlertStore {
+    /// Create an empty `MemoryAlertStore`
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+impl AlertStore for MemoryAlertStore {
+    fn load(&self, id: &str) -> Option<AlertRecord> {
+        let records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.get(id).cloned()
+    }
+
+    fn save(&self, record: AlertRecord) {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.insert(record.id.clone(), record);
+    }
+}
+
+impl Notification {
+    /// Identifies the notification when tracked or acted on from Slack:
+    /// its fingerprint, idempotency key or message
+    pub(crate) fn alert_id(&self) -> &str {
+        self.fingerprint
+            .as_ref()
+            .or(self.idempotency_key.as_ref())
+            .unwrap_or(&self.message)
+    }
+}
+
+impl Notifier {
+    /// Mark a tracked notification as acknowledged, replying in the thread
+    /// of the message it was delivered as (Slack Web API only)
+    ///
+    /// Returns the updated record, or `None` if the notification isn't tracked.
+    pub async fn acknowledge(
+        &self,
+        id: &str,
+        by: Option<&str>,
+    ) -> Result<Option<AlertRecord>, Error> {
+        self.close(id, AlertState::Acknowledged, by).await
+    }
+
+    /// Mark a tracked notification as resolved, replying in the thread
+    /// of the message it was delivered as (Slack Web API only)
+    ///
+    /// Returns the updated record, or `None` if the notification isn't tracked.
+    pub async fn resolve(&self, id: &str) -> Result<Option<AlertRecord>, Error> {
+        self.close(id, AlertState::Resolved, None).await
+    }
+
+    /// Track a delivered notification as open, unless it's already open or
+    /// acknowledged, so recurrences stay threaded under the first message
+    pub(crate) fn track(
+        &self,
+        notification: Notification,
+        destination: &Endpoint,
+        receipt: &DeliveryReceipt,
+    ) {
+        let Some(store) = self.alert_store() else {
+            return;
+        };
+        let id = notification.alert_id();
+        if store
+            .load(id)
+            .is_some_and(|record| record.state != AlertState::Resolved)
+        {
+            return;
+        }
+
+        store.save(AlertRecord {
+            id: id.to_string(),
+            state: AlertState::Open,
+            by: None,
+            updated_at: crate::timestamp::now(),
+            url: destination.url().to_string(),
+            channel: receipt.channel.clone(),
+            ts: receipt.ts.clone(),
+        });
+        #[cfg(feature = "escalation")]
+        if let Some(escalator) = self.escalator() {
+            escalator.watch(notification);
+        }
+    }
+
+    /// Move a tracked notification to a given state
+    pub(crate) fn transition(
+        &self,
+        id: &str,
+        state: AlertState,
+        by: Option<&str>,
+    ) -> Option<AlertRecord> {
+        let store = self.alert_store()?;
+        let mut record = store.load(id)?;
+        record.state = state;
+        record.by = by.map(String::from);
+        record.updated_at = crate::timestamp::now();
+        store.save(record.clone());
+
+        Some(record)
+    }
+
+    /// Move a tracked notification to a given state and note it in the
+    /// thread of its message, through the named destination it was delivered to
+    async fn close(
+        &self,
+        id: &str,
+        state: AlertState,
+        by: Option<&str>,
+    ) -> Result<Option<AlertRecord>, Error> {
+        let Some(record) = self.transition(id, state, by) else {
+            return Ok(None);
+        };
+        let (Some(channel), Some(ts)) = (&record.channel, &record.ts) else {
+            return Ok(Some(record));
+        };
+        let Some(destination) = self.destination_at(&record.url) else {
+            return Ok(Some(record));
+        };
+
+        let done = match state {
+            AlertState::Open => "Reopened",
+            AlertState::Acknowledged => "Acknowledged",
+            AlertState::Resolved => "Resolved",
+        };
+        let text = match by {
+            Some(by) => format!(":white_check_mark: {done} by {by}"),
+            None => format!(":white_check_mark: {done}"),
+        };
+        let body = json!({ "channel": channel, "thread_ts": ts, "text": text });
+        let request = destination
+            .post_body(self.http_client(), body.to_string())
+            .header("Content-type", "application/json");
+        crate::runtime::send_checked(request).await?;
+
+        Ok(Some(record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AlertState, MemoryAlertStore};
+    use crate::testing::MockDestination;
+    use crate::{Notification, Notifier};
+
+    /// A test to make sure delivered notifications are tracked until resolved
+    #[tokio::test]
+    async fn tracks_until_resolved() {
+        let mock = MockDestination::new();
+        let notifier = Notifier::builder()
+            .alert_store(MemoryAlertStore::new())
+            .destination("slack", mock.endpoint())
+            .build()
+            .unwrap();
+
+        let mut notification = Notification::new("Disk full");
+        notification.fingerprint = Some(String::from("disk-full"));
+        notifier.notify(notification.clone()).await;
+        let store = notifier.alert_store().unwrap();
+        assert_eq!(store.load("disk-full").unwrap().state, AlertState::Open);
+
+        let acknowledged = notifier.acknowledge("disk-full", Some("jane")).await;
+        let acknowledged = acknowledged.unwrap().unwrap();
+        assert_eq!(acknowledged.state, AlertState::Acknowledged);
+        assert_eq!(acknowledged.by.as_deref(), Some("jane"));
+
+        // Recurrences don't reopen an acknowledged alert
+        notifier.notify(notification.clone()).await;
+        let record = store.load("disk-full").unwrap();
+        assert_eq!(record.state, AlertState::Acknowledged);
+
+        let resolved = notifier.resolve("disk-full").await.unwrap().unwrap();
+        assert_eq!((resolved.state, resolved.by), (AlertState::Resolved, None));
+        notifier.notify(notification).await;
+        assert_eq!(store.load("disk-full").unwrap().state, AlertState::Open);
+
+        assert!(notifier.resolve("unknown").await.unwrap().is_none());
+    }
+}
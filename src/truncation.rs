@@ -0,0 +1,108 @@
+use crate::{Attachment, Context, ContextKind, Notification};
+
+/// A limit on the length of context values, so one huge value (e.g. a SQL
+/// query) doesn't dominate a message
+///
+/// Longer values are cut short with an ellipsis, and optionally attached in
+/// full. Links are never cut, as that would break them.
+#[derive(Clone, Copy, Debug)]
+pub struct ContextLimit {
+    max_len: usize,
+    attach_full: bool,
+}
+impl ContextLimit {
+    /// Create a `ContextLimit` of a given number of characters per value
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            max_len: max_len.max(1),
+            attach_full: false,
+        }
+    }
+
+    /// Attach the full value of every truncated context, named after its label
+    pub fn attach_full(mut self) -> Self {
+        self.attach_full = true;
+        self
+    }
+
+    /// Truncate the context values of a `Notification` over the limit,
+    /// sections included
+    pub(crate) fn apply(&self, mut notification: Notification) -> Notification {
+        let mut attachments = Vec::new();
+        let contexts = notification.context.iter_mut().chain(
+            notification
+                .sections
+                .iter_mut()
+                .flat_map(|s| &mut s.context),
+        );
+        for ctx in contexts {
+            if let Some(full) = self.truncate(ctx) {
+                if self.attach_full {
+                    attachments.push(Attachment {
+                        filename: format!("{}.txt", ctx.label),
+                        content: full,
+                    });
+                }
+            }
+        }
+        notification.attachments.extend(attachments);
+
+        notification
+    }
+
+    /// Truncate the value of a `Context` over the limit, returning the full value
+    fn truncate(&self, ctx: &mut Context) -> Option<String> {
+        if matches!(ctx.kind, ContextKind::Link { .. }) {
+            return None;
+        }
+        let (end, _) = ctx.value.char_indices().nth(self.max_len)?;
+
+        // Leave room for the ellipsis
+        let end = ctx.value[..end]
+            .char_indices()
+            .last()
+            .map_or(0, |(idx, _)| idx);
+        let mut truncated = ctx.value[..end].to_string();
+        truncated.push('…');
+
+        Some(std::mem::replace(&mut ctx.value, truncated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContextLimit;
+    use crate::{Context, Notification, Section};
+
+    /// A test to make sure long values are truncated with an ellipsis, and attached if set
+    #[test]
+    fn truncates_long_values() {
+        let query = format!("SELECT {} FROM orders", "id, ".repeat(2500));
+        let mut notification = Notification::new("Slow query")
+            .with_context("Query", &query)
+            .with_context("Rows", 12)
+            .with_section(Section::new("Request").with_context("Body", "é".repeat(30)));
+        notification.context.push(Context::link(
+            "Trace",
+            format!("https://traces.example.com/{}", "a".repeat(50)),
+            "View",
+        ));
+
+        let limited = ContextLimit::new(20).apply(notification.clone());
+        assert_eq!(limited.context[0].value, "SELECT id, id, id, …");
+        assert_eq!(limited.context[0].value.chars().count(), 20);
+        assert_eq!(limited.context[1].value, "12");
+        assert_eq!(limited.context[2].value, notification.context[2].value);
+        assert_eq!(
+            limited.sections[0].context[0].value,
+            format!("{}…", "é".repeat(19))
+        );
+        assert!(limited.attachments.is_empty());
+
+        let limited = ContextLimit::new(20).attach_full().apply(notification);
+        assert_eq!(limited.attachments.len(), 2);
+        assert_eq!(limited.attachments[0].filename, "Query.txt");
+        assert_eq!(limited.attachments[0].content, query);
+        assert_eq!(limited.attachments[1].filename, "Body.txt");
+    }
+}
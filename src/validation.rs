@@ -0,0 +1,391 @@
+use crate::{Attachment, Context, ContextKind, Labels, Notification, Section, Severity};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+
+/// The most context entries a `Notification` can carry, across its sections
+const MAX_CONTEXT_ENTRIES: usize = 100;
+
+/// The most characters in a single context label or value
+const MAX_CONTEXT_LENGTH: usize = 10_000;
+
+/// The most characters in a message checked by `NotificationBuilder::build`
+const MAX_MESSAGE_LENGTH: usize = 10_000;
+
+/// Why a `Notification` failed validation, e.g. one received from a buggy producer
+#[derive(Debug)]
+pub struct ValidationError(String);
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid notification: {}", self.0)
+    }
+}
+impl std::error::Error for ValidationError {}
+
+impl Notification {
+    /// Check the `Notification` is well formed: a non-empty message, a timestamp
+    /// formatted like `2024-01-19 19:26:20.022233`, and at most 100 context
+    /// entries of at most 10000 characters each
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.message.trim().is_empty() {
+            return Err(ValidationError(String::from("message is empty")));
+        }
+        if crate::timestamp::parse(&self.timestamp).is_none() {
+            return Err(ValidationError(format!(
+                "timestamp `{}` isn't formatted like `2024-01-19 19:26:20.022233`",
+                self.timestamp
+            )));
+        }
+
+        let context: Vec<&Context> = self
+            .context
+            .iter()
+            .chain(self.sections.iter().flat_map(|s| &s.context))
+            .collect();
+        if context.len() > MAX_CONTEXT_ENTRIES {
+            return Err(ValidationError(format!(
+                "{} context entries, at most {MAX_CONTEXT_ENTRIES} are allowed",
+                context.len()
+            )));
+        }
+        if let Some(ctx) = context.iter().find(|ctx| {
+            ctx.label.chars().count() > MAX_CONTEXT_LENGTH
+                || ctx.value.chars().count() > MAX_CONTEXT_LENGTH
+        }) {
+            return Err(ValidationError(format!(
+                "context `{}` is longer than {MAX_CONTEXT_LENGTH} characters",
+                ctx.label.chars().take(64).collect::<String>()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Start building a `Notification` with a given message, checked when it's built
+    ///
+    /// ```
+    /// # use dev_notify::Notification;
+    /// let notification = Notification::builder("Payment failed")
+    ///     .context("Order ID", 1042)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder(message: impl Into<String>) -> NotificationBuilder {
+        NotificationBuilder {
+            notification: Self::new(message),
+        }
+    }
+
+    /// Parse a `Notification` from JSON, rejecting unknown fields (e.g. a
+    /// misspelled `severty`) and validating it, see `Notification::validate`
+    pub fn from_json_strict(json: &str) -> Result<Self, ValidationError> {
+        let notification: StrictNotification =
+            serde_json::from_str(json).map_err(|e| ValidationError(e.to_string()))?;
+        let notification = Self::from(notification);
+        notification.validate()?;
+        Ok(notification)
+    }
+}
+
+/// Builder for a `Notification`, catching mistakes before they reach a destination
+pub struct NotificationBuilder {
+    notification: Notification,
+}
+impl NotificationBuilder {
+    /// Add a labelled value to the context
+    pub fn context(mut self, label: impl Into<String>, value: impl ToString) -> Self {
+        self.notification = self.notification.with_context(label, value);
+        self
+    }
+
+    /// Add a `Section` of context, rendered as its own block
+    pub fn section(mut self, section: Section) -> Self {
+        self.notification.sections.push(section);
+        self
+    }
+
+    /// Set the `Severity`, `Severity::Error` by default
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.notification.severity = severity;
+        self
+    }
+
+    /// Render the message with given built-in labels, e.g. "Alert" instead of "Issue"
+    pub fn labels(mut self, labels: Labels) -> Self {
+        self.notification.labels = Some(labels);
+        self
+    }
+
+    /// Post to a given channel instead of the channel of the destination
+    pub fn channel(mut self, channel: impl Into<String>) -> Self {
+        self.notification.channel = Some(channel.into());
+        self
+    }
+
+    /// Attach a text payload under a given filename, e.g. a log excerpt
+    pub fn attachment(mut self, filename: impl Into<String>, content: impl Into<String>) -> Self {
+        self.notification = self.notification.with_attachment(filename, content);
+        self
+    }
+
+    /// Group notifications for the same underlying issue by a given fingerprint
+    pub fn fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.notification.fingerprint = Some(fingerprint.into());
+        self
+    }
+
+    /// Build the `Notification`, which must pass `Notification::validate`,
+    /// with a message of at most 10000 characters and no label repeated
+    /// within the context or a section
+    pub fn build(self) -> Result<Notification, ValidationError> {
+        let notification = self.notification;
+        notification.validate()?;
+        if notification.message.chars().count() > MAX_MESSAGE_LENGTH {
+            return Err(ValidationError(format!(
+                "message is longer than {MAX_MESSAGE_LENGTH} characters"
+            )));
+        }
+        let lists = std::iter::once(("context", &notification.context)).chain(
+            notification
+                .sections
+                .iter()
+                .map(|s| (s.title.as_str(), &s.context)),
+        );
+        for (list, context) in lists {
+            let mut labels = HashSet::new();
+            if let Some(ctx) = context.iter().find(|ctx| !labels.insert(&ctx.label)) {
+                return Err(ValidationError(format!(
+                    "label `{}` is repeated in {list}",
+                    ctx.label
+                )));
+            }
+        }
+
+        Ok(notification)
+    }
+}
+
+/// A `Notification` as parsed by `Notification::from_json_strict`, rejecting
+/// unknown fields
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StrictNotification {
+    message: String,
+    timestamp: String,
+    context: Vec<StrictContext>,
+    #[serde(default)]
+    severity: Severity,
+    #[serde(default)]
+    idempotency_key: Option<String>,
+    #[serde(default)]
+    fingerprint: Option<String>,
+    #[serde(default)]
+    sections: Vec<StrictSection>,
+    #[serde(default)]
+    attachments: Vec<StrictAttachment>,
+    #[serde(default)]
+    labels: Option<StrictLabels>,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    id: Option<String>,
+}
+impl From<StrictNotification> for Notification {
+    fn from(strict: StrictNotification) -> Self {
+        Self {
+            message: strict.message,
+            timestamp: strict.timestamp,
+            context: strict.context.into_iter().map(Context::from).collect(),
+            severity: strict.severity,
+            idempotency_key: strict.idempotency_key,
+            fingerprint: strict.fingerprint,
+            sections: strict.sections.into_iter().map(Section::from).collect(),
+            attachments: strict
+                .attachments
+                .into_iter()
+                .map(Attachment::from)
+                .collect(),
+            labels: strict.labels.map(Labels::from),
+            channel: strict.channel,
+            id: strict.id,
+        }
+    }
+}
+
+/// A `Context` as parsed by `Notification::from_json_strict`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StrictContext {
+    label: String,
+    value: String,
+    #[serde(default)]
+    kind: ContextKind,
+}
+impl From<StrictContext> for Context {
+    fn from(strict: StrictContext) -> Self {
+        Self {
+            label: strict.label,
+            value: strict.value,
+            kind: strict.kind,
+        }
+    }
+}
+
+/// A `Section` as parsed by `Notification::from_json_strict`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StrictSection {
+    title: String,
+    context: Vec<StrictContext>,
+}
+impl From<StrictSection> for Section {
+    fn from(strict: StrictSection) -> Self {
+        Self {
+            title: strict.title,
+            context: strict.context.into_iter().map(Context::from).collect(),
+        }
+    }
+}
+
+/// An `Attachment` as parsed by `Notification::from_json_strict`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StrictAttachment {
+    filename: String,
+    content: String,
+}
+impl From<StrictAttachment> for Attachment {
+    fn from(strict: StrictAttachment) -> Self {
+        Self {
+            filename: strict.filename,
+            content: strict.content,
+        }
+    }
+}
+
+/// `Labels` as parsed by `Notification::from_json_strict`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StrictLabels {
+    issue: String,
+    timestamp: String,
+}
+impl From<StrictLabels> for Labels {
+    fn from(strict: StrictLabels) -> Self {
+        Self::new(strict.issue, strict.timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Context, Labels, Notification, Section, Severity};
+
+    /// A test to make sure well formed notifications pass validation
+    #[test]
+    fn accepts_valid_notification() {
+        let notification = Notification::new("Payment failed")
+            .with_context("Order ID", 1042)
+            .with_section(Section::new("Request").with_context("Path", "/checkout"));
+        assert!(notification.validate().is_ok());
+    }
+
+    /// A test to make sure malformed notifications fail validation
+    #[test]
+    fn rejects_invalid_notification() {
+        assert!(Notification::new("  ").validate().is_err());
+
+        let mut notification = Notification::new("Payment failed");
+        notification.timestamp = String::from("yesterday");
+        assert!(notification.validate().is_err());
+
+        let mut notification = Notification::new("Payment failed");
+        for idx in 0..101 {
+            notification = notification.with_context("Order ID", idx);
+        }
+        assert!(notification.validate().is_err());
+
+        let notification =
+            Notification::new("Payment failed").with_context("Body", "x".repeat(10_001));
+        assert!(notification.validate().is_err());
+    }
+
+    /// A test to make sure building a notification rejects empty messages, repeated labels and long fields
+    #[test]
+    fn checks_built_notification() {
+        let notification = Notification::builder("Payment failed")
+            .context("Order ID", 1042)
+            .section(Section::new("Request").with_context("Order ID", 1042))
+            .severity(Severity::Warning)
+            .build()
+            .unwrap();
+        assert_eq!(notification.severity, Severity::Warning);
+
+        assert!(Notification::builder("").build().is_err());
+        assert!(Notification::builder("x".repeat(10_001)).build().is_err());
+        assert!(Notification::builder("Payment failed")
+            .context("Body", "x".repeat(10_001))
+            .build()
+            .is_err());
+
+        let error = Notification::builder("Payment failed")
+            .context("Order ID", 1042)
+            .context("Order ID", 1043)
+            .build()
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("label `Order ID` is repeated in context"));
+
+        let section = Section::new("Request")
+            .with_context("Path", "/checkout")
+            .with_context("Path", "/cart");
+        let error = Notification::builder("Payment failed")
+            .section(section)
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("repeated in Request"));
+    }
+
+    /// A test to make sure strict parsing accepts every field of a serialized notification
+    #[test]
+    fn parses_every_field() {
+        let mut notification = Notification::new("Payment failed")
+            .with_context("Order ID", 1042)
+            .with_section(Section::new("Request").with_context("Path", "/checkout"))
+            .with_attachment("response.json", "{}")
+            .with_labels(Labels::default())
+            .with_channel("#payments");
+        notification
+            .context
+            .push(Context::code("Backtrace", "main"));
+        notification.idempotency_key = Some(String::from("order-1042"));
+        notification.fingerprint = Some(String::from("payment-failed"));
+
+        let json = serde_json::to_string(&notification).unwrap();
+        let parsed = Notification::from_json_strict(&json).unwrap();
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+    }
+
+    /// A test to make sure strict parsing rejects unknown fields
+    #[test]
+    fn rejects_unknown_fields() {
+        let json = r#"{
+            "message": "Payment failed",
+            "timestamp": "2024-01-19 19:26:20.022233",
+            "context": [{ "label": "Order ID", "value": "1042" }]
+        }"#;
+        assert!(Notification::from_json_strict(json).is_ok());
+
+        let misspelled = json.replace("\"message\"", "\"mesage\"");
+        let error = Notification::from_json_strict(&misspelled).unwrap_err();
+        assert!(error.to_string().contains("unknown field `mesage`"));
+
+        let nested = json.replace("\"value\"", "\"valeu\"");
+        assert!(Notification::from_json_strict(&nested).is_err());
+
+        let serialized =
+            serde_json::to_string(&Notification::new("Payment failed").with_channel("#payments"))
+                .unwrap();
+        assert!(Notification::from_json_strict(&serialized).is_ok());
+    }
+}
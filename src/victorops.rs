@@ -0,0 +1,104 @@
+use crate::render::plain_text;
+use crate::{
+    Endpoint, Error, Formatter, MessageFormat, Notification, OversizePolicy, RenderedPayload,
+    Severity,
+};
+use serde_json::{json, Value};
+
+/// The REST endpoint integration of Splunk On-Call (VictorOps)
+const REST_ENDPOINT: &str = "https://alert.victorops.com/integrations/generic/20131114/alert";
+
+/// The most characters in the details of an alert, keeping it readable in
+/// the incident timeline
+const MAX_STATE_MESSAGE: usize = 10_000;
+
+impl Endpoint {
+    /// Create an `Endpoint` raising Splunk On-Call (VictorOps) alerts through
+    /// the REST endpoint integration, routed to the teams of a routing key
+    pub fn victorops(api_key: &str, routing_key: &str) -> Self {
+        Self::new(format!("{REST_ENDPOINT}/{api_key}/{routing_key}"))
+            .format(MessageFormat::VictorOps)
+    }
+}
+
+/// Renders notifications as Splunk On-Call (VictorOps) alerts, as `MessageFormat::VictorOps`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct VictorOpsFormatter {
+    policy: OversizePolicy,
+}
+impl VictorOpsFormatter {
+    /// Create a `VictorOpsFormatter` with the options of a destination
+    pub(crate) fn for_destination(destination: &Endpoint) -> Self {
+        Self {
+            policy: destination.oversize_policy(),
+        }
+    }
+}
+impl Formatter for VictorOpsFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let payload = notification.clone().into_victorops_payload(self.policy)?;
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into a Splunk On-Call
+    /// (VictorOps) alert, identified by its fingerprint (or message) so
+    /// repeats update the same incident
+    ///
+    /// Critical and error notifications page (`CRITICAL`), warnings are
+    /// `WARNING` and anything lower is `INFO`, which never pages.
+    pub(crate) fn into_victorops_payload(self, policy: OversizePolicy) -> Result<Value, Error> {
+        let state_message = plain_text(&self, MAX_STATE_MESSAGE, policy)?;
+        let message_type = match self.severity {
+            Severity::Critical | Severity::Error => "CRITICAL",
+            Severity::Warning => "WARNING",
+            Severity::Info | Severity::Debug => "INFO",
+        };
+        Ok(json!({
+            "message_type": message_type,
+            "entity_id": self.fingerprint.as_deref().unwrap_or(&self.message),
+            "entity_display_name": self.message,
+            "state_message": state_message,
+            "monitoring_tool": "dev-notify",
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Endpoint, Notification, OversizePolicy, Severity};
+    use serde_json::Value;
+
+    /// A test to make sure notifications raise alerts to the routing key, typed by severity
+    #[test]
+    fn can_parse_into_alert() {
+        let mut notification = Notification::new("Queue backed up").with_context("Depth", 5000);
+        notification.timestamp = String::from("2024-01-19 19:26:20.022233");
+        notification.severity = Severity::Warning;
+
+        let destination = Endpoint::victorops("api-key", "ops");
+        assert_eq!(
+            destination.url(),
+            "https://alert.victorops.com/integrations/generic/20131114/alert/api-key/ops"
+        );
+        let payload: Value =
+            serde_json::from_str(&notification.clone().render_for(&destination).unwrap().body)
+                .unwrap();
+        assert_eq!(payload["message_type"], "WARNING");
+        assert_eq!(payload["entity_id"], "Queue backed up");
+        assert_eq!(payload["entity_display_name"], "Queue backed up");
+        assert_eq!(
+            payload["state_message"],
+            "Timestamp: 2024-01-19 19:26:20.022233\nDepth: 5000"
+        );
+
+        notification.severity = Severity::Critical;
+        notification.fingerprint = Some(String::from("queue-depth"));
+        let payload = notification
+            .into_victorops_payload(OversizePolicy::Reject)
+            .unwrap();
+        assert_eq!(payload["message_type"], "CRITICAL");
+        assert_eq!(payload["entity_id"], "queue-depth");
+    }
+}
@@ -0,0 +1,409 @@
+use crate::{Endpoint, Error, Notification, OversizePolicy, Severity};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::{Client, RequestBuilder};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use ring::{aead, agreement, hkdf};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The most bytes push services take in the body of a message
+const MAX_BODY: usize = 4096;
+
+/// The length of the header of an `aes128gcm` body: salt, record size, key ID
+/// length and the key ID (an uncompressed P-256 public key)
+const HEADER_LEN: usize = 16 + 4 + 1 + 65;
+
+/// How long VAPID tokens are valid for, at most 24 hours per RFC 8292
+const TOKEN_LIFETIME: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// The VAPID key pair (RFC 8292) identifying the sender of push messages to
+/// push services, the public key of which subscriptions are created with
+#[derive(Clone)]
+pub struct Vapid {
+    key_pair: Arc<EcdsaKeyPair>,
+    public_key: String,
+    subject: String,
+}
+impl Vapid {
+    /// Create a `Vapid` key pair from base64url keys: the raw 32 byte
+    /// private key and 65 byte public key (as generated by `web-push
+    /// generate-vapid-keys`), with a contact `subject` like
+    /// `mailto:ops@example.com`
+    pub fn new(
+        public_key: impl Into<String>,
+        private_key: &str,
+        subject: impl Into<String>,
+    ) -> Result<Self, Error> {
+        let public_key = public_key.into();
+        let key_pair = EcdsaKeyPair::from_private_key_and_public_key(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            &decode(private_key, "VAPID private key")?,
+            &decode(&public_key, "VAPID public key")?,
+            &SystemRandom::new(),
+        )
+        .map_err(|e| Error::Config(format!("invalid VAPID key pair: {e}")))?;
+
+        Ok(Self {
+            key_pair: Arc::new(key_pair),
+            public_key,
+            subject: subject.into(),
+        })
+    }
+
+    /// The `Authorization` header for requests to a push service: a signed
+    /// JWT for its origin along with the public key
+    fn authorization(&self, push_url: &str) -> Result<String, Error> {
+        let url = reqwest::Url::parse(push_url)
+            .map_err(|e| Error::Config(format!("invalid push subscription URL: {e}")))?;
+        let expires = crate::time::SystemTime::now()
+            .duration_since(crate::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            + TOKEN_LIFETIME;
+        let header = URL_SAFE_NO_PAD.encode(r#"{"typ":"JWT","alg":"ES256"}"#);
+        let claims = URL_SAFE_NO_PAD.encode(
+            serde_json::json!({
+                "aud": url.origin().ascii_serialization(),
+                "exp": expires.as_secs(),
+                "sub": self.subject,
+            })
+            .to_string(),
+        );
+
+        let unsigned = format!("{header}.{claims}");
+        let signature = self
+            .key_pair
+            .sign(&SystemRandom::new(), unsigned.as_bytes())
+            .map_err(|_| Error::Config(String::from("signing the VAPID token failed")))?;
+        Ok(format!(
+            "vapid t={unsigned}.{}, k={}",
+            URL_SAFE_NO_PAD.encode(signature),
+            self.public_key
+        ))
+    }
+}
+impl fmt::Debug for Vapid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Vapid")
+            .field("public_key", &self.public_key)
+            .field("subject", &self.subject)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A Web Push subscription (e.g. of the service worker of a dashboard)
+/// notifications are pushed to, with the serialized `Notification` as the
+/// encrypted payload (RFC 8291)
+///
+/// The destination is the `endpoint` URL of the subscription, and the keys
+/// are its `p256dh` and `auth` keys.
+#[derive(Clone, Debug)]
+pub struct WebPush {
+    p256dh: Vec<u8>,
+    auth: Vec<u8>,
+    vapid: Vapid,
+    ttl: Duration,
+}
+impl WebPush {
+    /// Create a `WebPush` subscription from its base64url `p256dh` and
+    /// `auth` keys, pushed to with a given `Vapid` key pair
+    pub fn new(p256dh: &str, auth: &str, vapid: Vapid) -> Result<Self, Error> {
+        let p256dh = decode(p256dh, "subscription p256dh key")?;
+        let auth = decode(auth, "subscription auth secret")?;
+        if p256dh.len() != 65 || auth.len() != 16 {
+            return Err(Error::Config(String::from(
+                "a subscription needs a 65 byte p256dh key and a 16 byte auth secret",
+            )));
+        }
+
+        Ok(Self {
+            p256dh,
+            auth,
+            vapid,
+            ttl: Duration::from_secs(24 * 60 * 60),
+        })
+    }
+
+    /// How long the push service keeps messages for while the browser is
+    /// offline (defaults to a day)
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Start the request pushing a `Notification` to the push service, fitting
+    /// it within the limit of push services
+    ///
+    /// Over the limit, attachments, then sections, then context are dropped,
+    /// unless the policy rejects it.
+    pub(crate) fn request(
+        &self,
+        http_client: &Client,
+        destination: &Endpoint,
+        mut notification: Notification,
+    ) -> Result<RequestBuilder, Error> {
+        let urgency = match notification.severity {
+            Severity::Critical | Severity::Error => "high",
+            Severity::Warning => "normal",
+            Severity::Info | Severity::Debug => "low",
+        };
+
+        let mut payload = serde_json::to_vec(&notification).unwrap_or_default();
+        let limit = MAX_BODY - HEADER_LEN - 1 - aead::AES_128_GCM.tag_len();
+        if payload.len() > limit && destination.oversize_policy() == OversizePolicy::Truncate {
+            let trims: [fn(&mut Notification); 3] = [
+                |n| n.attachments.clear(),
+                |n| n.sections.clear(),
+                |n| n.context.clear(),
+            ];
+            for trim in trims {
+                trim(&mut notification);
+                payload = serde_json::to_vec(&notification).unwrap_or_default();
+                if payload.len() <= limit {
+                    break;
+                }
+            }
+        }
+        if payload.len() > limit {
+            return Err(Error::PayloadTooLarge {
+                limit,
+                size: payload.len(),
+            });
+        }
+
+        Ok(destination
+            .post(http_client)
+            .header("Content-Encoding", "aes128gcm")
+            .header("Content-Type", "application/octet-stream")
+            .header("TTL", self.ttl.as_secs())
+            .header("Urgency", urgency)
+            .header(
+                "Authorization",
+                self.vapid.authorization(destination.url())?,
+            )
+            .body(self.encrypt(&payload)?))
+    }
+
+    /// Encrypt a payload for the subscription as a single `aes128gcm` record
+    fn encrypt(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        let failed = |_| Error::Config(String::from("encrypting the push message failed"));
+        let rng = SystemRandom::new();
+        let private_key = agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng)
+            .map_err(failed)?;
+        let public_key = private_key.compute_public_key().map_err(failed)?;
+        let mut salt = [0; 16];
+        rng.fill(&mut salt).map_err(failed)?;
+
+        let subscription_key =
+            agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, &self.p256dh);
+        let (key, nonce) = agreement::agree_ephemeral(private_key, &subscription_key, |secret| {
+            content_keys(secret, &self.auth, &self.p256dh, public_key.as_ref(), &salt)
+        })
+        .map_err(failed)??;
+
+        let mut record = payload.to_vec();
+        // Delimits the last (and only) record
+        record.push(2);
+        aead::LessSafeKey::new(aead::UnboundKey::new(&aead::AES_128_GCM, &key).map_err(failed)?)
+            .seal_in_place_append_tag(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::empty(),
+                &mut record,
+            )
+            .map_err(failed)?;
+
+        let mut body = Vec::with_capacity(HEADER_LEN + record.len());
+        body.extend_from_slice(&salt);
+        body.extend_from_slice(&(MAX_BODY as u32).to_be_bytes());
+        body.push(65);
+        body.extend_from_slice(public_key.as_ref());
+        body.extend_from_slice(&record);
+
+        Ok(body)
+    }
+}
+
+/// A length of keying material to expand to
+struct Len(usize);
+impl hkdf::KeyType for Len {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+/// Derive the content encryption key and nonce of a message from the ECDH
+/// shared secret, the subscription's auth secret and public key, the
+/// sender's public key and the salt (RFC 8291 section 3.4)
+fn content_keys(
+    shared_secret: &[u8],
+    auth: &[u8],
+    subscription_key: &[u8],
+    sender_key: &[u8],
+    salt: &[u8],
+) -> Result<([u8; 16], [u8; 12]), Error> {
+    let failed = |_| Error::Config(String::from("deriving the push message keys failed"));
+    let mut ikm = [0; 32];
+    hkdf::Salt::new(hkdf::HKDF_SHA256, auth)
+        .extract(shared_secret)
+        .expand(&[b"WebPush: info\0", subscription_key, sender_key], Len(32))
+        .and_then(|okm| okm.fill(&mut ikm))
+        .map_err(failed)?;
+
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, salt).extract(&ikm);
+    let mut key = [0; 16];
+    prk.expand(&[b"Content-Encoding: aes128gcm\0"], Len(16))
+        .and_then(|okm| okm.fill(&mut key))
+        .map_err(failed)?;
+    let mut nonce = [0; 12];
+    prk.expand(&[b"Content-Encoding: nonce\0"], Len(12))
+        .and_then(|okm| okm.fill(&mut nonce))
+        .map_err(failed)?;
+
+    Ok((key, nonce))
+}
+
+/// Decode a base64url key, with or without padding
+fn decode(key: &str, name: &str) -> Result<Vec<u8>, Error> {
+    URL_SAFE_NO_PAD
+        .decode(key.trim_end_matches('='))
+        .map_err(|e| Error::Config(format!("invalid {name}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_keys, decode, Vapid, WebPush};
+    use crate::{Endpoint, Notification, Severity};
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use ring::rand::SystemRandom;
+    use ring::{aead, agreement, signature};
+
+    const VAPID_PUBLIC_KEY: &str =
+        "BELjEj-AA_elYP7ks1Swe92-GNG481w2rcgOJ0Jj6jM8bRkXb5rYsFYDVM0orBdoqR_RPZQbQpVgHlNU_p27p38";
+    const VAPID_PRIVATE_KEY: &str = "ytoxGl3zH2-k5NeM3qum_9YqgMJ0mkpfcGUS61nXISQ";
+
+    /// A test to make sure pushed notifications can be decrypted by the subscriber
+    #[test]
+    fn encrypts_for_subscription() {
+        // The browser's side of the subscription
+        let rng = SystemRandom::new();
+        let browser_key =
+            agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng).unwrap();
+        let browser_public_key = browser_key.compute_public_key().unwrap();
+        let auth = [7; 16];
+
+        let vapid = Vapid::new(
+            VAPID_PUBLIC_KEY,
+            VAPID_PRIVATE_KEY,
+            "mailto:ops@example.com",
+        );
+        let web_push = WebPush::new(
+            &URL_SAFE_NO_PAD.encode(browser_public_key.as_ref()),
+            &URL_SAFE_NO_PAD.encode(auth),
+            vapid.unwrap(),
+        )
+        .unwrap();
+        let mut notification = Notification::new("Disk full").with_context("Host", "web-1");
+        notification.severity = Severity::Critical;
+        let destination =
+            Endpoint::new("https://fcm.googleapis.com/fcm/send/abc123").web_push(web_push);
+
+        let request = destination
+            .web_push_config()
+            .unwrap()
+            .request(&reqwest::Client::new(), &destination, notification.clone())
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()["Content-Encoding"], "aes128gcm");
+        assert_eq!(request.headers()["TTL"], "86400");
+        assert_eq!(request.headers()["Urgency"], "high");
+        let body = request.body().unwrap().as_bytes().unwrap();
+
+        // Decrypt as the browser would
+        let (salt, rest) = body.split_at(16);
+        assert_eq!(rest[..4], 4096u32.to_be_bytes());
+        assert_eq!(rest[4], 65);
+        let (sender_key, record) = rest[5..].split_at(65);
+        let (key, nonce) = agreement::agree_ephemeral(
+            browser_key,
+            &agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, sender_key),
+            |secret| content_keys(secret, &auth, browser_public_key.as_ref(), sender_key, salt),
+        )
+        .unwrap()
+        .unwrap();
+        let mut record = record.to_vec();
+        let plaintext =
+            aead::LessSafeKey::new(aead::UnboundKey::new(&aead::AES_128_GCM, &key).unwrap())
+                .open_in_place(
+                    aead::Nonce::assume_unique_for_key(nonce),
+                    aead::Aad::empty(),
+                    &mut record,
+                )
+                .unwrap();
+        assert_eq!(plaintext.last(), Some(&2));
+        let pushed: Notification =
+            serde_json::from_slice(&plaintext[..plaintext.len() - 1]).unwrap();
+        assert_eq!(pushed.message, "Disk full");
+        assert_eq!(pushed.id, notification.id);
+    }
+
+    /// A test to make sure VAPID tokens are signed for the origin of the push service
+    #[test]
+    fn signs_vapid_token() {
+        let vapid = Vapid::new(
+            VAPID_PUBLIC_KEY,
+            VAPID_PRIVATE_KEY,
+            "mailto:ops@example.com",
+        )
+        .unwrap();
+
+        let authorization = vapid
+            .authorization("https://updates.push.services.mozilla.com/wpush/v2/abc")
+            .unwrap();
+        let (token, public_key) = authorization
+            .strip_prefix("vapid t=")
+            .unwrap()
+            .split_once(", k=")
+            .unwrap();
+        assert_eq!(public_key, VAPID_PUBLIC_KEY);
+
+        let (unsigned, signature) = token.rsplit_once('.').unwrap();
+        let claims = unsigned.split('.').nth(1).unwrap();
+        let claims: serde_json::Value =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(claims).unwrap()).unwrap();
+        assert_eq!(claims["aud"], "https://updates.push.services.mozilla.com");
+        assert_eq!(claims["sub"], "mailto:ops@example.com");
+        signature::UnparsedPublicKey::new(
+            &signature::ECDSA_P256_SHA256_FIXED,
+            decode(VAPID_PUBLIC_KEY, "key").unwrap(),
+        )
+        .verify(
+            unsigned.as_bytes(),
+            &URL_SAFE_NO_PAD.decode(signature).unwrap(),
+        )
+        .unwrap();
+    }
+
+    /// A test to make sure invalid keys are rejected up front
+    #[test]
+    fn rejects_invalid_keys() {
+        assert!(Vapid::new(
+            VAPID_PRIVATE_KEY,
+            VAPID_PUBLIC_KEY,
+            "mailto:ops@example.com"
+        )
+        .is_err());
+
+        let vapid = Vapid::new(
+            VAPID_PUBLIC_KEY,
+            VAPID_PRIVATE_KEY,
+            "mailto:ops@example.com",
+        )
+        .unwrap();
+        assert!(WebPush::new("not base64!", "AAAAAAAAAAAAAAAAAAAAAA", vapid.clone()).is_err());
+        assert!(WebPush::new(VAPID_PRIVATE_KEY, "AAAAAAAAAAAAAAAAAAAAAA", vapid).is_err());
+    }
+}
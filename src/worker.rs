@@ -0,0 +1,362 @@
+use crate::time::SystemTime;
+#[cfg(feature = "sink")]
+use crate::Error;
+use crate::{Digest, Endpoint, Notification, Notifier};
+#[cfg(feature = "sink")]
+use futures_sink::Sink;
+#[cfg(feature = "sink")]
+use std::pin::Pin;
+use std::sync::Arc;
+#[cfg(feature = "sink")]
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+#[cfg(feature = "sink")]
+use tokio_util::sync::PollSender;
+
+/// How many notifications a `Worker` queues by default before dropping new ones
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// A handle to a background task delivering notifications to a destination,
+/// so they can be queued from synchronous code without waiting on delivery
+///
+/// Cloning the handle shares the same task, which stops once every handle is dropped.
+#[derive(Clone)]
+pub struct Worker {
+    queue: mpsc::Sender<Notification>,
+    digest: Option<Arc<Digest>>,
+}
+impl Worker {
+    /// Spawn a `Worker` on the current tokio runtime, sending through
+    /// a given `Notifier` to a given destination
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn(notifier: Arc<Notifier>, destination: Endpoint) -> Self {
+        Self::spawn_with_capacity(notifier, destination, DEFAULT_CAPACITY)
+    }
+
+    /// Spawn a `Worker` queueing up to `capacity` notifications
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn_with_capacity(
+        notifier: Arc<Notifier>,
+        destination: Endpoint,
+        capacity: usize,
+    ) -> Self {
+        let (queue, mut received) = mpsc::channel(capacity.max(1));
+        tokio::spawn(async move {
+            while let Some(notification) = received.recv().await {
+                // Failures are already handled by the `Notifier`
+                // (spool, metrics and `after_send` hooks)
+                let _ = notifier.send(notification, &destination).await;
+            }
+        });
+
+        Self {
+            queue,
+            digest: None,
+        }
+    }
+
+    /// Hold low severity notifications for a periodic summary instead of
+    /// sending each one, see `Digest`
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn with_digest(mut self, digest: Digest) -> Self {
+        let digest = Arc::new(digest);
+        // Only hold a weak handle so the `Worker` still stops once every handle is dropped
+        let queue = self.queue.downgrade();
+        let summaries = Arc::clone(&digest);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(summaries.interval());
+            // The first tick completes immediately
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let Some(queue) = queue.upgrade() else {
+                    break;
+                };
+                if let Some(summary) = summaries.summary() {
+                    let _ = queue.try_send(summary);
+                }
+            }
+        });
+
+        self.digest = Some(digest);
+        self
+    }
+
+    /// Queue a `Notification` for delivery
+    ///
+    /// Returns `false` if it was dropped because the queue is full.
+    pub fn notify(&self, notification: Notification) -> bool {
+        let notification = match &self.digest {
+            Some(digest) => match digest.collect(notification) {
+                Some(notification) => notification,
+                None => return true,
+            },
+            None => notification,
+        };
+        self.queue.try_send(notification).is_ok()
+    }
+
+    /// Queue a `Notification` for delivery once `delay` has passed, e.g. as a
+    /// reminder that's cancelled if the issue is resolved in the meantime
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn notify_after(&self, delay: Duration, notification: Notification) -> Scheduled {
+        let worker = self.clone();
+        // Counted from now, rather than from when the task is first polled
+        let deadline = tokio::time::Instant::now() + delay;
+        let task = tokio::spawn(async move {
+            tokio::time::sleep_until(deadline).await;
+            worker.notify(notification);
+        });
+
+        Scheduled {
+            task: task.abort_handle(),
+        }
+    }
+
+    /// Queue a `Notification` for delivery at a given time, or right
+    /// away if it has already passed
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn notify_at(&self, time: SystemTime, notification: Notification) -> Scheduled {
+        let delay = time
+            .duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO);
+        self.notify_after(delay, notification)
+    }
+}
+
+/// A `Worker` as a `Sink` of notifications, so it can end a stream pipeline
+/// (`stream.forward(sink)`), created with `Worker::into_sink`
+///
+/// Unlike `Worker::notify`, sending waits for room in the queue instead of
+/// dropping notifications when it's full.
+#[cfg(feature = "sink")]
+pub struct WorkerSink {
+    queue: PollSender<Notification>,
+    digest: Option<Arc<Digest>>,
+}
+#[cfg(feature = "sink")]
+impl Worker {
+    /// Turn the `Worker` into a `Sink` of notifications, see `WorkerSink`
+    pub fn into_sink(self) -> WorkerSink {
+        WorkerSink {
+            queue: PollSender::new(self.queue),
+            digest: self.digest,
+        }
+    }
+}
+#[cfg(feature = "sink")]
+impl Sink<Notification> for WorkerSink {
+    type Error = Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.queue
+            .poll_reserve(cx)
+            .map_err(|_| Error::WorkerStopped)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, notification: Notification) -> Result<(), Error> {
+        let notification = match &self.digest {
+            Some(digest) => match digest.collect(notification) {
+                Some(notification) => notification,
+                None => {
+                    // Held for the digest, so give back the reserved room
+                    self.queue.abort_send();
+                    return Ok(());
+                }
+            },
+            None => notification,
+        };
+        self.queue
+            .send_item(notification)
+            .map_err(|_| Error::WorkerStopped)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.queue.close();
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A handle to a `Notification` scheduled with `Worker::notify_after` or
+/// `Worker::notify_at`, dropping it leaves the notification scheduled
+pub struct Scheduled {
+    task: AbortHandle,
+}
+impl Scheduled {
+    /// Cancel the notification, if it hasn't been queued for delivery yet
+    pub fn cancel(&self) {
+        self.task.abort();
+    }
+
+    /// Whether the notification has been queued for delivery (or cancelled)
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockDestination;
+    use crate::{Context, ContextKind, Digest, Notification, Notifier, Severity, Worker};
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+
+    /// A test to make sure queued notifications are delivered in the background
+    #[tokio::test]
+    async fn delivers_in_background() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+
+        assert!(worker.notify(Notification {
+            message: String::from("External API Error: Could not find API Keys"),
+            timestamp: String::from("2024-01-19 19:26:20.022233"),
+            context: vec![Context {
+                label: String::from("Customer ID"),
+                value: String::from("0"),
+                kind: ContextKind::Text,
+            }],
+            severity: Severity::Error,
+            idempotency_key: None,
+            fingerprint: None,
+            sections: Vec::new(),
+            attachments: Vec::new(),
+            labels: None,
+            channel: None,
+            id: None,
+        }));
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        mock.assert_delivered("External API Error: Could not find API Keys");
+    }
+
+    /// A test to make sure scheduled notifications wait for their delay
+    #[tokio::test(start_paused = true)]
+    async fn delivers_after_delay() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+
+        let scheduled = worker.notify_after(
+            Duration::from_secs(30 * 60),
+            Notification::new("Incident still unresolved"),
+        );
+        tokio::time::advance(Duration::from_secs(29 * 60)).await;
+        assert!(!scheduled.is_finished());
+        assert!(mock.is_empty());
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        assert!(scheduled.is_finished());
+        mock.assert_delivered("Incident still unresolved");
+    }
+
+    /// A test to make sure notifications scheduled at a time wait until then
+    #[tokio::test(start_paused = true)]
+    async fn delivers_at_time() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+
+        let at = SystemTime::now() + Duration::from_secs(30 * 60);
+        let scheduled = worker.notify_at(at, Notification::new("Incident still unresolved"));
+        tokio::time::advance(Duration::from_secs(29 * 60)).await;
+        assert!(mock.is_empty());
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        assert!(scheduled.is_finished());
+
+        let past = SystemTime::now() - Duration::from_secs(60);
+        worker.notify_at(past, Notification::new("Deploy overdue"));
+        while mock.len() < 2 {
+            tokio::task::yield_now().await;
+        }
+        mock.assert_delivered("Deploy overdue");
+    }
+
+    /// A test to make sure cancelled notifications are never delivered
+    #[tokio::test(start_paused = true)]
+    async fn can_cancel_scheduled() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint());
+
+        let scheduled = worker.notify_after(
+            Duration::from_secs(30 * 60),
+            Notification::new("Incident still unresolved"),
+        );
+        scheduled.cancel();
+        tokio::time::advance(Duration::from_secs(60 * 60)).await;
+        tokio::task::yield_now().await;
+        assert!(scheduled.is_finished());
+        assert!(mock.is_empty());
+    }
+
+    /// A test to make sure a digest summarizes warnings but sends errors immediately
+    #[tokio::test]
+    async fn digests_low_severity() {
+        let mock = MockDestination::new();
+        let worker = Worker::spawn(Arc::new(Notifier::new()), mock.endpoint())
+            .with_digest(Digest::new(Duration::from_millis(20)));
+
+        let mut warning = Notification::new("Slow query");
+        warning.severity = Severity::Warning;
+        assert!(worker.notify(warning.clone()));
+        assert!(worker.notify(warning));
+        assert!(worker.notify(Notification::new("Database down")));
+        while mock.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        mock.assert_delivered("Database down");
+        assert_eq!(mock.len(), 1);
+
+        while mock.len() < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(
+            mock.delivered()[1].message,
+            "2 warnings in the last 20ms, top 1: `Slow query` (2)"
+        );
+    }
+
+    /// A test to make sure a stream can be forwarded into a worker with a small queue
+    #[cfg(feature = "sink")]
+    #[tokio::test]
+    async fn forwards_stream_into_sink() {
+        use futures_util::{stream, StreamExt};
+
+        let mock = MockDestination::new();
+        let sink =
+            Worker::spawn_with_capacity(Arc::new(Notifier::new()), mock.endpoint(), 1).into_sink();
+
+        let notifications = (0..5).map(|idx| Ok(Notification::new(format!("Event {idx}"))));
+        stream::iter(notifications).forward(sink).await.unwrap();
+        while mock.len() < 5 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(mock.delivered()[4].message, "Event 4");
+    }
+}
@@ -0,0 +1,81 @@
+use crate::render::variable_name;
+use crate::{Error, Formatter, Notification, RenderedPayload};
+use serde_json::{Map, Value};
+
+/// Renders notifications as Slack Workflow Builder variables, as `MessageFormat::Workflow`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct WorkflowFormatter;
+impl Formatter for WorkflowFormatter {
+    fn render(&self, notification: &Notification) -> Result<RenderedPayload, Error> {
+        let payload = notification.clone().into_workflow_payload();
+        Ok(RenderedPayload::json(payload.to_string()))
+    }
+}
+
+impl Notification {
+    /// Consume the `Notification` and parse it into a Slack Workflow Builder
+    /// webhook payload: a flat object of text variables
+    ///
+    /// The variables are `message`, `timestamp`, `severity` and `channel` (if
+    /// set), then one per
+    /// context entry named after its label in snake case (e.g. `Order ID` is
+    /// `order_id`), sections included. Labels repeating a variable are skipped.
+    pub(crate) fn into_workflow_payload(self) -> Value {
+        let mut variables = Map::new();
+        variables.insert(String::from("message"), Value::String(self.message));
+        variables.insert(String::from("timestamp"), Value::String(self.timestamp));
+        variables.insert(
+            String::from("severity"),
+            Value::String(format!("{:?}", self.severity)),
+        );
+        if let Some(channel) = self.channel {
+            variables.insert(String::from("channel"), Value::String(channel));
+        }
+
+        let contexts = self
+            .context
+            .into_iter()
+            .chain(self.sections.into_iter().flat_map(|s| s.context));
+        for ctx in contexts {
+            let name = variable_name(&ctx.label);
+            if !name.is_empty() {
+                variables.entry(name).or_insert(Value::String(ctx.value));
+            }
+        }
+
+        Value::Object(variables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MessageFormat, Notification, OversizePolicy, Section};
+    use serde_json::Value;
+
+    /// A test to make sure notifications map onto flat workflow variables
+    #[test]
+    fn can_parse_into_workflow_variables() {
+        let mut notification = Notification::new("Payment failed")
+            .with_context("Order ID", 1042)
+            .with_context("Message", "card declined")
+            .with_section(Section::new("Request").with_context("HTTP Method", "POST"));
+        notification.timestamp = String::from("2024-01-19 19:26:20.022233");
+
+        let payload: Value = serde_json::from_str(
+            &notification
+                .into_payload(MessageFormat::Workflow, OversizePolicy::Reject)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "message": "Payment failed",
+                "timestamp": "2024-01-19 19:26:20.022233",
+                "severity": "Error",
+                "order_id": "1042",
+                "http_method": "POST",
+            })
+        );
+    }
+}